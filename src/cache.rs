@@ -0,0 +1,137 @@
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use reqwest::Url;
+
+// ResponseCache keeps the raw bodies of GET responses keyed by path and
+// query, it is disabled until a ttl is set
+#[derive(Debug, Default)]
+pub struct ResponseCache {
+    ttl: Option<Duration>,
+    entries: Mutex<HashMap<String, (Instant, Vec<u8>)>>,
+}
+
+impl ResponseCache {
+    pub fn set_ttl(&mut self, ttl: Option<Duration>) {
+        self.ttl = ttl;
+        self.clear();
+    }
+
+    pub fn ttl(&self) -> Option<Duration> {
+        self.ttl
+    }
+
+    pub fn get(&self, url: &Url) -> Option<Vec<u8>> {
+        let ttl = self.ttl?;
+        let mut entries = self.entries.lock().unwrap();
+        let key = cache_key(url);
+
+        match entries.get(&key) {
+            Some((created, body)) if created.elapsed() < ttl => Some(body.clone()),
+            Some(_) => {
+                entries.remove(&key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    pub fn insert(&self, url: &Url, body: Vec<u8>) {
+        if self.ttl.is_none() {
+            return;
+        }
+
+        self.entries
+            .lock()
+            .unwrap()
+            .insert(cache_key(url), (Instant::now(), body));
+    }
+
+    pub fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+
+    // removes all entries of paths starting with path_prefix
+    pub fn invalidate(&self, path_prefix: &str) {
+        self.entries
+            .lock()
+            .unwrap()
+            .retain(|key, _| !key.starts_with(path_prefix));
+    }
+
+    // removes the entries of the resource a write went to, a PUT to
+    // /dna/intent/api/v1/network-device/{id} drops the cached device lists of
+    // every API version but keeps e.g. the cached sites
+    pub fn invalidate_resource(&self, path: &str) {
+        let written = resource(path);
+        self.entries
+            .lock()
+            .unwrap()
+            .retain(|key, _| resource(key) != written);
+    }
+}
+
+// the path segment after the API version, e.g. network-device, or the whole
+// path if it has no version
+fn resource(path: &str) -> &str {
+    let path = path.split('?').next().unwrap_or(path);
+    let mut segments = path.split('/');
+    segments
+        .find(|s| s.len() > 1 && s.starts_with('v') && s[1..].chars().all(|c| c.is_ascii_digit()))
+        .and_then(|_| segments.next())
+        .unwrap_or(path)
+}
+
+fn cache_key(url: &Url) -> String {
+    match url.query() {
+        Some(query) => format!("{}?{query}", url.path()),
+        None => url.path().to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache() {
+        let mut cache = ResponseCache::default();
+        let url = Url::parse("https://dnac/dna/intent/api/v1/network-device?limit=500").unwrap();
+
+        cache.insert(&url, b"{}".to_vec());
+        assert_eq!(cache.get(&url), None);
+
+        cache.set_ttl(Some(Duration::from_secs(60)));
+        cache.insert(&url, b"{}".to_vec());
+        assert_eq!(cache.get(&url), Some(b"{}".to_vec()));
+
+        cache.invalidate("/dna/intent/api/v1/network-device");
+        assert_eq!(cache.get(&url), None);
+    }
+
+    #[test]
+    fn test_invalidate_resource() {
+        let mut cache = ResponseCache::default();
+        cache.set_ttl(Some(Duration::from_secs(60)));
+        let devices =
+            Url::parse("https://dnac/dna/intent/api/v1/network-device?limit=500").unwrap();
+        let poller =
+            Url::parse("https://dnac/dna/intent/api/v1/network-device-poller/cli/legit-reads")
+                .unwrap();
+        let sites = Url::parse("https://dnac/dna/intent/api/v2/site").unwrap();
+        for url in [&devices, &poller, &sites] {
+            cache.insert(url, b"{}".to_vec());
+        }
+
+        cache.invalidate_resource("/dna/intent/api/v2/network-device/3f0ac5d4/sync");
+        assert_eq!(cache.get(&devices), None);
+        assert!(cache.get(&poller).is_some());
+        assert!(cache.get(&sites).is_some());
+
+        cache.invalidate_resource("/dna/intent/api/v1/site/a4f3a3c2");
+        assert_eq!(cache.get(&sites), None);
+    }
+}
@@ -0,0 +1,611 @@
+use std::collections::BTreeSet;
+
+use serde::Serialize;
+
+use crate::dnac::SUPPORTED_VERSIONS;
+
+// Endpoint is an API endpoint the crate has a typed implementation for, ids
+// in the path are written as {id}
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Endpoint {
+    pub module: &'static str,
+    pub method: &'static str,
+    pub path: &'static str,
+}
+
+// Capabilities lists the endpoints the crate supports for a DNAC version,
+// anything else has to be called via get_raw/post_raw
+#[derive(Debug, Clone, Serialize)]
+pub struct Capabilities {
+    pub crate_version: &'static str,
+    pub dnac_version: Option<String>,
+    pub endpoints: Vec<Endpoint>,
+}
+
+macro_rules! endpoint {
+    ($module:literal, $method:literal, $path:literal) => {
+        Endpoint {
+            module: $module,
+            method: $method,
+            path: $path,
+        }
+    };
+}
+
+// the registry of all endpoints, keep in sync when adding API calls
+const ENDPOINTS: &[Endpoint] = &[
+    endpoint!("auth", "POST", "/dna/system/api/v1/auth/token"),
+    endpoint!("tasks", "GET", "/api/v1/task/{id}/tree"),
+    endpoint!("platform", "GET", "/dna/intent/api/v1/dnac-release"),
+    endpoint!("devices", "GET", "/dna/intent/api/v1/network-device"),
+    endpoint!("devices", "POST", "/dna/intent/api/v1/network-device"),
+    endpoint!("devices", "PUT", "/dna/intent/api/v1/network-device"),
+    endpoint!("devices", "PUT", "/dna/intent/api/v1/network-device/sync"),
+    endpoint!("devices", "PUT", "/dna/intent/api/v1/network-device/brief"),
+    endpoint!(
+        "devices",
+        "GET",
+        "/dna/intent/api/v1/networkDevices/{id}/assignedToSite"
+    ),
+    endpoint!("devices", "POST", "/dna/intent/api/v1/network-device/file"),
+    endpoint!("file", "GET", "/dna/intent/api/v1/file/{id}"),
+    endpoint!(
+        "devices",
+        "GET",
+        "/dna/intent/api/v1/network-device/user-defined-field"
+    ),
+    endpoint!(
+        "devices",
+        "POST",
+        "/dna/intent/api/v1/network-device/user-defined-field"
+    ),
+    endpoint!(
+        "devices",
+        "PUT",
+        "/dna/intent/api/v1/network-device/{id}/user-defined-field"
+    ),
+    endpoint!("devices", "GET", "/dna/intent/api/v1/network-device/module"),
+    endpoint!("devices", "GET", "/dna/intent/api/v1/network-device/config"),
+    endpoint!(
+        "devices",
+        "GET",
+        "/dna/intent/api/v1/network-device/{id}/config"
+    ),
+    endpoint!(
+        "devices",
+        "GET",
+        "/dna/intent/api/v1/network-device/{id}/stack"
+    ),
+    endpoint!(
+        "devices",
+        "PUT",
+        "/dna/intent/api/v1/networkDevices/{id}/resyncIntervalSettings"
+    ),
+    endpoint!("devices", "GET", "/dna/intent/api/v1/network-device/{id}"),
+    endpoint!(
+        "devices",
+        "DELETE",
+        "/dna/intent/api/v1/network-device/{id}"
+    ),
+    endpoint!(
+        "devices",
+        "GET",
+        "/dna/intent/api/v1/network-device/serial-number/{id}"
+    ),
+    endpoint!(
+        "command_runner",
+        "POST",
+        "/dna/intent/api/v1/network-device-poller/cli/read-request"
+    ),
+    endpoint!(
+        "command_runner",
+        "GET",
+        "/dna/intent/api/v1/network-device-poller/cli/legit-reads"
+    ),
+    endpoint!(
+        "maintenance",
+        "GET",
+        "/dna/intent/api/v1/networkDeviceMaintenanceSchedules"
+    ),
+    endpoint!(
+        "maintenance",
+        "POST",
+        "/dna/intent/api/v1/networkDeviceMaintenanceSchedules"
+    ),
+    endpoint!(
+        "maintenance",
+        "DELETE",
+        "/dna/intent/api/v1/networkDeviceMaintenanceSchedules/{id}"
+    ),
+    endpoint!("compliance", "GET", "/dna/intent/api/v1/compliance"),
+    endpoint!("compliance", "POST", "/dna/intent/api/v1/compliance/"),
+    endpoint!(
+        "compliance",
+        "GET",
+        "/dna/intent/api/v1/compliance/{id}/detail"
+    ),
+    endpoint!(
+        "config_archive",
+        "GET",
+        "/dna/intent/api/v1/networkDeviceConfigFiles"
+    ),
+    endpoint!(
+        "config_archive",
+        "POST",
+        "/dna/intent/api/v1/networkDeviceConfigFiles/{id}/downloadMasked"
+    ),
+    endpoint!(
+        "config_archive",
+        "POST",
+        "/dna/intent/api/v1/network-device-archive/cleartext"
+    ),
+    endpoint!("discovery", "POST", "/dna/intent/api/v1/discovery"),
+    endpoint!("discovery", "GET", "/dna/intent/api/v1/discovery/{id}"),
+    endpoint!("discovery", "PUT", "/dna/intent/api/v1/discovery"),
+    endpoint!("discovery", "GET", "/dna/intent/api/v1/discovery/{id}/{id}"),
+    endpoint!("discovery", "DELETE", "/dna/intent/api/v1/discovery/{id}"),
+    endpoint!(
+        "discovery",
+        "GET",
+        "/dna/intent/api/v1/discovery/{id}/network-device"
+    ),
+    endpoint!("sites", "GET", "/dna/intent/api/v2/site"),
+    endpoint!("sites", "POST", "/dna/intent/api/v1/site"),
+    endpoint!("sites", "GET", "/dna/intent/api/v1/sites"),
+    endpoint!("sites", "POST", "/dna/intent/api/v1/sites/bulk"),
+    endpoint!("sites", "PUT", "/dna/intent/api/v1/site/{id}"),
+    endpoint!("sites", "DELETE", "/dna/intent/api/v1/site/{id}"),
+    endpoint!("sites", "GET", "/dna/intent/api/v1/site-member/{id}/member"),
+    endpoint!("sites", "GET", "/dna/intent/api/v1/membership/{id}"),
+    endpoint!(
+        "sites",
+        "POST",
+        "/dna/intent/api/v1/assign-device-to-site/{id}/device"
+    ),
+    endpoint!(
+        "sites",
+        "POST",
+        "/dna/intent/api/v1/credential-to-site/{id}"
+    ),
+    endpoint!(
+        "sites",
+        "PUT",
+        "/dna/intent/api/v1/sites/{id}/deviceCredentials"
+    ),
+    endpoint!("maps", "POST", "/dna/intent/api/v1/maps/import/start"),
+    endpoint!(
+        "maps",
+        "POST",
+        "/dna/intent/api/v1/maps/import/{id}/perform"
+    ),
+    endpoint!("maps", "POST", "/dna/intent/api/v1/maps/export/{id}"),
+    endpoint!(
+        "executions",
+        "GET",
+        "/dna/platform/management/business-api/v1/execution-status/{id}"
+    ),
+    endpoint!("profile", "GET", "/dna/intent/api/v1/network"),
+    endpoint!("profile", "POST", "/dna/intent/api/v1/network/{id}"),
+    endpoint!("profile", "GET", "/dna/intent/api/v2/global-credential"),
+    endpoint!("profile", "GET", "/dna/intent/api/v1/enterprise-ssid"),
+    endpoint!("profile", "POST", "/dna/intent/api/v1/enterprise-ssid"),
+    endpoint!(
+        "telemetry",
+        "GET",
+        "/dna/intent/api/v1/sites/{id}/telemetrySettings"
+    ),
+    endpoint!(
+        "templates",
+        "GET",
+        "/dna/intent/api/v1/template-programmer/project"
+    ),
+    endpoint!(
+        "templates",
+        "GET",
+        "/dna/intent/api/v1/template-programmer/template"
+    ),
+    endpoint!(
+        "templates",
+        "GET",
+        "/dna/intent/api/v1/template-programmer/template/{id}"
+    ),
+    endpoint!(
+        "templates",
+        "POST",
+        "/dna/intent/api/v1/template-programmer/project/{id}/template"
+    ),
+    endpoint!(
+        "templates",
+        "PUT",
+        "/dna/intent/api/v1/template-programmer/template"
+    ),
+    endpoint!(
+        "templates",
+        "POST",
+        "/dna/intent/api/v1/template-programmer/template/version"
+    ),
+    endpoint!(
+        "templates",
+        "POST",
+        "/dna/intent/api/v2/template-programmer/template/deploy"
+    ),
+    endpoint!(
+        "templates",
+        "PUT",
+        "/dna/intent/api/v1/template-programmer/template/preview"
+    ),
+    endpoint!(
+        "templates",
+        "POST",
+        "/dna/intent/api/v1/template-programmer/template/name/exportTemplates"
+    ),
+    endpoint!(
+        "templates",
+        "POST",
+        "/dna/intent/api/v1/template-programmer/project/name/exportprojects"
+    ),
+    endpoint!(
+        "templates",
+        "POST",
+        "/dna/intent/api/v1/template-programmer/project/importprojects"
+    ),
+    endpoint!(
+        "templates",
+        "GET",
+        "/dna/intent/api/v1/template-programmer/template/deploy/status/{id}"
+    ),
+    endpoint!(
+        "pnp",
+        "POST",
+        "/dna/intent/api/v1/onboarding/pnp-device/site-claim"
+    ),
+    endpoint!(
+        "pnp",
+        "POST",
+        "/dna/intent/api/v1/onboarding/pnp-device/import"
+    ),
+    endpoint!(
+        "pnp",
+        "DELETE",
+        "/dna/intent/api/v1/onboarding/pnp-device/{id}"
+    ),
+    endpoint!(
+        "pnp",
+        "POST",
+        "/dna/intent/api/v1/onboarding/pnp-device/unclaim"
+    ),
+    endpoint!(
+        "pnp",
+        "POST",
+        "/dna/intent/api/v1/onboarding/pnp-device/reset"
+    ),
+    endpoint!("pnp", "GET", "/dna/intent/api/v1/onboarding/pnp-workflow"),
+    endpoint!("pnp", "POST", "/dna/intent/api/v1/onboarding/pnp-workflow"),
+    endpoint!(
+        "pnp",
+        "PUT",
+        "/dna/intent/api/v1/onboarding/pnp-workflow/{id}"
+    ),
+    endpoint!(
+        "pnp",
+        "DELETE",
+        "/dna/intent/api/v1/onboarding/pnp-workflow/{id}"
+    ),
+    endpoint!("pnp", "GET", "/dna/intent/api/v1/onboarding/pnp-settings"),
+    endpoint!("pnp", "PUT", "/dna/intent/api/v1/onboarding/pnp-settings"),
+    endpoint!(
+        "pnp",
+        "POST",
+        "/dna/intent/api/v1/onboarding/pnp-device/vacct-sync"
+    ),
+    endpoint!(
+        "pnp",
+        "GET",
+        "/dna/intent/api/v1/onboarding/pnp-settings/sacct/{id}/vacct/{id}/sync-result"
+    ),
+    endpoint!("swim", "GET", "/dna/intent/api/v1/image/importation"),
+    endpoint!(
+        "swim",
+        "POST",
+        "/dna/intent/api/v1/image/importation/source/url"
+    ),
+    endpoint!(
+        "swim",
+        "POST",
+        "/dna/intent/api/v1/image/importation/source/file"
+    ),
+    endpoint!(
+        "swim",
+        "POST",
+        "/dna/intent/api/v1/image/importation/golden"
+    ),
+    endpoint!("swim", "POST", "/dna/intent/api/v1/image/distribution"),
+    endpoint!("swim", "POST", "/dna/intent/api/v1/image/activation/device"),
+    endpoint!("swim", "GET", "/dna/intent/api/v1/image/task"),
+    endpoint!(
+        "swim",
+        "GET",
+        "/dna/intent/api/v1/image/importation/golden/site/{id}/family/{id}/role/{id}/image/{id}"
+    ),
+    endpoint!(
+        "swim",
+        "DELETE",
+        "/dna/intent/api/v1/image/importation/golden/site/{id}/family/{id}/role/{id}/image/{id}"
+    ),
+    endpoint!("health", "GET", "/dna/intent/api/v1/device-health"),
+    endpoint!("health", "GET", "/dna/intent/api/v1/site-health"),
+    endpoint!("health", "GET", "/dna/intent/api/v1/network-health"),
+    endpoint!("health", "GET", "/dna/intent/api/v1/healthScoreDefinitions"),
+    endpoint!(
+        "health",
+        "PUT",
+        "/dna/intent/api/v1/healthScoreDefinitions/{id}"
+    ),
+    endpoint!("clients", "GET", "/dna/intent/api/v1/client-health"),
+    endpoint!("clients", "GET", "/dna/intent/api/v1/client-detail"),
+    endpoint!("pathtrace", "POST", "/dna/intent/api/v1/flow-analysis"),
+    endpoint!("pathtrace", "GET", "/dna/intent/api/v1/flow-analysis/{id}"),
+    endpoint!(
+        "pathtrace",
+        "DELETE",
+        "/dna/intent/api/v1/flow-analysis/{id}"
+    ),
+    endpoint!("issues", "GET", "/dna/intent/api/v1/issues"),
+    endpoint!(
+        "assurance_events",
+        "GET",
+        "/dna/data/api/v1/assuranceEvents"
+    ),
+    endpoint!("events", "GET", "/dna/intent/api/v1/event/subscription"),
+    endpoint!("events", "POST", "/dna/intent/api/v1/event/subscription"),
+    endpoint!("events", "PUT", "/dna/intent/api/v1/event/subscription"),
+    endpoint!("events", "DELETE", "/dna/intent/api/v1/event/subscription"),
+    endpoint!("events", "GET", "/dna/system/api/v1/event/webhook"),
+    endpoint!("events", "POST", "/dna/system/api/v1/event/webhook"),
+    endpoint!("events", "PUT", "/dna/system/api/v1/event/webhook"),
+    endpoint!(
+        "issues",
+        "GET",
+        "/dna/intent/api/v1/issue-enrichment-details"
+    ),
+    endpoint!("issues", "POST", "/dna/data/api/v1/assuranceIssues/resolve"),
+    endpoint!(
+        "issues",
+        "POST",
+        "/dna/intent/api/v1/execute-suggested-actions-commands"
+    ),
+    endpoint!("issues", "POST", "/dna/data/api/v1/assuranceIssues/ignore"),
+    endpoint!(
+        "enrichment",
+        "GET",
+        "/dna/intent/api/v1/client-enrichment-details"
+    ),
+    endpoint!(
+        "enrichment",
+        "GET",
+        "/dna/intent/api/v1/device-enrichment-details"
+    ),
+    endpoint!("interfaces", "GET", "/dna/intent/api/v1/interface"),
+    endpoint!("interfaces", "PUT", "/dna/intent/api/v1/interface/{id}"),
+    endpoint!(
+        "interfaces",
+        "GET",
+        "/dna/intent/api/v1/interface/network-device/{id}"
+    ),
+    endpoint!(
+        "interfaces",
+        "GET",
+        "/dna/intent/api/v1/interface/network-device/{id}/interface-name"
+    ),
+    endpoint!(
+        "interfaces",
+        "GET",
+        "/dna/intent/api/v1/interface/ip-address/{id}"
+    ),
+    endpoint!(
+        "interfaces",
+        "POST",
+        "/dna/data/api/v1/interfaces/{id}/trendAnalytics"
+    ),
+    endpoint!(
+        "wireless",
+        "GET",
+        "/dna/intent/api/v1/wireless/accesspoint-configuration/summary"
+    ),
+    endpoint!(
+        "wireless",
+        "POST",
+        "/dna/intent/api/v1/device-reboot/apreboot"
+    ),
+    endpoint!(
+        "wireless",
+        "GET",
+        "/dna/intent/api/v1/device-reboot/apreboot/status"
+    ),
+    endpoint!(
+        "wireless",
+        "POST",
+        "/dna/intent/api/v1/wirelessAccessPoints/factoryResetRequest/provision"
+    ),
+    endpoint!(
+        "wireless",
+        "GET",
+        "/dna/intent/api/v1/wirelessAccessPoints/factoryResetRequestStatus"
+    ),
+    endpoint!(
+        "topology",
+        "GET",
+        "/dna/intent/api/v1/topology/physical-topology"
+    ),
+    endpoint!("topology", "GET", "/dna/intent/api/v1/topology/l2/{id}"),
+    endpoint!("topology", "GET", "/dna/intent/api/v1/topology/l3/{id}"),
+    endpoint!(
+        "topology",
+        "GET",
+        "/dna/intent/api/v1/topology/vlan/vlan-names"
+    ),
+    endpoint!(
+        "topology",
+        "GET",
+        "/dna/intent/api/v1/topology/site-topology"
+    ),
+];
+
+impl Capabilities {
+    // an unknown version lists all endpoints, an unsupported one none
+    pub fn for_version(dnac_version: Option<&str>) -> Self {
+        let supported = dnac_version
+            .is_none_or(|version| SUPPORTED_VERSIONS.iter().any(|v| version.contains(v)));
+
+        let endpoints = if supported {
+            ENDPOINTS
+                .iter()
+                .filter(|e| module_enabled(e.module))
+                .filter(|e| {
+                    dnac_version.is_none()
+                        || !is_sites_v2(e.path)
+                        || sites_v2_supported(dnac_version)
+                })
+                .copied()
+                .collect()
+        } else {
+            vec![]
+        };
+
+        Self {
+            crate_version: env!("CARGO_PKG_VERSION"),
+            dnac_version: dnac_version.map(String::from),
+            endpoints,
+        }
+    }
+
+    pub fn supports(&self, method: &str, path: &str) -> bool {
+        let path = path.split('?').next().unwrap_or_default();
+        self.endpoints
+            .iter()
+            .any(|e| e.method.eq_ignore_ascii_case(method) && path_matches(e.path, path))
+    }
+
+    // sorted and without duplicates
+    pub fn modules(&self) -> Vec<&'static str> {
+        let modules: BTreeSet<&'static str> = self.endpoints.iter().map(|e| e.module).collect();
+        modules.into_iter().collect()
+    }
+}
+
+// the modules per cargo feature, a table instead of a match as the cfg!
+// values are constants and all false without default features
+const MODULE_FEATURES: &[(&[&str], bool)] = &[
+    (
+        &[
+            "devices",
+            "discovery",
+            "compliance",
+            "config_archive",
+            "topology",
+            "interfaces",
+            "maintenance",
+            "command_runner",
+            "pnp",
+            "pathtrace",
+            "enrichment",
+        ],
+        cfg!(feature = "devices"),
+    ),
+    (&["sites", "profile", "maps"], cfg!(feature = "sites")),
+    (
+        &[
+            "telemetry",
+            "health",
+            "clients",
+            "issues",
+            "assurance_events",
+        ],
+        cfg!(feature = "assurance"),
+    ),
+    (&["events"], cfg!(feature = "core")),
+    (&["wireless"], cfg!(feature = "wireless")),
+    (&["templates"], cfg!(feature = "templates")),
+    (&["swim"], cfg!(feature = "swim")),
+];
+
+// modules behind a disabled cargo feature are not compiled, so they are not
+// supported, modules without a feature are always enabled
+fn module_enabled(module: &str) -> bool {
+    MODULE_FEATURES
+        .iter()
+        .find(|(modules, _)| modules.contains(&module))
+        .map(|(_, enabled)| *enabled)
+        .unwrap_or(true)
+}
+
+// the new sites API, only available on the versions SitesV2 supports
+const SITES_V2_PATH: &str = "/dna/intent/api/v1/sites";
+
+fn is_sites_v2(path: &str) -> bool {
+    path.strip_prefix(SITES_V2_PATH)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+}
+
+#[cfg(feature = "sites")]
+fn sites_v2_supported(dnac_version: Option<&str>) -> bool {
+    crate::sites::SitesV2::is_supported(dnac_version)
+}
+
+// the sites endpoints are filtered by their module without the feature
+#[cfg(not(feature = "sites"))]
+fn sites_v2_supported(_dnac_version: Option<&str>) -> bool {
+    false
+}
+
+fn path_matches(template: &str, path: &str) -> bool {
+    let template: Vec<&str> = template.trim_end_matches('/').split('/').collect();
+    let path: Vec<&str> = path.trim_end_matches('/').split('/').collect();
+
+    template.len() == path.len()
+        && template
+            .iter()
+            .zip(&path)
+            .all(|(t, p)| *t == "{id}" || t == p)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capabilities() {
+        let capabilities = Capabilities::for_version(Some("2.3.7.6-70045"));
+        // discovery is only compiled with the devices feature
+        assert_eq!(
+            capabilities.supports(
+                "get",
+                "/dna/intent/api/v1/discovery/12/network-device?limit=10"
+            ),
+            cfg!(feature = "devices")
+        );
+        assert!(capabilities.supports(
+            "GET",
+            "/api/v1/task/0193739c-0d88-78e4-ba0f-d82889fca555/tree/"
+        ));
+        assert!(!capabilities.supports("DELETE", "/dna/intent/api/v1/network-device"));
+        assert!(capabilities.modules().contains(&"platform"));
+        let modules = capabilities.modules();
+        assert!(modules.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(
+            capabilities.supports("GET", "/dna/intent/api/v1/sites"),
+            cfg!(feature = "sites")
+        );
+
+        // the new sites API was added with 2.3.7.6
+        let capabilities = Capabilities::for_version(Some("2.3.7.5"));
+        assert!(!capabilities.supports("GET", "/dna/intent/api/v1/sites"));
+        assert_eq!(
+            capabilities.supports("GET", "/dna/intent/api/v2/site"),
+            cfg!(feature = "sites")
+        );
+
+        let capabilities = Capabilities::for_version(Some("2.3.5.3"));
+        assert!(capabilities.endpoints.is_empty());
+    }
+}
@@ -0,0 +1,308 @@
+use serde::Deserialize;
+use thiserror::Error;
+use tracing::{event, Level};
+
+use crate::{
+    dnac::{ApiClient, ResponseType},
+    query::Query,
+};
+
+// ClientHealth is the client health of a site, the scores are split by
+// client type and each of them by score type, e.g. GOOD or POOR
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClientHealth {
+    // "global" for the whole network
+    pub site_id: Option<String>,
+    #[serde(default)]
+    pub score_detail: Vec<ClientScore>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClientScore {
+    pub score_category: ScoreCategory,
+    // the percentage of healthy clients, -1 if there is no data
+    pub score_value: Option<f64>,
+    pub client_count: Option<u32>,
+    pub client_unique_count: Option<u32>,
+    #[serde(default)]
+    pub score_list: Vec<ClientScore>,
+}
+
+// e.g. CLIENT_TYPE with WIRED or SCORE_TYPE with POOR
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScoreCategory {
+    pub score_category: String,
+    pub value: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClientType {
+    All,
+    Wired,
+    Wireless,
+}
+
+// ClientDetail is the assurance view of a single client, rssi and snr are
+// only set for wireless clients
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClientDetail {
+    pub id: Option<String>,
+    pub host_mac: String,
+    pub host_name: Option<String>,
+    pub host_ip_v4: Option<String>,
+    pub host_os: Option<String>,
+    // WIRED or WIRELESS
+    pub host_type: Option<String>,
+    pub user_id: Option<String>,
+    pub connection_status: Option<String>,
+    pub ssid: Option<String>,
+    pub vlan_id: Option<u32>,
+    pub frequency: Option<String>,
+    pub channel: Option<String>,
+    #[serde(default, deserialize_with = "lossy_f64")]
+    pub rssi: Option<f64>,
+    #[serde(default, deserialize_with = "lossy_f64")]
+    pub snr: Option<f64>,
+    pub location: Option<String>,
+    // the name of the AP or switch the client is connected to
+    pub client_connection: Option<String>,
+    pub port: Option<String>,
+    pub issue_count: Option<u32>,
+    #[serde(default)]
+    pub health_score: Vec<ClientHealthScore>,
+    #[serde(default)]
+    pub connected_device: Vec<ConnectedDevice>,
+    pub onboarding: Option<ClientOnboarding>,
+}
+
+// the health types are OVERALL, ONBOARDED and CONNECTED
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClientHealthScore {
+    pub health_type: String,
+    pub score: Option<f64>,
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ConnectedDevice {
+    pub id: Option<String>,
+    pub name: Option<String>,
+    pub mac: Option<String>,
+    // e.g. AP or SWITCH
+    #[serde(rename = "type")]
+    pub device_type: Option<String>,
+}
+
+// the durations are in milliseconds
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClientOnboarding {
+    #[serde(default, deserialize_with = "lossy_f64")]
+    pub average_run_duration: Option<f64>,
+    #[serde(default, deserialize_with = "lossy_f64")]
+    pub max_run_duration: Option<f64>,
+    #[serde(default, deserialize_with = "lossy_f64")]
+    pub average_assoc_duration: Option<f64>,
+    #[serde(default, deserialize_with = "lossy_f64")]
+    pub average_auth_duration: Option<f64>,
+    #[serde(default, deserialize_with = "lossy_f64")]
+    pub average_dhcp_duration: Option<f64>,
+    pub aaa_server_ip: Option<String>,
+    pub dhcp_server_ip: Option<String>,
+    #[serde(default)]
+    pub latest_root_cause_list: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ClientDetailResponse {
+    detail: ClientDetail,
+}
+
+#[derive(Debug, Error)]
+pub enum ClientError {
+    #[error("General Client Error")]
+    GeneralError,
+}
+
+impl ClientHealth {
+    // timestamp is in epoch milliseconds and defaults to the latest data
+    pub async fn get<C: ApiClient>(
+        dnac: &C,
+        timestamp: Option<u64>,
+    ) -> Result<Vec<ClientHealth>, ClientError> {
+        let path = "/dna/intent/api/v1/client-health";
+        let query = Query::new().eq_opt("timestamp", timestamp);
+
+        let health_data = dnac
+            .get::<ClientHealth>(path, Some(query.as_slice()), None)
+            .await;
+
+        match health_data {
+            Ok(health_data) => match health_data.response {
+                ResponseType::Array(data) => Ok(data),
+                ResponseType::Item(data) => Ok(vec![data]),
+            },
+            Err(e) => {
+                event!(Level::ERROR, "{e}");
+                Err(ClientError::GeneralError)
+            }
+        }
+    }
+
+    pub fn score(&self, client_type: ClientType) -> Option<&ClientScore> {
+        let value = match client_type {
+            ClientType::All => "ALL",
+            ClientType::Wired => "WIRED",
+            ClientType::Wireless => "WIRELESS",
+        };
+        self.score_detail
+            .iter()
+            .find(|s| s.score_category.value == value)
+    }
+}
+
+impl ClientDetail {
+    // timestamp is in epoch milliseconds and defaults to the latest data
+    pub async fn get<C: ApiClient>(
+        dnac: &C,
+        mac_address: &str,
+        timestamp: Option<u64>,
+    ) -> Result<ClientDetail, ClientError> {
+        let path = "/dna/intent/api/v1/client-detail";
+        let query = Query::new()
+            .eq("macAddress", mac_address)
+            .eq_opt("timestamp", timestamp);
+
+        // the detail isn't wrapped in a response field
+        let client_data = dnac.get_raw(path, Some(query.as_slice()), None).await;
+
+        match client_data {
+            Ok(client_data) => serde_json::from_value::<ClientDetailResponse>(client_data)
+                .map(|c| c.detail)
+                .map_err(|e| {
+                    event!(Level::ERROR, "{e}");
+                    ClientError::GeneralError
+                }),
+            Err(e) => {
+                event!(Level::ERROR, "{e}");
+                Err(ClientError::GeneralError)
+            }
+        }
+    }
+
+    pub fn overall_score(&self) -> Option<f64> {
+        self.health_score("OVERALL")
+    }
+
+    // scores of 0 mean the onboarding failed, see the root causes of the
+    // onboarding
+    pub fn onboarding_score(&self) -> Option<f64> {
+        self.health_score("ONBOARDED")
+    }
+
+    fn health_score(&self, health_type: &str) -> Option<f64> {
+        self.health_score
+            .iter()
+            .find(|s| s.health_type == health_type)
+            .and_then(|s| s.score)
+    }
+}
+
+// numbers are returned as strings in many fields, values which can't be
+// parsed (e.g. an empty string) are treated as missing
+fn lossy_f64<'de, D>(deserializer: D) -> Result<Option<f64>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Number {
+        Float(f64),
+        String(String),
+    }
+
+    Ok(match Option::<Number>::deserialize(deserializer)? {
+        Some(Number::Float(value)) => Some(value),
+        Some(Number::String(value)) => value.trim().parse().ok(),
+        None => None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::MockDnac;
+
+    #[tokio::test]
+    async fn test_client_health() {
+        let mock = MockDnac::start().await;
+        mock.mock_response(
+            "GET",
+            "/dna/intent/api/v1/client-health",
+            200,
+            r#"
+              {"response":[{"siteId":"global","scoreDetail":[
+                {"scoreCategory":{"scoreCategory":"CLIENT_TYPE","value":"ALL"},"scoreValue":88,"clientCount":412,"clientUniqueCount":412,"starttime":1732811400000,"endtime":1732811700000},
+                {"scoreCategory":{"scoreCategory":"CLIENT_TYPE","value":"WIRELESS"},"scoreValue":85,"clientCount":292,"clientUniqueCount":292,"scoreList":[
+                  {"scoreCategory":{"scoreCategory":"SCORE_TYPE","value":"POOR"},"scoreValue":-1,"clientCount":12},
+                  {"scoreCategory":{"scoreCategory":"SCORE_TYPE","value":"GOOD"},"scoreValue":-1,"clientCount":248}]}]}],
+               "version":"1.0"}
+              "#,
+        )
+        .await;
+
+        let health = ClientHealth::get(&mock.dnac, None).await.unwrap();
+        let wireless = health[0].score(ClientType::Wireless).unwrap();
+        assert_eq!(wireless.score_value, Some(85.0));
+        assert_eq!(wireless.score_list[0].client_count, Some(12));
+        assert!(health[0].score(ClientType::Wired).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_client_detail() {
+        let mock = MockDnac::start().await;
+        mock.mock_response(
+            "GET",
+            "/dna/intent/api/v1/client-detail",
+            200,
+            r#"
+              {"detail":{"id":"B8:27:EB:12:34:56","connectionStatus":"CONNECTED","hostType":"WIRELESS","userId":"jdoe","hostName":"jdoe-laptop","hostOs":"Windows 11","hostMac":"B8:27:EB:12:34:56","hostIpV4":"10.20.1.55","hostIpV6":[],"authType":"WPA2/WPA3+802.1x","vlanId":20,"ssid":"corp","frequency":"5.0","channel":"36","location":"Global/CH/ZRH/HQ/Floor 2","clientConnection":"zrh-ap-2-01","port":null,"issueCount":0,"rssi":"-61.0","snr":"34.0","dataRate":"866.7",
+                         "healthScore":[{"healthType":"OVERALL","reason":"","score":10},{"healthType":"ONBOARDED","reason":"","score":4},{"healthType":"CONNECTED","reason":"","score":10}],
+                         "connectedDevice":[{"type":"AP","name":"zrh-ap-2-01","mac":"70:1f:53:aa:10:80","id":"3f0ac5d4-6f3d-4b5e-9d2b-5b0f3a3c2f10"}],
+                         "onboarding":{"averageRunDuration":"1520.0","maxRunDuration":"2500.0","averageAssocDuration":"20.0","averageAuthDuration":"1200.0","averageDhcpDuration":"","aaaServerIp":"10.0.0.20","dhcpServerIp":"10.0.0.21","latestRootCauseList":[]}},
+               "connectionInfo":null,"topology":null}
+              "#,
+        )
+        .await;
+
+        let client = ClientDetail::get(&mock.dnac, "B8:27:EB:12:34:56", None)
+            .await
+            .unwrap();
+        assert_eq!(client.rssi, Some(-61.0));
+        assert_eq!(client.snr, Some(34.0));
+        assert_eq!(client.onboarding_score(), Some(4.0));
+        assert_eq!(
+            client.connected_device[0].device_type.as_deref(),
+            Some("AP")
+        );
+
+        let onboarding = client.onboarding.unwrap();
+        assert_eq!(onboarding.average_auth_duration, Some(1200.0));
+        assert_eq!(onboarding.average_dhcp_duration, None);
+
+        let requests = mock.server.received_requests().await.unwrap();
+        let request = requests
+            .iter()
+            .find(|r| r.url.path().ends_with("client-detail"))
+            .unwrap();
+        assert_eq!(
+            request.url.query(),
+            Some("macAddress=B8%3A27%3AEB%3A12%3A34%3A56")
+        );
+    }
+}
@@ -0,0 +1,343 @@
+use std::{collections::HashMap, time::Duration};
+
+use futures::{stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::{event, Level};
+
+use crate::{
+    dnac::{ApiClient, ApiClientExt, ResponseType},
+    file::File,
+    ids::DeviceId,
+};
+
+// Poller wraps the command runner API of the network-device-poller, which
+// runs read only commands on the devices
+pub struct Poller;
+
+// the command runner accepts at most this many devices and commands per request
+const MAX_DEVICES_PER_REQUEST: usize = 100;
+const MAX_COMMANDS_PER_REQUEST: usize = 5;
+// attempts per request before its commands are reported as failed
+const MAX_ATTEMPTS: usize = 3;
+// delay before the first retry, doubled for every further attempt
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(500);
+
+// CommandRunner runs commands on any number of devices by splitting them into
+// requests the API accepts
+pub struct CommandRunner;
+
+// the output of every command per device, or the reason it failed
+pub type BulkOutput = HashMap<DeviceId, HashMap<String, Result<String, String>>>;
+
+#[derive(Debug, Serialize)]
+struct ReadRequest<'a> {
+    commands: &'a [&'a str],
+    #[serde(rename = "deviceUuids")]
+    device_uuids: &'a [DeviceId],
+}
+
+// CommandOutput holds the outputs of one device, keyed by command
+#[derive(Debug, Deserialize)]
+pub struct CommandOutput {
+    #[serde(rename = "deviceUuid")]
+    pub device_id: DeviceId,
+    #[serde(rename = "commandResponses")]
+    pub responses: CommandResponses,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct CommandResponses {
+    #[serde(rename = "SUCCESS", default)]
+    pub success: HashMap<String, String>,
+    #[serde(rename = "FAILURE", default)]
+    pub failure: HashMap<String, String>,
+    // commands which are not allowed by the command runner
+    #[serde(rename = "BLACKLISTED", default)]
+    pub blacklisted: HashMap<String, String>,
+}
+
+#[derive(Debug, Error)]
+pub enum CommandError {
+    #[error("General Command Error")]
+    GeneralError,
+    #[error("Task failed")]
+    TaskFailed,
+}
+
+impl Poller {
+    // runs the commands on all devices and downloads the outputs once the
+    // task finished
+    pub async fn read_commands<C: ApiClientExt>(
+        dnac: &C,
+        device_ids: &[DeviceId],
+        commands: &[&str],
+    ) -> Result<Vec<CommandOutput>, CommandError> {
+        let path = "/dna/intent/api/v1/network-device-poller/cli/read-request";
+        let request = ReadRequest {
+            commands,
+            device_uuids: device_ids,
+        };
+
+        let task_info = dnac.post_task(path, request).await.map_err(|e| {
+            event!(Level::ERROR, "{e}");
+            CommandError::GeneralError
+        })?;
+        let tasks = dnac.poll_task(task_info).await.map_err(|e| {
+            event!(Level::ERROR, "{e}");
+            CommandError::TaskFailed
+        })?;
+
+        let file = File::from_tasks(dnac, &tasks).await.map_err(|e| {
+            event!(Level::ERROR, "{e}");
+            CommandError::GeneralError
+        })?;
+
+        serde_json::from_slice(&file).map_err(|e| {
+            event!(Level::ERROR, "{e}");
+            CommandError::GeneralError
+        })
+    }
+
+    // the keywords commands have to start with, e.g. show or ping
+    pub async fn legit_reads<C: ApiClient>(dnac: &C) -> Result<Vec<String>, CommandError> {
+        let path = "/dna/intent/api/v1/network-device-poller/cli/legit-reads";
+
+        let legit_data = dnac.get::<String>(path, None, None).await;
+
+        match legit_data {
+            Ok(legit_data) => match legit_data.response {
+                ResponseType::Array(data) => Ok(data),
+                ResponseType::Item(data) => Ok(vec![data]),
+            },
+            Err(e) => {
+                event!(Level::ERROR, "{e}");
+                Err(CommandError::GeneralError)
+            }
+        }
+    }
+
+    // checks the command against the result of legit_reads before it is
+    // submitted, DNAC would otherwise report it as blacklisted
+    pub fn is_legit(legit_reads: &[String], command: &str) -> bool {
+        command
+            .split_whitespace()
+            .next()
+            .is_some_and(|keyword| legit_reads.iter().any(|l| l.eq_ignore_ascii_case(keyword)))
+    }
+}
+
+impl CommandRunner {
+    // runs up to concurrency requests at once, requests which fail are
+    // retried and after the last attempt every command of them gets the error
+    pub async fn run_bulk<C: ApiClientExt>(
+        dnac: &C,
+        devices: &[DeviceId],
+        commands: &[&str],
+        concurrency: usize,
+    ) -> BulkOutput {
+        let requests: Vec<(&[DeviceId], &[&str])> = devices
+            .chunks(MAX_DEVICES_PER_REQUEST)
+            .flat_map(|devices| {
+                commands
+                    .chunks(MAX_COMMANDS_PER_REQUEST)
+                    .map(move |commands| (devices, commands))
+            })
+            .collect();
+
+        let results: Vec<_> = stream::iter(requests)
+            .map(|(devices, commands)| async move {
+                let result = CommandRunner::run_with_retries(dnac, devices, commands).await;
+                (devices, commands, result)
+            })
+            .buffer_unordered(concurrency.max(1))
+            .collect()
+            .await;
+
+        let mut bulk: BulkOutput = HashMap::new();
+        for (devices, commands, result) in results {
+            match result {
+                Ok(outputs) => {
+                    let mut outputs: HashMap<DeviceId, CommandOutput> =
+                        outputs.into_iter().map(|o| (o.device_id, o)).collect();
+                    for device_id in devices {
+                        let output = outputs.remove(device_id);
+                        let entry = bulk.entry(*device_id).or_default();
+                        for command in commands {
+                            let result = match &output {
+                                Some(output) => output.result(command),
+                                None => Err("No output for device".to_string()),
+                            };
+                            entry.insert(command.to_string(), result);
+                        }
+                    }
+                }
+                Err(e) => {
+                    for device_id in devices {
+                        let entry = bulk.entry(*device_id).or_default();
+                        for command in commands {
+                            entry.insert(command.to_string(), Err(e.to_string()));
+                        }
+                    }
+                }
+            }
+        }
+
+        bulk
+    }
+
+    async fn run_with_retries<C: ApiClientExt>(
+        dnac: &C,
+        devices: &[DeviceId],
+        commands: &[&str],
+    ) -> Result<Vec<CommandOutput>, CommandError> {
+        let mut attempt = 1;
+        loop {
+            match Poller::read_commands(dnac, devices, commands).await {
+                Ok(outputs) => return Ok(outputs),
+                Err(e) if attempt < MAX_ATTEMPTS => {
+                    // the retried request still waits for the rate limiter,
+                    // the backoff only spaces out the attempts on top of it
+                    let delay = RETRY_BASE_DELAY * 2u32.pow(attempt as u32 - 1);
+                    event!(
+                        Level::WARN,
+                        "Command request failed, retrying in {}ms: {e}",
+                        delay.as_millis()
+                    );
+                    dnac.record_retry();
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+impl CommandOutput {
+    pub fn result(&self, command: &str) -> Result<String, String> {
+        match (self.output(command), self.error(command)) {
+            (Some(output), _) => Ok(output.to_string()),
+            (None, Some(error)) => Err(error.to_string()),
+            (None, None) => Err("No output for command".to_string()),
+        }
+    }
+
+    // the output of a command which ran successfully
+    pub fn output(&self, command: &str) -> Option<&str> {
+        self.responses.success.get(command).map(String::as_str)
+    }
+
+    // the error of a command which failed or was not allowed
+    pub fn error(&self, command: &str) -> Option<&str> {
+        self.responses
+            .failure
+            .get(command)
+            .or_else(|| self.responses.blacklisted.get(command))
+            .map(String::as_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{fixtures, MockDnac};
+
+    #[tokio::test]
+    async fn test_legit_reads() {
+        let mock = MockDnac::start().await;
+        mock.mock_response(
+            "GET",
+            "/dna/intent/api/v1/network-device-poller/cli/legit-reads",
+            200,
+            r#"{"response":["cat","dir","ls","ping","show","traceroute","who"],"version":"1.0"}"#,
+        )
+        .await;
+
+        let legit_reads = Poller::legit_reads(&mock.dnac).await.unwrap();
+        assert!(Poller::is_legit(&legit_reads, "show ip int brief"));
+        assert!(Poller::is_legit(&legit_reads, "  SHOW version"));
+        assert!(!Poller::is_legit(&legit_reads, "configure terminal"));
+        assert!(!Poller::is_legit(&legit_reads, ""));
+    }
+
+    #[tokio::test]
+    async fn test_read_commands() {
+        let mock = MockDnac::start().await;
+        mock.mock_response(
+            "POST",
+            "/dna/intent/api/v1/network-device-poller/cli/read-request",
+            202,
+            fixtures::TASK_INFO,
+        )
+        .await;
+        mock.mock_response(
+            "GET",
+            &format!("/api/v1/task/{}/tree/", fixtures::TASK_ID),
+            200,
+            r#"
+              {"response":[{"id":"0193739c-0d88-78e4-ba0f-d82889fca555","progress":"{\"fileId\":\"2b7a2d44-4f0e-4a1e-80d6-c9b3e5a218c1\"}","startTime":1732811427209,"endTime":1732811429512,"serviceType":"Command Runner Service","isError":false,"instanceTenantId":"6307971e4289f95403c86831","version":1732811427309}]}
+              "#,
+        )
+        .await;
+        mock.mock_response(
+            "GET",
+            "/dna/intent/api/v1/file/2b7a2d44-4f0e-4a1e-80d6-c9b3e5a218c1",
+            200,
+            r#"
+              [{"deviceUuid":"3f0ac5d4-6f3d-4b5e-9d2b-5b0f3a3c2f10","commandResponses":{"SUCCESS":{"show version":"Cisco IOS XE Software, Version 17.09.04a"},"FAILURE":{"show foo":"% Invalid input detected"},"BLACKLISTED":{}}}]
+              "#,
+        )
+        .await;
+
+        let device_id = "3f0ac5d4-6f3d-4b5e-9d2b-5b0f3a3c2f10".parse().unwrap();
+        let outputs =
+            Poller::read_commands(&mock.dnac, &[device_id], &["show version", "show foo"])
+                .await
+                .unwrap();
+        assert_eq!(outputs.len(), 1);
+        assert!(outputs[0]
+            .output("show version")
+            .unwrap()
+            .contains("17.09.04a"));
+        assert_eq!(
+            outputs[0].error("show foo"),
+            Some("% Invalid input detected")
+        );
+
+        let other_device = "8c1e2b7a-2d44-4f0e-a1e3-0d6c9b3e5a21".parse().unwrap();
+        let bulk = CommandRunner::run_bulk(
+            &mock.dnac,
+            &[device_id, other_device],
+            &["show version", "show foo"],
+            2,
+        )
+        .await;
+        assert!(bulk[&device_id]["show version"].is_ok());
+        assert!(bulk[&device_id]["show foo"].is_err());
+        assert!(bulk[&other_device]["show version"].is_err());
+    }
+
+    #[tokio::test]
+    async fn test_run_bulk_retries() {
+        let mock = MockDnac::start().await;
+        mock.mock_response(
+            "POST",
+            "/dna/intent/api/v1/network-device-poller/cli/read-request",
+            429,
+            r#"{"message":"Too Many Requests"}"#,
+        )
+        .await;
+
+        let device_id: DeviceId = "3f0ac5d4-6f3d-4b5e-9d2b-5b0f3a3c2f10".parse().unwrap();
+        let start = std::time::Instant::now();
+        let bulk = CommandRunner::run_bulk(&mock.dnac, &[device_id], &["show version"], 1).await;
+        assert!(bulk[&device_id]["show version"].is_err());
+        // 500ms before the second and 1s before the third attempt
+        assert!(start.elapsed() >= RETRY_BASE_DELAY * 3);
+
+        let requests = mock.server.received_requests().await.unwrap();
+        assert_eq!(requests.len(), MAX_ATTEMPTS);
+        assert_eq!(mock.dnac.metrics().retries, MAX_ATTEMPTS as u64 - 1);
+    }
+}
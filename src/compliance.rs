@@ -0,0 +1,288 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::{event, Level};
+
+use crate::{
+    dnac::{ApiClient, Pagination, PaginationError, ResponseType},
+    ids::DeviceId,
+    query::Query,
+};
+
+// Compliance wraps the compliance checks of the devices, e.g. if the running
+// config differs from the startup config or the image from the golden one
+pub struct Compliance;
+
+// ComplianceSummary is the overall compliance of a device over all categories
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComplianceSummary {
+    pub device_uuid: DeviceId,
+    pub compliance_status: ComplianceState,
+    pub message: Option<String>,
+    pub schedule_time: Option<i64>,
+    pub last_update_time: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComplianceDetail {
+    pub device_uuid: DeviceId,
+    pub compliance_type: ComplianceCategory,
+    pub status: ComplianceState,
+    // the state of the last check, e.g. SUCCESS or FAILED
+    pub state: Option<String>,
+    pub last_sync_time: Option<i64>,
+    pub last_update_time: Option<i64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ComplianceCategory {
+    Image,
+    Psirt,
+    RunningConfig,
+    Eox,
+    NetworkProfile,
+    NetworkSettings,
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ComplianceState {
+    Compliant,
+    NonCompliant,
+    InProgress,
+    NotAvailable,
+    NotApplicable,
+    Error,
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ComplianceFilter {
+    pub compliance_status: Option<ComplianceState>,
+    pub device_ids: Vec<DeviceId>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ComplianceRun<'a> {
+    trigger_full: bool,
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    categories: &'a [ComplianceCategory],
+    device_uuids: &'a [DeviceId],
+}
+
+#[derive(Debug, Error)]
+pub enum ComplianceError {
+    #[error("General Compliance Error")]
+    GeneralError,
+    #[error(transparent)]
+    PaginationOutOfRange(#[from] PaginationError),
+}
+
+impl fmt::Display for ComplianceCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self {
+            Self::Image => write!(f, "IMAGE"),
+            Self::Psirt => write!(f, "PSIRT"),
+            Self::RunningConfig => write!(f, "RUNNING_CONFIG"),
+            Self::Eox => write!(f, "EOX"),
+            Self::NetworkProfile => write!(f, "NETWORK_PROFILE"),
+            Self::NetworkSettings => write!(f, "NETWORK_SETTINGS"),
+            Self::Other => write!(f, "OTHER"),
+        }
+    }
+}
+
+impl fmt::Display for ComplianceState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self {
+            Self::Compliant => write!(f, "COMPLIANT"),
+            Self::NonCompliant => write!(f, "NON_COMPLIANT"),
+            Self::InProgress => write!(f, "IN_PROGRESS"),
+            Self::NotAvailable => write!(f, "NOT_AVAILABLE"),
+            Self::NotApplicable => write!(f, "NOT_APPLICABLE"),
+            Self::Error => write!(f, "ERROR"),
+            Self::Unknown => write!(f, "UNKNOWN"),
+        }
+    }
+}
+
+impl Compliance {
+    pub async fn summary<C: ApiClient>(
+        dnac: &C,
+        filter: Option<&ComplianceFilter>,
+        pagination: Option<Pagination>,
+    ) -> Result<Vec<ComplianceSummary>, ComplianceError> {
+        let path = "/dna/intent/api/v1/compliance";
+        let query = filter.map(ComplianceFilter::to_query).unwrap_or_default();
+
+        let compliance_data = dnac
+            .get::<ComplianceSummary>(path, Some(query.as_slice()), pagination)
+            .await;
+
+        match compliance_data {
+            Ok(compliance_data) => match compliance_data.response {
+                ResponseType::Array(data) => Ok(data),
+                ResponseType::Item(data) => Ok(vec![data]),
+            },
+            Err(e) => match e.downcast::<PaginationError>() {
+                Ok(pagination_error) => Err(pagination_error.into()),
+                Err(e) => {
+                    event!(Level::ERROR, "{e}");
+                    Err(ComplianceError::GeneralError)
+                }
+            },
+        }
+    }
+
+    // runs the compliance check of the devices and waits until it's done,
+    // all categories are checked if none are given
+    pub async fn run<C: ApiClient>(
+        dnac: &C,
+        device_ids: &[DeviceId],
+        categories: &[ComplianceCategory],
+    ) -> anyhow::Result<()> {
+        let path = "/dna/intent/api/v1/compliance/";
+        let request = ComplianceRun {
+            trigger_full: categories.is_empty(),
+            categories,
+            device_uuids: device_ids,
+        };
+        dnac.post(path, request, true).await
+    }
+
+    // the compliance per category, all categories are returned if None
+    pub async fn detail<C: ApiClient>(
+        dnac: &C,
+        device_id: DeviceId,
+        category: Option<ComplianceCategory>,
+    ) -> Result<Vec<ComplianceDetail>, ComplianceError> {
+        let path = format!("/dna/intent/api/v1/compliance/{device_id}/detail");
+        let query = Query::new().eq_opt("category", category);
+
+        let compliance_data = dnac
+            .get::<ComplianceDetail>(path.as_str(), Some(query.as_slice()), None)
+            .await;
+
+        match compliance_data {
+            Ok(compliance_data) => match compliance_data.response {
+                ResponseType::Array(data) => Ok(data),
+                ResponseType::Item(data) => Ok(vec![data]),
+            },
+            Err(e) => {
+                event!(Level::ERROR, "{e}");
+                Err(ComplianceError::GeneralError)
+            }
+        }
+    }
+}
+
+impl ComplianceFilter {
+    pub fn to_query(&self) -> Query<'static> {
+        let device_ids = (!self.device_ids.is_empty()).then(|| {
+            self.device_ids
+                .iter()
+                .map(|id| id.to_string())
+                .collect::<Vec<_>>()
+                .join(",")
+        });
+
+        Query::new()
+            .eq_opt("complianceStatus", self.compliance_status)
+            .eq_opt("deviceUuid", device_ids)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::MockDnac;
+
+    #[tokio::test]
+    async fn test_run() {
+        let mock = MockDnac::start().await;
+        mock.mock_task("POST", "/dna/intent/api/v1/compliance/", false)
+            .await;
+
+        let device_id: DeviceId = "3f0ac5d4-6f3d-4b5e-9d2b-5b0f3a3c2f10".parse().unwrap();
+        let categories = [ComplianceCategory::RunningConfig, ComplianceCategory::Image];
+        Compliance::run(&mock.dnac, &[device_id], &categories)
+            .await
+            .unwrap();
+
+        let requests = mock.server.received_requests().await.unwrap();
+        let run = requests
+            .iter()
+            .find(|r| r.method.as_str() == "POST")
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&run.body).unwrap();
+        assert_eq!(body["triggerFull"], false);
+        assert_eq!(body["categories"][0], "RUNNING_CONFIG");
+        assert_eq!(
+            body["deviceUuids"][0],
+            "3f0ac5d4-6f3d-4b5e-9d2b-5b0f3a3c2f10"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_summary_and_detail() {
+        let mock = MockDnac::start().await;
+        let device_id: DeviceId = "3f0ac5d4-6f3d-4b5e-9d2b-5b0f3a3c2f10".parse().unwrap();
+        mock.mock_response(
+            "GET",
+            "/dna/intent/api/v1/compliance",
+            200,
+            r#"
+              {"response":[{"deviceUuid":"3f0ac5d4-6f3d-4b5e-9d2b-5b0f3a3c2f10","complianceStatus":"NON_COMPLIANT","message":"","scheduleTime":null,"lastUpdateTime":1732811427209}],"version":"1.0"}
+              "#,
+        )
+        .await;
+        mock.mock_response(
+            "GET",
+            &format!("/dna/intent/api/v1/compliance/{device_id}/detail"),
+            200,
+            r#"
+              {"response":[{"deviceUuid":"3f0ac5d4-6f3d-4b5e-9d2b-5b0f3a3c2f10","complianceType":"RUNNING_CONFIG","status":"NON_COMPLIANT","state":"SUCCESS","lastSyncTime":1732811427209,"lastUpdateTime":1732811427209},
+                           {"deviceUuid":"3f0ac5d4-6f3d-4b5e-9d2b-5b0f3a3c2f10","complianceType":"APPLICATION_VISIBILITY","status":"NOT_APPLICABLE","state":"SUCCESS"}],"version":"1.0"}
+              "#,
+        )
+        .await;
+
+        let filter = ComplianceFilter {
+            compliance_status: Some(ComplianceState::NonCompliant),
+            device_ids: vec![device_id],
+        };
+        let summary = Compliance::summary(&mock.dnac, Some(&filter), None)
+            .await
+            .unwrap();
+        assert_eq!(summary[0].device_uuid, device_id);
+        assert_eq!(summary[0].compliance_status, ComplianceState::NonCompliant);
+
+        let detail = Compliance::detail(
+            &mock.dnac,
+            device_id,
+            Some(ComplianceCategory::RunningConfig),
+        )
+        .await
+        .unwrap();
+        assert_eq!(detail[0].compliance_type, ComplianceCategory::RunningConfig);
+        assert_eq!(detail[1].compliance_type, ComplianceCategory::Other);
+
+        let requests = mock.server.received_requests().await.unwrap();
+        let summary_request = requests
+            .iter()
+            .find(|r| r.url.path() == "/dna/intent/api/v1/compliance")
+            .unwrap();
+        assert_eq!(
+            summary_request.url.query(),
+            Some("complianceStatus=NON_COMPLIANT&deviceUuid=3f0ac5d4-6f3d-4b5e-9d2b-5b0f3a3c2f10")
+        );
+    }
+}
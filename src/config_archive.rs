@@ -0,0 +1,300 @@
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::{event, Level};
+
+use crate::{
+    dnac::{ApiClient, ApiClientExt, ResponseType},
+    file::File,
+    ids::DeviceId,
+    query::Query,
+};
+
+// ConfigArchive wraps the configuration archive, DNAC stores a version with
+// the running and startup config of a device whenever its config changed
+pub struct ConfigArchive;
+
+// ConfigFile is a single file of an archived version
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigFile {
+    pub id: String,
+    pub network_device_id: DeviceId,
+    pub version_id: String,
+    pub file_type: ConfigFileType,
+    pub created_by: Option<String>,
+    pub created_time: Option<i64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ConfigFileType {
+    Runningconfig,
+    Startupconfig,
+    Vlan,
+    #[serde(other)]
+    Other,
+}
+
+// ConfigVersion groups the files which were archived together
+#[derive(Debug, Clone)]
+pub struct ConfigVersion {
+    pub id: String,
+    pub device_id: DeviceId,
+    pub created_by: Option<String>,
+    pub created_time: Option<i64>,
+    pub files: Vec<ConfigFile>,
+}
+
+// ArchivedConfig is the content of a version, the passwords and keys are
+// masked by DNAC
+#[derive(Debug, Default)]
+pub struct ArchivedConfig {
+    pub version_id: String,
+    pub running_config: Option<String>,
+    pub startup_config: Option<String>,
+    pub vlan: Option<String>,
+}
+
+// the password encrypts the zip, it's needed to open the archive
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ArchiveExport<'a> {
+    device_id: &'a [DeviceId],
+    password: &'a str,
+}
+
+#[derive(Debug, Error)]
+pub enum ConfigArchiveError {
+    #[error("General Config Archive Error")]
+    GeneralError,
+    #[error("Config version {0} not found")]
+    VersionNotFound(String),
+}
+
+async fn get_config_files<C: ApiClient>(
+    dnac: &C,
+    query: Query<'_>,
+) -> Result<Vec<ConfigFile>, ConfigArchiveError> {
+    let path = "/dna/intent/api/v1/networkDeviceConfigFiles";
+
+    let file_data = dnac
+        .get::<ConfigFile>(path, Some(query.as_slice()), None)
+        .await;
+
+    match file_data {
+        Ok(file_data) => match file_data.response {
+            ResponseType::Array(data) => Ok(data),
+            ResponseType::Item(data) => Ok(vec![data]),
+        },
+        Err(e) => {
+            event!(Level::ERROR, "{e}");
+            Err(ConfigArchiveError::GeneralError)
+        }
+    }
+}
+
+impl ConfigArchive {
+    // returns the archived versions of the device, the newest first
+    pub async fn versions<C: ApiClient>(
+        dnac: &C,
+        device_id: DeviceId,
+    ) -> Result<Vec<ConfigVersion>, ConfigArchiveError> {
+        let query = Query::new().eq("networkDeviceId", device_id);
+        let files = get_config_files(dnac, query).await?;
+
+        let mut versions: Vec<ConfigVersion> = vec![];
+        for file in files {
+            match versions.iter_mut().find(|v| v.id == file.version_id) {
+                Some(version) => version.files.push(file),
+                None => versions.push(ConfigVersion {
+                    id: file.version_id.clone(),
+                    device_id: file.network_device_id,
+                    created_by: file.created_by.clone(),
+                    created_time: file.created_time,
+                    files: vec![file],
+                }),
+            }
+        }
+        versions.sort_by_key(|v| std::cmp::Reverse(v.created_time));
+
+        Ok(versions)
+    }
+
+    // exports the current configs of the devices in cleartext, the zip is
+    // encrypted with the password. DNAC requires a password with 8 to 25
+    // characters and at least one upper case letter, digit and special char.
+    pub async fn export<C: ApiClientExt>(
+        dnac: &C,
+        device_ids: &[DeviceId],
+        password: &str,
+    ) -> anyhow::Result<Vec<u8>> {
+        let path = "/dna/intent/api/v1/network-device-archive/cleartext";
+        let request = ArchiveExport {
+            device_id: device_ids,
+            password,
+        };
+        let task_info = dnac.post_task(path, request).await?;
+        let tasks = dnac.poll_task(task_info).await?;
+
+        Ok(File::from_tasks(dnac, &tasks).await?)
+    }
+
+    // downloads the files of the version, every file is exported by its own
+    // task
+    pub async fn get_version<C: ApiClientExt>(
+        dnac: &C,
+        version_id: &str,
+    ) -> Result<ArchivedConfig, ConfigArchiveError> {
+        let query = Query::new().eq("versionId", version_id);
+        let files = get_config_files(dnac, query).await?;
+        if files.is_empty() {
+            return Err(ConfigArchiveError::VersionNotFound(version_id.to_string()));
+        }
+
+        let mut config = ArchivedConfig {
+            version_id: version_id.to_string(),
+            ..Default::default()
+        };
+        for file in files {
+            let content = download_masked(dnac, &file.id).await.map_err(|e| {
+                event!(Level::ERROR, "{e}");
+                ConfigArchiveError::GeneralError
+            })?;
+
+            match file.file_type {
+                ConfigFileType::Runningconfig => config.running_config = Some(content),
+                ConfigFileType::Startupconfig => config.startup_config = Some(content),
+                ConfigFileType::Vlan => config.vlan = Some(content),
+                ConfigFileType::Other => {}
+            }
+        }
+
+        Ok(config)
+    }
+}
+
+async fn download_masked<C: ApiClientExt>(dnac: &C, file_id: &str) -> anyhow::Result<String> {
+    let path = format!("/dna/intent/api/v1/networkDeviceConfigFiles/{file_id}/downloadMasked");
+    let task_info = dnac.post_task(path.as_str(), serde_json::json!({})).await?;
+    let tasks = dnac.poll_task(task_info).await?;
+
+    let content = File::from_tasks(dnac, &tasks).await?;
+    Ok(String::from_utf8_lossy(&content).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{fixtures, MockDnac};
+
+    const CONFIG_FILES: &str = r#"
+      {"response":[{"id":"9d2b5b0f-3a3c-4f10-8f0a-c5d46f3d4b5e","networkDeviceId":"3f0ac5d4-6f3d-4b5e-9d2b-5b0f3a3c2f10","versionId":"c5d46f3d-4b5e-4d2b-9b0f-3a3c2f103f0a","fileType":"RUNNINGCONFIG","createdBy":"Periodic","createdTime":1732811427209},
+                   {"id":"3a3c2f10-9d2b-4b0f-8f0a-c5d46f3d4b5e","networkDeviceId":"3f0ac5d4-6f3d-4b5e-9d2b-5b0f3a3c2f10","versionId":"c5d46f3d-4b5e-4d2b-9b0f-3a3c2f103f0a","fileType":"STARTUPCONFIG","createdBy":"Periodic","createdTime":1732811427209},
+                   {"id":"4b5e9d2b-5b0f-4a3c-8f10-3f0ac5d46f3d","networkDeviceId":"3f0ac5d4-6f3d-4b5e-9d2b-5b0f3a3c2f10","versionId":"2f103f0a-c5d4-46f3-8d4b-5e9d2b5b0f3a","fileType":"RUNNINGCONFIG","createdBy":"SPF","createdTime":1732897827209}],"version":"1.0"}
+      "#;
+
+    #[tokio::test]
+    async fn test_versions() {
+        let mock = MockDnac::start().await;
+        mock.mock_response(
+            "GET",
+            "/dna/intent/api/v1/networkDeviceConfigFiles",
+            200,
+            CONFIG_FILES,
+        )
+        .await;
+
+        let device_id: DeviceId = "3f0ac5d4-6f3d-4b5e-9d2b-5b0f3a3c2f10".parse().unwrap();
+        let versions = ConfigArchive::versions(&mock.dnac, device_id)
+            .await
+            .unwrap();
+        assert_eq!(versions.len(), 2);
+        assert_eq!(versions[0].id, "2f103f0a-c5d4-46f3-8d4b-5e9d2b5b0f3a");
+        assert_eq!(versions[1].files.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_get_version() {
+        let mock = MockDnac::start().await;
+        mock.mock_response(
+            "GET",
+            "/dna/intent/api/v1/networkDeviceConfigFiles",
+            200,
+            r#"
+              {"response":[{"id":"9d2b5b0f-3a3c-4f10-8f0a-c5d46f3d4b5e","networkDeviceId":"3f0ac5d4-6f3d-4b5e-9d2b-5b0f3a3c2f10","versionId":"c5d46f3d-4b5e-4d2b-9b0f-3a3c2f103f0a","fileType":"RUNNINGCONFIG","createdBy":"Periodic","createdTime":1732811427209}],"version":"1.0"}
+              "#,
+        )
+        .await;
+        mock.mock_response(
+            "POST",
+            "/dna/intent/api/v1/networkDeviceConfigFiles/9d2b5b0f-3a3c-4f10-8f0a-c5d46f3d4b5e/downloadMasked",
+            202,
+            fixtures::TASK_INFO,
+        )
+        .await;
+        mock.mock_response(
+            "GET",
+            &format!("/api/v1/task/{}/tree/", fixtures::TASK_ID),
+            200,
+            r#"
+              {"response":[{"id":"0193739c-0d88-78e4-ba0f-d82889fca555","progress":"Config file download ready","additionalStatusURL":"/file/2b7a2d44-4f0e-4a1e-80d6-c9b3e5a218c1","startTime":1732811427209,"endTime":1732811429512,"serviceType":"Inventory service","isError":false,"instanceTenantId":"6307971e4289f95403c86831","version":1732811427309}]}
+              "#,
+        )
+        .await;
+        mock.mock_response(
+            "GET",
+            "/dna/intent/api/v1/file/2b7a2d44-4f0e-4a1e-80d6-c9b3e5a218c1",
+            200,
+            r#""hostname zrh-acc-sw01""#,
+        )
+        .await;
+
+        let config = ConfigArchive::get_version(&mock.dnac, "c5d46f3d-4b5e-4d2b-9b0f-3a3c2f103f0a")
+            .await
+            .unwrap();
+        assert_eq!(
+            config.running_config.as_deref(),
+            Some(r#""hostname zrh-acc-sw01""#)
+        );
+        assert!(config.startup_config.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_export() {
+        let mock = MockDnac::start().await;
+        mock.mock_response(
+            "POST",
+            "/dna/intent/api/v1/network-device-archive/cleartext",
+            202,
+            fixtures::TASK_INFO,
+        )
+        .await;
+        mock.mock_response(
+            "GET",
+            &format!("/api/v1/task/{}/tree/", fixtures::TASK_ID),
+            200,
+            r#"
+              {"response":[{"id":"0193739c-0d88-78e4-ba0f-d82889fca555","progress":"Device configs exported","additionalStatusURL":"/file/2b7a2d44-4f0e-4a1e-80d6-c9b3e5a218c1","startTime":1732811427209,"endTime":1732811429512,"serviceType":"Inventory service","isError":false,"instanceTenantId":"6307971e4289f95403c86831","version":1732811427309}]}
+              "#,
+        )
+        .await;
+        mock.mock_response(
+            "GET",
+            "/dna/intent/api/v1/file/2b7a2d44-4f0e-4a1e-80d6-c9b3e5a218c1",
+            200,
+            r#""zip""#,
+        )
+        .await;
+
+        let device_id: DeviceId = "3f0ac5d4-6f3d-4b5e-9d2b-5b0f3a3c2f10".parse().unwrap();
+        let archive = ConfigArchive::export(&mock.dnac, &[device_id], "Backup#2024")
+            .await
+            .unwrap();
+        assert_eq!(archive, br#""zip""#);
+
+        let requests = mock.server.received_requests().await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+        assert_eq!(body["deviceId"][0], "3f0ac5d4-6f3d-4b5e-9d2b-5b0f3a3c2f10");
+        assert_eq!(body["password"], "Backup#2024");
+    }
+}
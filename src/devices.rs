@@ -1,41 +1,121 @@
+use core::fmt;
+use std::{convert::Infallible, str::FromStr};
+
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
+use tokio_util::sync::CancellationToken;
 use tracing::{event, Level};
 use uuid::Uuid;
 
-use crate::dnac::{ResponseType, DNAC};
+use crate::{
+    dnac::{ApiClient, ApiClientExt, ResponseType, TaskInfo, REDACTED},
+    file::File,
+    ids::{DeviceId, SiteId},
+    query::Query,
+};
+
+#[cfg(feature = "wireless")]
+use crate::wireless::{AccessPoint, AccessPointConfig};
 
-use super::dnac::{FetchableType, Pagination};
+use super::dnac::{with_cancel, Cancelled, FetchableType, Pagination, PaginationError};
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+// families DNAC reports which are not listed, e.g. "Third Party Device",
+// are kept as Other
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
 pub enum DeviceFamily {
-    #[serde(rename = "Switches and Hubs")]
     SwitchesAndHubs,
-    #[serde(rename = "Unified AP")]
     UnifiedAp,
-    #[serde(rename = "Routers")]
     Routers,
-    #[serde(rename = "Wireless Controller")]
     WirelessController,
-    #[serde(rename = "Wireless Sensor")]
     WirelessSensor,
+    Other(String),
 }
 
-impl ToString for DeviceFamily {
-    fn to_string(&self) -> String {
+impl fmt::Display for DeviceFamily {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match &self {
-            Self::SwitchesAndHubs => String::from("Switches and Hubs"),
-            Self::UnifiedAp => String::from("Unified AP"),
-            Self::Routers => String::from("Routers"),
-            Self::WirelessController => String::from("Wireless Controller"),
-            Self::WirelessSensor => String::from("Wireless Sensor"),
+            Self::SwitchesAndHubs => write!(f, "Switches and Hubs"),
+            Self::UnifiedAp => write!(f, "Unified AP"),
+            Self::Routers => write!(f, "Routers"),
+            Self::WirelessController => write!(f, "Wireless Controller"),
+            Self::WirelessSensor => write!(f, "Wireless Sensor"),
+            Self::Other(family) => write!(f, "{family}"),
         }
     }
 }
 
+impl FromStr for DeviceFamily {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "Switches and Hubs" => Self::SwitchesAndHubs,
+            "Unified AP" => Self::UnifiedAp,
+            "Routers" => Self::Routers,
+            "Wireless Controller" => Self::WirelessController,
+            "Wireless Sensor" => Self::WirelessSensor,
+            other => Self::Other(other.to_string()),
+        })
+    }
+}
+
+impl From<String> for DeviceFamily {
+    fn from(family: String) -> Self {
+        let Ok(family) = family.parse();
+        family
+    }
+}
+
+impl From<DeviceFamily> for String {
+    fn from(family: DeviceFamily) -> Self {
+        family.to_string()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum DeviceRole {
+    #[serde(rename = "ACCESS")]
+    Access,
+    #[serde(rename = "DISTRIBUTION")]
+    Distribution,
+    #[serde(rename = "CORE")]
+    Core,
+    #[serde(rename = "BORDER ROUTER")]
+    BorderRouter,
+    #[serde(rename = "UNKNOWN", other)]
+    Unknown,
+}
+
+impl fmt::Display for DeviceRole {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self {
+            Self::Access => write!(f, "ACCESS"),
+            Self::Distribution => write!(f, "DISTRIBUTION"),
+            Self::Core => write!(f, "CORE"),
+            Self::BorderRouter => write!(f, "BORDER ROUTER"),
+            Self::Unknown => write!(f, "UNKNOWN"),
+        }
+    }
+}
+
+// AUTO roles are derived by DNAC from the topology, MANUAL roles were set by
+// a user and are not changed by the next resync
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RoleSource {
+    #[serde(rename = "AUTO")]
+    Auto,
+    #[serde(rename = "MANUAL")]
+    Manual,
+    #[serde(other)]
+    Unknown,
+}
+
+// Device is the inventory record of the network-device endpoints, only id,
+// collectionStatus and managementIpAddress are always set
 #[derive(Debug, Deserialize)]
 pub struct Device {
-    pub id: Uuid,
+    pub id: DeviceId,
     #[serde(rename = "collectionStatus")]
     pub collection_status: DeviceStatus,
     #[serde(rename = "managementIpAddress")]
@@ -43,12 +123,152 @@ pub struct Device {
     pub hostname: Option<String>,
     pub description: Option<String>,
     pub family: Option<DeviceFamily>,
+    #[serde(rename = "serialNumber")]
+    pub serial_number: Option<String>,
+    #[serde(rename = "macAddress")]
+    pub mac_address: Option<String>,
+    #[serde(rename = "platformId")]
+    pub platform_id: Option<String>,
+    pub series: Option<String>,
+    #[serde(rename = "type")]
+    pub device_type: Option<String>,
+    pub role: Option<DeviceRole>,
+    #[serde(rename = "roleSource")]
+    pub role_source: Option<RoleSource>,
+    #[serde(rename = "softwareType")]
+    pub software_type: Option<String>,
+    #[serde(rename = "softwareVersion")]
+    pub software_version: Option<String>,
+    #[serde(rename = "upTime")]
+    pub up_time: Option<String>,
+    #[serde(rename = "uptimeSeconds")]
+    pub uptime_seconds: Option<u64>,
+    #[serde(rename = "bootDateTime")]
+    pub boot_date_time: Option<String>,
+    #[serde(rename = "lastUpdated")]
+    pub last_updated: Option<String>,
+    #[serde(rename = "lastUpdateTime")]
+    pub last_update_time: Option<u64>,
+    #[serde(rename = "reachabilityStatus")]
+    pub reachability_status: Option<String>,
+    #[serde(rename = "reachabilityFailureReason")]
+    pub reachability_failure_reason: Option<String>,
+    #[serde(rename = "collectionInterval")]
+    pub collection_interval: Option<String>,
+    #[serde(rename = "managedAtleastOnce")]
+    pub managed_at_least_once: Option<bool>,
+    #[serde(rename = "errorCode")]
+    pub error_code: Option<String>,
+    #[serde(rename = "errorDescription")]
+    pub error_description: Option<String>,
+    pub location: Option<String>,
+    #[serde(rename = "locationName")]
+    pub location_name: Option<String>,
+    #[serde(rename = "snmpContact")]
+    pub snmp_contact: Option<String>,
+    #[serde(rename = "snmpLocation")]
+    pub snmp_location: Option<String>,
+    #[serde(rename = "memorySize")]
+    pub memory_size: Option<String>,
+    #[serde(rename = "interfaceCount")]
+    pub interface_count: Option<String>,
+    #[serde(rename = "lineCardCount")]
+    pub line_card_count: Option<String>,
+    #[serde(rename = "lineCardId")]
+    pub line_card_id: Option<String>,
+    #[serde(rename = "tagCount")]
+    pub tag_count: Option<String>,
+    #[serde(rename = "associatedWlcIp")]
+    pub associated_wlc_ip: Option<String>,
+    #[serde(rename = "apManagerInterfaceIp")]
+    pub ap_manager_interface_ip: Option<String>,
+    #[serde(rename = "apEthernetMacAddress")]
+    pub ap_ethernet_mac_address: Option<String>,
+    #[serde(rename = "deviceSupportLevel")]
+    pub device_support_level: Option<String>,
+}
+
+// DeviceConfig is an entry of the bulk config endpoint
+#[derive(Debug, Deserialize)]
+pub struct DeviceConfig {
+    pub id: DeviceId,
+    #[serde(rename = "runningConfig")]
+    pub running_config: Option<String>,
+}
+
+// StackInfo describes the members and stack ports of a switch stack
+#[derive(Debug, Deserialize)]
+pub struct StackInfo {
+    #[serde(rename = "deviceId")]
+    pub device_id: DeviceId,
+    #[serde(rename = "stackSwitchInfo", default)]
+    pub members: Vec<StackMember>,
+    #[serde(rename = "stackPortInfo", default)]
+    pub ports: Vec<StackPort>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StackMember {
+    #[serde(rename = "stackMemberNumber")]
+    pub switch_number: u8,
+    // e.g. ACTIVE, STANDBY or MEMBER
+    pub role: Option<String>,
+    // e.g. READY
+    pub state: Option<String>,
+    #[serde(rename = "switchPriority")]
+    pub priority: Option<u8>,
+    #[serde(rename = "hwPriority")]
+    pub hw_priority: Option<u8>,
+    #[serde(rename = "numNextReload")]
+    pub number_next_reload: Option<u8>,
+    #[serde(rename = "macAddress")]
+    pub mac_address: Option<String>,
+    #[serde(rename = "platformId")]
+    pub platform_id: Option<String>,
+    #[serde(rename = "serialNumber")]
+    pub serial_number: Option<String>,
+    #[serde(rename = "softwareImage")]
+    pub software_image: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StackPort {
+    pub name: Option<String>,
+    #[serde(rename = "switchPort")]
+    pub switch_port: Option<String>,
+    #[serde(rename = "neighborPort")]
+    pub neighbor_port: Option<String>,
+    #[serde(rename = "linkActive")]
+    pub link_active: Option<bool>,
+    #[serde(rename = "linkOk")]
+    pub link_ok: Option<bool>,
+    #[serde(rename = "nrLinkOkChanges")]
+    pub link_ok_changes: Option<u32>,
+    #[serde(rename = "stackPortOperStatusInfo")]
+    pub oper_status: Option<String>,
+    #[serde(rename = "stackCableLengthInfo")]
+    pub cable_length: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeviceModule {
+    pub id: String,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    #[serde(rename = "serialNumber")]
+    pub serial_number: Option<String>,
+    #[serde(rename = "partNumber")]
+    pub part_number: Option<String>,
+    #[serde(rename = "containmentEntity")]
+    pub containment_entity: Option<String>,
+    #[serde(rename = "isFieldReplaceable")]
+    pub is_field_replaceable: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct MembershipDevice {
     #[serde(rename = "instanceUuid")]
-    pub id: Uuid,
+    pub id: DeviceId,
     #[serde(rename = "collectionStatus")]
     pub collection_status: DeviceStatus,
     #[serde(rename = "managementIpAddress")]
@@ -58,31 +278,137 @@ pub struct MembershipDevice {
     pub family: Option<DeviceFamily>,
 }
 
-#[derive(Debug, Clone, Copy, Deserialize)]
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssignedSite {
+    pub device_id: DeviceId,
+    pub site_id: SiteId,
+    pub site_name_hierarchy: Option<String>,
+    // e.g. area, building or floor
+    pub site_type: Option<String>,
+}
+
+// Udf is the definition of a user-defined field, the values are set per device
+#[derive(Debug, Deserialize)]
+pub struct Udf {
+    pub id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+}
+
+// collection statuses which are not listed are kept as Other
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
 pub enum DeviceStatus {
     Unassociated,
     Synchronizing,
-    #[serde(rename = "Sync Disabled")]
     SyncDisabled,
-    #[serde(rename = "Could Not Synchronize")]
     CouldNotSynchronize,
-    #[serde(rename = "Not Manageable")]
     NotManageable,
     Managed,
-    #[serde(rename = "Partial Collection Failure")]
     PartialCollectionFailure,
     Incomplete,
     Unreachable,
-    #[serde(rename = "Wrong Credential")]
     WrongCredential,
     Reachable,
-    #[serde(rename = "In Progress")]
     InProgress,
+    Other(String),
 }
 
+impl fmt::Display for DeviceStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self {
+            Self::Unassociated => write!(f, "Unassociated"),
+            Self::Synchronizing => write!(f, "Synchronizing"),
+            Self::SyncDisabled => write!(f, "Sync Disabled"),
+            Self::CouldNotSynchronize => write!(f, "Could Not Synchronize"),
+            Self::NotManageable => write!(f, "Not Manageable"),
+            Self::Managed => write!(f, "Managed"),
+            Self::PartialCollectionFailure => write!(f, "Partial Collection Failure"),
+            Self::Incomplete => write!(f, "Incomplete"),
+            Self::Unreachable => write!(f, "Unreachable"),
+            Self::WrongCredential => write!(f, "Wrong Credential"),
+            Self::Reachable => write!(f, "Reachable"),
+            Self::InProgress => write!(f, "In Progress"),
+            Self::Other(status) => write!(f, "{status}"),
+        }
+    }
+}
+
+impl FromStr for DeviceStatus {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "Unassociated" => Self::Unassociated,
+            "Synchronizing" => Self::Synchronizing,
+            "Sync Disabled" => Self::SyncDisabled,
+            "Could Not Synchronize" => Self::CouldNotSynchronize,
+            "Not Manageable" => Self::NotManageable,
+            "Managed" => Self::Managed,
+            "Partial Collection Failure" => Self::PartialCollectionFailure,
+            "Incomplete" => Self::Incomplete,
+            "Unreachable" => Self::Unreachable,
+            "Wrong Credential" => Self::WrongCredential,
+            "Reachable" => Self::Reachable,
+            "In Progress" => Self::InProgress,
+            other => Self::Other(other.to_string()),
+        })
+    }
+}
+
+impl From<String> for DeviceStatus {
+    fn from(status: String) -> Self {
+        let Ok(status) = status.parse();
+        status
+    }
+}
+
+impl From<DeviceStatus> for String {
+    fn from(status: DeviceStatus) -> Self {
+        status.to_string()
+    }
+}
+
+// DeviceFilter is kept for existing callers, each variant maps to the
+// DeviceQuery criterion of the same name
+#[deprecated(note = "use DeviceQuery, e.g. DeviceQuery::from(filter)")]
 pub enum DeviceFilter {
     Family(DeviceFamily),
+    // matches devices of any of the families
+    Families(Vec<DeviceFamily>),
     ManagementIPAddress(String),
+    SerialNumber(String),
+}
+
+#[allow(deprecated)]
+impl From<DeviceFilter> for DeviceQuery {
+    fn from(filter: DeviceFilter) -> Self {
+        match filter {
+            DeviceFilter::Family(family) => DeviceQuery::new().family(family),
+            DeviceFilter::Families(families) => families
+                .into_iter()
+                .fold(DeviceQuery::new(), DeviceQuery::family),
+            DeviceFilter::ManagementIPAddress(ip) => DeviceQuery::new().management_ip_address(ip),
+            DeviceFilter::SerialNumber(serial) => DeviceQuery::new().serial_number(serial),
+        }
+    }
+}
+
+// DeviceQuery combines the filters of the network-device endpoint. Different
+// criteria must all match, multiple values of the same criterion match any
+// of them. Hostnames support the .* wildcard of the API.
+#[derive(Debug, Clone, Default)]
+pub struct DeviceQuery {
+    hostnames: Vec<String>,
+    families: Vec<DeviceFamily>,
+    roles: Vec<DeviceRole>,
+    software_versions: Vec<String>,
+    reachability_statuses: Vec<String>,
+    location_names: Vec<String>,
+    platform_ids: Vec<String>,
+    serial_numbers: Vec<String>,
+    management_ip_addresses: Vec<String>,
 }
 
 #[derive(Debug, Error)]
@@ -91,48 +417,52 @@ pub enum DeviceError {
     GeneralError,
     #[error("Invalid Device")]
     InvalidDevice,
+    #[error(transparent)]
+    PaginationOutOfRange(#[from] PaginationError),
+    #[error(transparent)]
+    Cancelled(#[from] Cancelled),
 }
 
 impl Device {
-    pub async fn get_device_list(
-        dnac: &DNAC,
-        filter: Option<DeviceFilter>,
+    pub async fn get_device_list<C: ApiClient>(
+        dnac: &C,
+        query: Option<&DeviceQuery>,
         pagination: Option<Pagination>,
     ) -> Result<Vec<Device>, DeviceError> {
         let path = "/dna/intent/api/v1/network-device";
-        let query = {
-            let mut query = vec![];
-
-            if let Some(filter) = filter {
-                match filter {
-                    DeviceFilter::Family(family) => query.push(("family", family.to_string())),
-                    DeviceFilter::ManagementIPAddress(ip) => {
-                        query.push(("managementIpAddress", ip))
-                    }
-                }
-            };
-
-            query
-        };
+        let query = query.map(DeviceQuery::to_query).unwrap_or_default();
         let device_data = dnac
             .get::<Device>(path, Some(query.as_slice()), pagination)
             .await;
 
         match device_data {
             Ok(device_data) => match device_data.response {
-                ResponseType::Array(data) => Ok(data.into_iter().map(|d| d).collect()),
+                ResponseType::Array(data) => Ok(data),
                 ResponseType::Item(data) => Ok(vec![data]),
             },
-            Err(e) => {
-                event!(Level::ERROR, "{e}");
-                Err(DeviceError::GeneralError)
-            }
+            Err(e) => match e.downcast::<PaginationError>() {
+                Ok(pagination_error) => Err(pagination_error.into()),
+                Err(e) => {
+                    event!(Level::ERROR, "{e}");
+                    Err(DeviceError::GeneralError)
+                }
+            },
         }
     }
 
-    pub async fn get_all_devices(
-        dnac: &DNAC,
+    pub async fn get_all_devices<C: ApiClient>(
+        dnac: &C,
         device_family: Option<DeviceFamily>,
+    ) -> Result<Vec<Device>, DeviceError> {
+        let query = device_family.map(|family| DeviceQuery::new().family(family));
+        Device::get_all_devices_with_cancel(dnac, query.as_ref(), None).await
+    }
+
+    // stops between or during page requests once cancel is triggered
+    pub async fn get_all_devices_with_cancel<C: ApiClient>(
+        dnac: &C,
+        query: Option<&DeviceQuery>,
+        cancel: Option<&CancellationToken>,
     ) -> Result<Vec<Device>, DeviceError> {
         let mut offset = 1;
         let limit = 500;
@@ -148,16 +478,16 @@ impl Device {
                 .with_limit(limit)
                 .build();
 
-            let filter = match device_family {
-                Some(device_family) => Some(DeviceFilter::Family(device_family)),
-                None => None,
-            };
-            let current_devices = Device::get_device_list(dnac, filter, Some(pagination)).await?;
+            let current_devices = with_cancel(
+                cancel,
+                Device::get_device_list(dnac, query, Some(pagination)),
+            )
+            .await??;
             if current_devices.len() <= 1 {
-                if current_devices.len() == 1 {
-                    if let None = devices.iter().find(|s| s.id == current_devices[0].id) {
-                        devices.extend(current_devices);
-                    }
+                if current_devices.len() == 1
+                    && !devices.iter().any(|s| s.id == current_devices[0].id)
+                {
+                    devices.extend(current_devices);
                 }
                 break;
             }
@@ -169,13 +499,297 @@ impl Device {
         Ok(devices)
     }
 
-    pub async fn add_device(dnac: &DNAC, device: AddDevice) -> anyhow::Result<()> {
+    // the single device endpoints return the same record as the list, so
+    // Device is reused instead of a separate detail struct
+    pub async fn get_by_id<C: ApiClient>(
+        dnac: &C,
+        device_id: DeviceId,
+    ) -> Result<Device, DeviceError> {
+        let path = format!("/dna/intent/api/v1/network-device/{device_id}");
+
+        let device_data = dnac.get::<Device>(path.as_str(), None, None).await;
+
+        match device_data {
+            Ok(device_data) => match device_data.response {
+                ResponseType::Item(data) => Ok(data),
+                ResponseType::Array(_) => Err(DeviceError::InvalidDevice),
+            },
+            Err(e) => {
+                event!(Level::ERROR, "{e}");
+                Err(DeviceError::GeneralError)
+            }
+        }
+    }
+
+    // looks up the ethernet MAC of the AP and fetches its configuration from
+    // the wireless controller
+    #[cfg(feature = "wireless")]
+    pub async fn get_ap_config<C: ApiClient>(
+        dnac: &C,
+        device_id: DeviceId,
+    ) -> Result<AccessPointConfig, DeviceError> {
+        let device = Device::get_by_id(dnac, device_id).await?;
+        let Some(ap_mac_address) = device.ap_ethernet_mac_address else {
+            return Err(DeviceError::InvalidDevice);
+        };
+
+        AccessPoint::get_config(dnac, &ap_mac_address)
+            .await
+            .map_err(|e| {
+                event!(Level::ERROR, "{e}");
+                DeviceError::GeneralError
+            })
+    }
+
+    // serial is a single serial number, not the comma separated list of a stack
+    pub async fn get_by_serial<C: ApiClient>(
+        dnac: &C,
+        serial: &str,
+    ) -> Result<Device, DeviceError> {
+        let path = format!("/dna/intent/api/v1/network-device/serial-number/{serial}");
+
+        let device_data = dnac.get::<Device>(path.as_str(), None, None).await;
+
+        match device_data {
+            Ok(device_data) => match device_data.response {
+                ResponseType::Item(data) => Ok(data),
+                ResponseType::Array(_) => Err(DeviceError::InvalidDevice),
+            },
+            Err(e) => {
+                event!(Level::ERROR, "{e}");
+                Err(DeviceError::GeneralError)
+            }
+        }
+    }
+
+    // returns the running config as text
+    pub async fn get_config<C: ApiClient>(
+        dnac: &C,
+        device_id: DeviceId,
+    ) -> Result<String, DeviceError> {
+        let path = format!("/dna/intent/api/v1/network-device/{device_id}/config");
+
+        let config_data = dnac.get::<String>(path.as_str(), None, None).await;
+
+        match config_data {
+            Ok(config_data) => match config_data.response {
+                ResponseType::Item(data) => Ok(data),
+                ResponseType::Array(_) => Err(DeviceError::InvalidDevice),
+            },
+            Err(e) => {
+                event!(Level::ERROR, "{e}");
+                Err(DeviceError::GeneralError)
+            }
+        }
+    }
+
+    // returns the running configs of all devices DNAC has collected one for
+    pub async fn get_all_configs<C: ApiClient>(dnac: &C) -> Result<Vec<DeviceConfig>, DeviceError> {
+        let path = "/dna/intent/api/v1/network-device/config";
+
+        let config_data = dnac.get::<DeviceConfig>(path, None, None).await;
+
+        match config_data {
+            Ok(config_data) => match config_data.response {
+                ResponseType::Array(data) => Ok(data),
+                ResponseType::Item(data) => Ok(vec![data]),
+            },
+            Err(e) => {
+                event!(Level::ERROR, "{e}");
+                Err(DeviceError::GeneralError)
+            }
+        }
+    }
+
+    // None if the device is not assigned to a site
+    pub async fn get_assigned_site<C: ApiClient>(
+        dnac: &C,
+        device_id: DeviceId,
+    ) -> Result<Option<AssignedSite>, DeviceError> {
+        let path = format!("/dna/intent/api/v1/networkDevices/{device_id}/assignedToSite");
+
+        let site_data = dnac.get::<AssignedSite>(path.as_str(), None, None).await;
+
+        match site_data {
+            Ok(site_data) => match site_data.response {
+                ResponseType::Item(data) => Ok(Some(data)),
+                ResponseType::Array(mut data) => Ok(data.pop()),
+            },
+            Err(e) => {
+                event!(Level::ERROR, "{e}");
+                Err(DeviceError::GeneralError)
+            }
+        }
+    }
+
+    pub async fn get_stack_info<C: ApiClient>(
+        dnac: &C,
+        device_id: DeviceId,
+    ) -> Result<StackInfo, DeviceError> {
+        let path = format!("/dna/intent/api/v1/network-device/{device_id}/stack");
+
+        let stack_data = dnac.get::<StackInfo>(path.as_str(), None, None).await;
+
+        match stack_data {
+            Ok(stack_data) => match stack_data.response {
+                ResponseType::Item(data) => Ok(data),
+                ResponseType::Array(_) => Err(DeviceError::InvalidDevice),
+            },
+            Err(e) => {
+                event!(Level::ERROR, "{e}");
+                Err(DeviceError::GeneralError)
+            }
+        }
+    }
+
+    pub async fn get_modules<C: ApiClient>(
+        dnac: &C,
+        device_id: DeviceId,
+    ) -> Result<Vec<DeviceModule>, DeviceError> {
+        let path = "/dna/intent/api/v1/network-device/module";
+        let query = Query::new().eq("deviceId", device_id);
+
+        let module_data = dnac
+            .get::<DeviceModule>(path, Some(query.as_slice()), None)
+            .await;
+
+        match module_data {
+            Ok(module_data) => match module_data.response {
+                ResponseType::Array(data) => Ok(data),
+                ResponseType::Item(data) => Ok(vec![data]),
+            },
+            Err(e) => {
+                event!(Level::ERROR, "{e}");
+                Err(DeviceError::GeneralError)
+            }
+        }
+    }
+
+    pub async fn add_device<C: ApiClient>(dnac: &C, device: AddDevice) -> anyhow::Result<()> {
         let path = "/dna/intent/api/v1/network-device";
-        Ok(dnac.post(path, Some(device), true).await?)
+        dnac.post(path, Some(device), true).await
+    }
+
+    pub async fn update<C: ApiClient>(dnac: &C, device: UpdateDevice) -> anyhow::Result<()> {
+        let path = "/dna/intent/api/v1/network-device";
+        dnac.put(path, device, true).await
+    }
+
+    // the role is set as MANUAL, so DNAC keeps it on the next resync
+    pub async fn update_role<C: ApiClient>(
+        dnac: &C,
+        device_id: DeviceId,
+        role: DeviceRole,
+    ) -> anyhow::Result<()> {
+        let path = "/dna/intent/api/v1/network-device/brief";
+        let request = UpdateRole {
+            id: device_id,
+            role,
+            role_source: RoleSource::Manual,
+        };
+        dnac.put(path, request, true).await
+    }
+
+    // minutes between the periodic resyncs of the device
+    pub async fn update_resync_interval<C: ApiClient>(
+        dnac: &C,
+        device_id: DeviceId,
+        minutes: u32,
+    ) -> anyhow::Result<()> {
+        let path = format!("/dna/intent/api/v1/networkDevices/{device_id}/resyncIntervalSettings");
+        dnac.put(path.as_str(), ResyncInterval { interval: minutes }, true)
+            .await
+    }
+
+    // starts a resync of the devices, force also resyncs devices which are
+    // already being synced. Poll the returned task to wait for the resync.
+    pub async fn sync<C: ApiClientExt>(
+        dnac: &C,
+        device_ids: &[DeviceId],
+        force: bool,
+    ) -> anyhow::Result<TaskInfo> {
+        let path = "/dna/intent/api/v1/network-device/sync";
+        let query = Query::new().eq("forceSync", force);
+
+        dnac.put_task(path, Some(query.as_slice()), device_ids)
+            .await
+    }
+
+    // clean_config removes the configuration DNAC pushed to the device
+    pub async fn delete<C: ApiClient>(
+        dnac: &C,
+        device_id: DeviceId,
+        clean_config: bool,
+    ) -> anyhow::Result<()> {
+        let path = format!("/dna/intent/api/v1/network-device/{device_id}");
+        let query = Query::new().eq("cleanConfig", clean_config);
+
+        dnac.delete(path.as_str(), Some(query.as_slice()), true)
+            .await
+    }
+
+    // creates the export file and downloads it. Exports with a password are
+    // returned as encrypted zip archive, otherwise as CSV.
+    pub async fn export<C: ApiClientExt>(
+        dnac: &C,
+        request: ExportRequest,
+    ) -> anyhow::Result<Vec<u8>> {
+        if request.operation == ExportOperation::CredentialDetails && request.password.is_none() {
+            return Err(anyhow::anyhow!("Credential exports require a password"));
+        }
+
+        let path = "/dna/intent/api/v1/network-device/file";
+        let task_info = dnac.post_task(path, request).await?;
+        let tasks = dnac.poll_task(task_info).await?;
+
+        Ok(File::from_tasks(dnac, &tasks).await?)
+    }
+
+    // the field has to be created with Udf::create before it can be set
+    pub async fn set_udf<C: ApiClient>(
+        dnac: &C,
+        device_id: DeviceId,
+        key: &str,
+        value: &str,
+    ) -> anyhow::Result<()> {
+        let path = format!("/dna/intent/api/v1/network-device/{device_id}/user-defined-field");
+        let request = [UdfValue { name: key, value }];
+        dnac.put(path.as_str(), request, true).await
     }
 }
 
-#[derive(Debug, Default, Serialize)]
+impl Udf {
+    // name limits the result to the field with that name
+    pub async fn list<C: ApiClient>(dnac: &C, name: Option<&str>) -> Result<Vec<Udf>, DeviceError> {
+        let path = "/dna/intent/api/v1/network-device/user-defined-field";
+        let query = Query::new().eq_opt("name", name);
+
+        let udf_data = dnac.get::<Udf>(path, Some(query.as_slice()), None).await;
+
+        match udf_data {
+            Ok(udf_data) => match udf_data.response {
+                ResponseType::Array(data) => Ok(data),
+                ResponseType::Item(data) => Ok(vec![data]),
+            },
+            Err(e) => {
+                event!(Level::ERROR, "{e}");
+                Err(DeviceError::GeneralError)
+            }
+        }
+    }
+
+    pub async fn create<C: ApiClient>(
+        dnac: &C,
+        name: &str,
+        description: Option<&str>,
+    ) -> anyhow::Result<()> {
+        let path = "/dna/intent/api/v1/network-device/user-defined-field";
+        let request = CreateUdf { name, description };
+        dnac.post(path, request, true).await
+    }
+}
+
+#[derive(Default, Serialize)]
 pub struct AddDevice {
     #[serde(rename = "ipAddress")]
     pub ip_address: Vec<String>,
@@ -207,7 +821,183 @@ pub struct AddDevice {
     pub netconf_port: u16,
 }
 
-#[derive(Debug, Default, Serialize)]
+impl fmt::Debug for AddDevice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AddDevice")
+            .field("ip_address", &self.ip_address)
+            .field("device_type", &self.device_type)
+            .field("user_name", &self.user_name)
+            .field("password", &REDACTED)
+            .field("enable_password", &REDACTED)
+            .field("cli_transport", &self.cli_transport)
+            .field("snmp_version", &self.snmp_version)
+            .field("snmp_user_name", &self.snmp_user_name)
+            .field("snmp_mode", &self.snmp_mode)
+            .field("snmp_auth_passphrase", &REDACTED)
+            .field("snmp_priv_passphrase", &REDACTED)
+            .field("snmp_auth_protocol", &self.snmp_auth_protocol)
+            .field("snmp_priv_protocol", &self.snmp_priv_protocol)
+            .field("netconf_port", &self.netconf_port)
+            .finish()
+    }
+}
+
+// UpdateDevice changes the management settings of the devices in
+// ip_address, only the fields which are set get sent
+#[derive(Default, Serialize)]
+pub struct UpdateDevice {
+    #[serde(rename = "ipAddress")]
+    pub ip_address: Vec<String>,
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub device_type: Option<DeviceType>,
+    #[serde(rename = "userName", skip_serializing_if = "Option::is_none")]
+    pub user_name: Option<String>,
+    #[serde(rename = "password", skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+    #[serde(rename = "enablePassword", skip_serializing_if = "Option::is_none")]
+    pub enable_password: Option<String>,
+    #[serde(rename = "cliTransport", skip_serializing_if = "Option::is_none")]
+    pub cli_transport: Option<CliTransport>,
+    #[serde(rename = "snmpVersion", skip_serializing_if = "Option::is_none")]
+    pub snmp_version: Option<SnmpVersion>,
+    #[serde(rename = "snmpUserName", skip_serializing_if = "Option::is_none")]
+    pub snmp_user_name: Option<String>,
+    #[serde(rename = "snmpMode", skip_serializing_if = "Option::is_none")]
+    pub snmp_mode: Option<SnmpMode>,
+    #[serde(rename = "snmpAuthPassphrase", skip_serializing_if = "Option::is_none")]
+    pub snmp_auth_passphrase: Option<String>,
+    #[serde(rename = "snmpPrivPassphrase", skip_serializing_if = "Option::is_none")]
+    pub snmp_priv_passphrase: Option<String>,
+    #[serde(rename = "snmpAuthProtocol", skip_serializing_if = "Option::is_none")]
+    pub snmp_auth_protocol: Option<SnmpAuthProtocol>,
+    #[serde(rename = "snmpPrivProtocol", skip_serializing_if = "Option::is_none")]
+    pub snmp_priv_protocol: Option<SnmpPrivProtocol>,
+    #[serde(rename = "snmpRetry", skip_serializing_if = "Option::is_none")]
+    pub snmp_retry: Option<u32>,
+    #[serde(rename = "snmpTimeout", skip_serializing_if = "Option::is_none")]
+    pub snmp_timeout: Option<u32>,
+    #[serde(rename = "netconfPort", skip_serializing_if = "Option::is_none")]
+    pub netconf_port: Option<u16>,
+    #[serde(
+        rename = "updateMgmtIPaddressList",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub update_management_ip_addresses: Vec<ManagementIpChange>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ManagementIpChange {
+    #[serde(rename = "existMgmtIpAddress")]
+    pub existing: String,
+    #[serde(rename = "newMgmtIpAddress")]
+    pub new: String,
+}
+
+#[derive(Debug, Serialize)]
+struct UpdateRole {
+    id: DeviceId,
+    role: DeviceRole,
+    #[serde(rename = "roleSource")]
+    role_source: RoleSource,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum ExportOperation {
+    // includes the CLI, SNMP and NETCONF credentials of the devices
+    #[serde(rename = "CREDENTIALDETAILS")]
+    CredentialDetails,
+    #[serde(rename = "DEVICEDETAILS")]
+    DeviceDetails,
+}
+
+#[derive(Clone, Serialize)]
+pub struct ExportRequest {
+    #[serde(rename = "deviceUuids")]
+    pub device_ids: Vec<DeviceId>,
+    #[serde(rename = "operationEnum")]
+    pub operation: ExportOperation,
+    // encrypts the export, required for credential exports
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+    // limits the exported attributes, all are exported if empty
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub parameters: Vec<String>,
+}
+
+impl ExportRequest {
+    pub fn new(device_ids: Vec<DeviceId>, operation: ExportOperation) -> Self {
+        Self {
+            device_ids,
+            operation,
+            password: None,
+            parameters: vec![],
+        }
+    }
+
+    pub fn with_password(mut self, password: impl Into<String>) -> Self {
+        self.password = Some(password.into());
+        self
+    }
+}
+
+impl fmt::Debug for ExportRequest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ExportRequest")
+            .field("device_ids", &self.device_ids)
+            .field("operation", &self.operation)
+            .field("password", &self.password.as_ref().map(|_| REDACTED))
+            .field("parameters", &self.parameters)
+            .finish()
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct CreateUdf<'a> {
+    name: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<&'a str>,
+}
+
+#[derive(Debug, Serialize)]
+struct UdfValue<'a> {
+    name: &'a str,
+    value: &'a str,
+}
+
+#[derive(Debug, Serialize)]
+struct ResyncInterval {
+    interval: u32,
+}
+
+impl fmt::Debug for UpdateDevice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let redact = |secret: &Option<String>| secret.as_ref().map(|_| REDACTED);
+        f.debug_struct("UpdateDevice")
+            .field("ip_address", &self.ip_address)
+            .field("device_type", &self.device_type)
+            .field("user_name", &self.user_name)
+            .field("password", &redact(&self.password))
+            .field("enable_password", &redact(&self.enable_password))
+            .field("cli_transport", &self.cli_transport)
+            .field("snmp_version", &self.snmp_version)
+            .field("snmp_user_name", &self.snmp_user_name)
+            .field("snmp_mode", &self.snmp_mode)
+            .field("snmp_auth_passphrase", &redact(&self.snmp_auth_passphrase))
+            .field("snmp_priv_passphrase", &redact(&self.snmp_priv_passphrase))
+            .field("snmp_auth_protocol", &self.snmp_auth_protocol)
+            .field("snmp_priv_protocol", &self.snmp_priv_protocol)
+            .field("snmp_retry", &self.snmp_retry)
+            .field("snmp_timeout", &self.snmp_timeout)
+            .field("netconf_port", &self.netconf_port)
+            .field(
+                "update_management_ip_addresses",
+                &self.update_management_ip_addresses,
+            )
+            .finish()
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum DeviceType {
     #[default]
     #[serde(rename = "NETWORK_DEVICE")]
@@ -222,7 +1012,7 @@ pub enum DeviceType {
     NoDataChange,
 }
 
-#[derive(Debug, Default, Serialize)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum CliTransport {
     #[default]
     #[serde(rename = "ssh")]
@@ -231,7 +1021,7 @@ pub enum CliTransport {
     Telnet,
 }
 
-#[derive(Debug, Default, Serialize)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum SnmpVersion {
     #[default]
     #[serde(rename = "v3")]
@@ -240,7 +1030,7 @@ pub enum SnmpVersion {
     V2,
 }
 
-#[derive(Debug, Default, Serialize)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum SnmpMode {
     #[default]
     #[serde(rename = "authPriv")]
@@ -251,7 +1041,7 @@ pub enum SnmpMode {
     NoAuthNoPriv,
 }
 
-#[derive(Debug, Default, Serialize)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum SnmpAuthProtocol {
     #[default]
     #[serde(rename = "sha")]
@@ -260,23 +1050,399 @@ pub enum SnmpAuthProtocol {
     Md5,
 }
 
-#[derive(Debug, Default, Serialize)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum SnmpPrivProtocol {
     #[default]
     #[serde(rename = "AES128")]
     Aes128,
 }
 
+impl StackInfo {
+    // the ring is complete if every stack port has an active link
+    pub fn is_ring_complete(&self) -> bool {
+        !self.ports.is_empty() && self.ports.iter().all(|p| p.link_active == Some(true))
+    }
+
+    pub fn active(&self) -> Option<&StackMember> {
+        self.members
+            .iter()
+            .find(|m| m.role.as_deref() == Some("ACTIVE"))
+    }
+}
+
+impl DeviceQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn hostname(mut self, hostname: impl Into<String>) -> Self {
+        self.hostnames.push(hostname.into());
+        self
+    }
+
+    pub fn family(mut self, family: DeviceFamily) -> Self {
+        self.families.push(family);
+        self
+    }
+
+    pub fn role(mut self, role: DeviceRole) -> Self {
+        self.roles.push(role);
+        self
+    }
+
+    pub fn software_version(mut self, version: impl Into<String>) -> Self {
+        self.software_versions.push(version.into());
+        self
+    }
+
+    // e.g. Reachable, Unreachable or Ping Reachable
+    pub fn reachability_status(mut self, status: impl Into<String>) -> Self {
+        self.reachability_statuses.push(status.into());
+        self
+    }
+
+    // the endpoint filters by site via the location name, e.g. "Global/CH/ZRH"
+    pub fn location_name(mut self, location_name: impl Into<String>) -> Self {
+        self.location_names.push(location_name.into());
+        self
+    }
+
+    pub fn platform_id(mut self, platform_id: impl Into<String>) -> Self {
+        self.platform_ids.push(platform_id.into());
+        self
+    }
+
+    pub fn serial_number(mut self, serial: impl Into<String>) -> Self {
+        self.serial_numbers.push(serial.into());
+        self
+    }
+
+    pub fn management_ip_address(mut self, ip: impl Into<String>) -> Self {
+        self.management_ip_addresses.push(ip.into());
+        self
+    }
+
+    pub fn to_query(&self) -> Query<'static> {
+        Query::new()
+            .any_of("hostname", &self.hostnames)
+            .any_of("family", &self.families)
+            .any_of("role", &self.roles)
+            .any_of("softwareVersion", &self.software_versions)
+            .any_of("reachabilityStatus", &self.reachability_statuses)
+            .any_of("locationName", &self.location_names)
+            .any_of("platformId", &self.platform_ids)
+            .any_of("serialNumber", &self.serial_numbers)
+            .any_of("managementIpAddress", &self.management_ip_addresses)
+    }
+}
+
 #[async_trait::async_trait]
 impl FetchableType for Device {
-    type Filter = DeviceFilter;
+    type Filter = DeviceQuery;
     type Error = DeviceError;
 
-    async fn fetch_list(
-        dnac: &DNAC,
+    async fn fetch_list<C: ApiClient>(
+        dnac: &C,
         filter: Option<Self::Filter>,
         pagination: Option<Pagination>,
     ) -> Result<Vec<Device>, DeviceError> {
-        Device::get_device_list(dnac, filter, pagination).await
+        Device::get_device_list(dnac, filter.as_ref(), pagination).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::{anyhow, Result};
+    use serde::{de::DeserializeOwned, Serialize};
+
+    use super::*;
+    use crate::{
+        dnac::{Response, Task, TaskInfo},
+        test_util::{fixtures, MockDnac},
+    };
+
+    struct MockClient {
+        get_response: &'static str,
+    }
+
+    #[async_trait::async_trait]
+    impl ApiClient for MockClient {
+        async fn get<T>(
+            &self,
+            _path: &str,
+            _input_query: Option<&[(&str, String)]>,
+            _pagination: Option<Pagination>,
+        ) -> Result<Response<T>>
+        where
+            T: DeserializeOwned + Send,
+        {
+            Ok(serde_json::from_str(self.get_response)?)
+        }
+
+        async fn get_raw(
+            &self,
+            _path: &str,
+            _input_query: Option<&[(&str, String)]>,
+            _pagination: Option<Pagination>,
+        ) -> Result<serde_json::Value> {
+            Ok(serde_json::from_str(self.get_response)?)
+        }
+
+        async fn post<T>(&self, _path: &str, _data: T, _poll: bool) -> Result<()>
+        where
+            T: Serialize + Send,
+        {
+            Err(anyhow!("not mocked"))
+        }
+
+        async fn post_task<T>(&self, _path: &str, _data: T) -> Result<TaskInfo>
+        where
+            T: Serialize + Send,
+        {
+            Err(anyhow!("not mocked"))
+        }
+
+        async fn put<T>(&self, _path: &str, _data: T, _poll: bool) -> Result<()>
+        where
+            T: Serialize + Send,
+        {
+            Err(anyhow!("not mocked"))
+        }
+
+        async fn delete(
+            &self,
+            _path: &str,
+            _input_query: Option<&[(&str, String)]>,
+            _poll: bool,
+        ) -> Result<()> {
+            Err(anyhow!("not mocked"))
+        }
+
+        async fn poll_task(&self, _task_info: TaskInfo) -> Result<Vec<Task>> {
+            Err(anyhow!("not mocked"))
+        }
+    }
+
+    #[test]
+    fn test_parse_device() {
+        let device = r#"
+          {"id":"8c1e2b7a-2d44-4f0e-a1e3-0d6c9b3e5a21","hostname":"zrh-core-rt01","managementIpAddress":"10.10.0.1","macAddress":"70:1f:53:aa:10:01","serialNumber":"FDO2231X0AB","platformId":"C8300-1N1S-6T","series":"Cisco Catalyst 8300 Series Edge Platforms","type":"Cisco Catalyst 8300-1N1S-6T Router","family":"Routers","role":"BORDER ROUTER","roleSource":"AUTO","softwareType":"IOS-XE","softwareVersion":"17.9.4a","upTime":"41 days, 6:12:03.42","uptimeSeconds":3564723,"lastUpdated":"2024-11-28 16:30:27","lastUpdateTime":1732811427209,"reachabilityStatus":"Reachable","reachabilityFailureReason":"","collectionStatus":"Managed","collectionInterval":"Global Default","managedAtleastOnce":true,"location":null,"locationName":null,"apManagerInterfaceIp":"","associatedWlcIp":"","tagCount":"0","deviceSupportLevel":"Supported"}
+          "#;
+
+        let device: Device = serde_json::from_str(device).unwrap();
+        assert_eq!(device.platform_id.as_deref(), Some("C8300-1N1S-6T"));
+        assert_eq!(device.uptime_seconds, Some(3564723));
+        assert_eq!(device.managed_at_least_once, Some(true));
+        assert_eq!(device.role, Some(DeviceRole::BorderRouter));
+        assert_eq!(device.role_source, Some(RoleSource::Auto));
+    }
+
+    #[test]
+    fn test_parse_unknown_family_and_status() {
+        let device = r#"
+          {"id":"8c1e2b7a-2d44-4f0e-a1e3-0d6c9b3e5a21","managementIpAddress":"10.10.0.9","family":"Third Party Device","collectionStatus":"Collection Pending"}
+          "#;
+
+        let device: Device = serde_json::from_str(device).unwrap();
+        assert_eq!(
+            device.family,
+            Some(DeviceFamily::Other("Third Party Device".to_string()))
+        );
+        assert_eq!(
+            device.collection_status,
+            DeviceStatus::Other("Collection Pending".to_string())
+        );
+        assert_eq!("Unified AP".parse(), Ok(DeviceFamily::UnifiedAp));
+        assert_eq!(DeviceStatus::InProgress.to_string(), "In Progress");
+    }
+
+    #[test]
+    fn test_update_device() {
+        let device = UpdateDevice {
+            ip_address: vec!["10.10.0.11".to_string()],
+            password: Some("secret".to_string()),
+            update_management_ip_addresses: vec![ManagementIpChange {
+                existing: "10.10.0.11".to_string(),
+                new: "10.20.0.11".to_string(),
+            }],
+            ..Default::default()
+        };
+
+        let payload = serde_json::to_value(&device).unwrap();
+        assert_eq!(payload["password"], "secret");
+        assert!(payload.get("userName").is_none());
+        assert_eq!(
+            payload["updateMgmtIPaddressList"][0]["newMgmtIpAddress"],
+            "10.20.0.11"
+        );
+        assert!(!format!("{device:?}").contains("secret"));
+    }
+
+    #[test]
+    fn test_device_query() {
+        let query = DeviceQuery::new()
+            .family(DeviceFamily::SwitchesAndHubs)
+            .family(DeviceFamily::Routers)
+            .software_version("17.9.4a")
+            .location_name("Global/CH/ZRH");
+
+        assert_eq!(
+            query.to_query().as_slice(),
+            &[
+                ("family", "Switches and Hubs".to_string()),
+                ("family", "Routers".to_string()),
+                ("softwareVersion", "17.9.4a".to_string()),
+                ("locationName", "Global/CH/ZRH".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_device_filter() {
+        let query = DeviceQuery::from(DeviceFilter::Families(vec![
+            DeviceFamily::SwitchesAndHubs,
+            DeviceFamily::Routers,
+        ]));
+        assert_eq!(
+            query.to_query().as_slice(),
+            &[
+                ("family", "Switches and Hubs".to_string()),
+                ("family", "Routers".to_string()),
+            ]
+        );
+
+        let query = DeviceQuery::from(DeviceFilter::SerialNumber("FOC1234X0AB".to_string()));
+        assert_eq!(
+            query.to_query().as_slice(),
+            &[("serialNumber", "FOC1234X0AB".to_string())]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_config() {
+        let client = MockClient {
+            get_response: r#"
+              {"response":"\nBuilding configuration...\n\nhostname zrh-acc-sw01\n!\nend\n","version":"1.0"}
+              "#,
+        };
+
+        let device_id = "3f0ac5d4-6f3d-4b5e-9d2b-5b0f3a3c2f10".parse().unwrap();
+        let config = Device::get_config(&client, device_id).await.unwrap();
+        assert!(config.contains("hostname zrh-acc-sw01"));
+    }
+
+    #[tokio::test]
+    async fn test_export() {
+        let mock = MockDnac::start().await;
+        mock.mock_response(
+            "POST",
+            "/dna/intent/api/v1/network-device/file",
+            202,
+            fixtures::TASK_INFO,
+        )
+        .await;
+        mock.mock_response(
+            "GET",
+            &format!("/api/v1/task/{}/tree/", fixtures::TASK_ID),
+            200,
+            r#"
+              {"response":[{"id":"0193739c-0d88-78e4-ba0f-d82889fca555","progress":"Export done","additionalStatusURL":"/file/6f3d4b5e-9d2b-5b0f-3a3c-2f103f0ac5d4","startTime":1732811427209,"endTime":1732811429512,"serviceType":"Inventory service","isError":false,"instanceTenantId":"6307971e4289f95403c86831","version":1732811427309}]}
+              "#,
+        )
+        .await;
+        mock.mock_response(
+            "GET",
+            "/dna/intent/api/v1/file/6f3d4b5e-9d2b-5b0f-3a3c-2f103f0ac5d4",
+            200,
+            r#"["export"]"#,
+        )
+        .await;
+
+        let device_id = "3f0ac5d4-6f3d-4b5e-9d2b-5b0f3a3c2f10".parse().unwrap();
+        let request = ExportRequest::new(vec![device_id], ExportOperation::DeviceDetails);
+        let file = Device::export(&mock.dnac, request).await.unwrap();
+        assert_eq!(file, br#"["export"]"#);
+
+        let request = ExportRequest::new(vec![device_id], ExportOperation::CredentialDetails);
+        assert!(Device::export(&mock.dnac, request).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_get_assigned_site() {
+        let client = MockClient {
+            get_response: r#"
+              {"response":{"deviceId":"3f0ac5d4-6f3d-4b5e-9d2b-5b0f3a3c2f10","siteId":"a4f3a3c2-f103-4f0a-8c5d-46f3d4b5e9d2","siteNameHierarchy":"Global/CH/ZRH/HQ/Floor 2","siteType":"floor"},"version":"1.0"}
+              "#,
+        };
+
+        let device_id = "3f0ac5d4-6f3d-4b5e-9d2b-5b0f3a3c2f10".parse().unwrap();
+        let site = Device::get_assigned_site(&client, device_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(site.site_type.as_deref(), Some("floor"));
+
+        let client = MockClient {
+            get_response: r#"{"response":[],"version":"1.0"}"#,
+        };
+        let site = Device::get_assigned_site(&client, device_id).await.unwrap();
+        assert!(site.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_list_udf() {
+        let client = MockClient {
+            get_response: r#"
+              {"response":[{"id":"9a7e1c52-3b1d-4f0e-8c1e-2b7a2d444f0e","name":"asset-tag","description":"Inventory asset tag"},{"id":"0d6c9b3e-5a21-4f0e-a1e3-8c1e2b7a2d44","name":"owner"}]}
+              "#,
+        };
+
+        let udfs = Udf::list(&client, None).await.unwrap();
+        assert_eq!(udfs.len(), 2);
+        assert_eq!(udfs[0].name, "asset-tag");
+        assert_eq!(udfs[1].description, None);
+    }
+
+    #[tokio::test]
+    async fn test_get_stack_info() {
+        let client = MockClient {
+            get_response: r#"
+              {"response":{"deviceId":"3f0ac5d4-6f3d-4b5e-9d2b-5b0f3a3c2f10","stackSwitchInfo":[{"hwPriority":0,"macAddress":"70:1f:53:aa:10:80","numNextReload":1,"platformId":"C9300-48P","role":"ACTIVE","serialNumber":"FOC2231X0AB","softwareImage":"17.9.4a","stackMemberNumber":1,"state":"READY","switchPriority":15},{"hwPriority":0,"macAddress":"70:1f:53:aa:11:80","numNextReload":2,"platformId":"C9300-48P","role":"STANDBY","serialNumber":"FOC2231X0AC","softwareImage":"17.9.4a","stackMemberNumber":2,"state":"READY","switchPriority":14}],"stackPortInfo":[{"isSynchOk":"Yes","linkActive":true,"linkOk":true,"name":"StackPort1/1","neighborPort":"StackPort2/2","nrLinkOkChanges":1,"stackCableLengthInfo":"50cm","stackPortOperStatusInfo":"Up","switchPort":"1/1"},{"isSynchOk":"Yes","linkActive":false,"linkOk":false,"name":"StackPort1/2","neighborPort":"","nrLinkOkChanges":3,"stackCableLengthInfo":"50cm","stackPortOperStatusInfo":"Down","switchPort":"1/2"}]}}
+              "#,
+        };
+
+        let device_id = "3f0ac5d4-6f3d-4b5e-9d2b-5b0f3a3c2f10".parse().unwrap();
+        let stack = Device::get_stack_info(&client, device_id).await.unwrap();
+        assert_eq!(stack.members.len(), 2);
+        assert_eq!(stack.active().unwrap().switch_number, 1);
+        assert!(!stack.is_ring_complete());
+    }
+
+    #[tokio::test]
+    async fn test_get_by_id() {
+        let client = MockClient {
+            get_response: r#"
+              {"response":{"id":"3f0ac5d4-6f3d-4b5e-9d2b-5b0f3a3c2f10","hostname":"sw1","managementIpAddress":"10.0.0.1","family":"Switches and Hubs","softwareVersion":"17.9.4a","reachabilityStatus":"Reachable","collectionStatus":"Managed","upTime":"12 days, 3:02:11.00"}}
+              "#,
+        };
+
+        let device_id = "3f0ac5d4-6f3d-4b5e-9d2b-5b0f3a3c2f10".parse().unwrap();
+        let device = Device::get_by_id(&client, device_id).await.unwrap();
+        assert_eq!(device.id, device_id);
+        assert_eq!(device.software_version.as_deref(), Some("17.9.4a"));
+    }
+
+    #[tokio::test]
+    async fn test_get_device_list_with_mock() {
+        let client = MockClient {
+            get_response: r#"
+              {"response":[{"id":"3f0ac5d4-6f3d-4b5e-9d2b-5b0f3a3c2f10","collectionStatus":"Managed","managementIpAddress":"10.0.0.1","hostname":"sw1","family":"Switches and Hubs"}]}
+              "#,
+        };
+
+        let devices = Device::get_device_list(&client, None, None).await.unwrap();
+        assert_eq!(devices.len(), 1);
+        assert_eq!(devices[0].hostname.as_deref(), Some("sw1"));
     }
 }
@@ -0,0 +1,649 @@
+use core::fmt;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::{event, Level};
+use uuid::Uuid;
+
+use crate::{
+    devices::{AddDevice, CliTransport, SnmpAuthProtocol, SnmpMode, SnmpPrivProtocol, SnmpVersion},
+    dnac::{ApiClient, ResponseType, REDACTED},
+};
+
+pub struct Discovery;
+
+// how long preflight waits for its discovery, a discovery of a handful of
+// IPs usually completes within a minute
+const PREFLIGHT_MAX_WAIT: Duration = Duration::from_secs(600);
+
+// DiscoveryRequest starts a discovery, the credentials are either given
+// inline or referenced by the ids of global credentials
+#[derive(Default, Serialize)]
+pub struct DiscoveryRequest<'a> {
+    pub name: String,
+    #[serde(rename = "discoveryType")]
+    pub discovery_type: DiscoveryType,
+    // the ranges, e.g. "10.0.0.1-10.0.0.254,10.0.1.1-10.0.1.254", or the
+    // seed device for CDP and LLDP
+    #[serde(rename = "ipAddressList")]
+    pub ip_address_list: String,
+    // ranges which are skipped, in the same format as ip_address_list
+    #[serde(rename = "ipFilterList", skip_serializing_if = "Vec::is_empty")]
+    pub ip_filter_list: Vec<String>,
+    // the number of hops from the seed device
+    #[serde(rename = "cdpLevel", skip_serializing_if = "Option::is_none")]
+    pub cdp_level: Option<u32>,
+    #[serde(rename = "lldpLevel", skip_serializing_if = "Option::is_none")]
+    pub lldp_level: Option<u32>,
+    #[serde(rename = "protocolOrder")]
+    pub protocol_order: CliTransport,
+    #[serde(
+        rename = "globalCredentialIdList",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub global_credential_id_list: Vec<&'a str>,
+    #[serde(rename = "userNameList", skip_serializing_if = "Vec::is_empty")]
+    pub user_name_list: Vec<&'a str>,
+    #[serde(rename = "passwordList", skip_serializing_if = "Vec::is_empty")]
+    pub password_list: Vec<&'a str>,
+    #[serde(rename = "enablePasswordList", skip_serializing_if = "Vec::is_empty")]
+    pub enable_password_list: Vec<&'a str>,
+    #[serde(flatten)]
+    pub snmp: Option<DiscoverySnmp<'a>>,
+    // the SNMP timeout in seconds and the number of retries
+    #[serde(rename = "timeout", skip_serializing_if = "Option::is_none")]
+    pub snmp_timeout: Option<u32>,
+    #[serde(rename = "retry", skip_serializing_if = "Option::is_none")]
+    pub snmp_retry: Option<u32>,
+    #[serde(rename = "netconfPort", skip_serializing_if = "Option::is_none")]
+    pub netconf_port: Option<String>,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum DiscoveryType {
+    #[serde(rename = "Single")]
+    Single,
+    #[serde(rename = "Range")]
+    Range,
+    #[default]
+    #[serde(rename = "Multi Range")]
+    MultiRange,
+    #[serde(rename = "CDP")]
+    Cdp,
+    #[serde(rename = "LLDP")]
+    Lldp,
+}
+
+// inline SNMP credentials, they are sent together with the snmpVersion
+#[derive(Serialize)]
+#[serde(tag = "snmpVersion")]
+pub enum DiscoverySnmp<'a> {
+    #[serde(rename = "v2")]
+    V2 {
+        #[serde(rename = "snmpROCommunity")]
+        read_community: &'a str,
+        #[serde(rename = "snmpRWCommunity", skip_serializing_if = "Option::is_none")]
+        write_community: Option<&'a str>,
+    },
+    #[serde(rename = "v3")]
+    V3 {
+        #[serde(rename = "snmpUserName")]
+        user_name: &'a str,
+        #[serde(rename = "snmpMode")]
+        mode: SnmpMode,
+        #[serde(rename = "snmpAuthPassphrase")]
+        auth_passphrase: &'a str,
+        #[serde(rename = "snmpAuthProtocol")]
+        auth_protocol: SnmpAuthProtocol,
+        #[serde(rename = "snmpPrivPassphrase")]
+        priv_passphrase: &'a str,
+        #[serde(rename = "snmpPrivProtocol")]
+        priv_protocol: SnmpPrivProtocol,
+    },
+}
+
+impl fmt::Debug for DiscoveryRequest<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DiscoveryRequest")
+            .field("name", &self.name)
+            .field("discovery_type", &self.discovery_type)
+            .field("ip_address_list", &self.ip_address_list)
+            .field("ip_filter_list", &self.ip_filter_list)
+            .field("cdp_level", &self.cdp_level)
+            .field("lldp_level", &self.lldp_level)
+            .field("protocol_order", &self.protocol_order)
+            .field("global_credential_id_list", &self.global_credential_id_list)
+            .field("user_name_list", &self.user_name_list)
+            .field("password_list", &REDACTED)
+            .field("enable_password_list", &REDACTED)
+            .field("snmp", &self.snmp)
+            .field("snmp_timeout", &self.snmp_timeout)
+            .field("snmp_retry", &self.snmp_retry)
+            .field("netconf_port", &self.netconf_port)
+            .finish()
+    }
+}
+
+impl fmt::Debug for DiscoverySnmp<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DiscoverySnmp::V2 { .. } => f
+                .debug_struct("V2")
+                .field("read_community", &REDACTED)
+                .field("write_community", &REDACTED)
+                .finish(),
+            DiscoverySnmp::V3 {
+                user_name,
+                mode,
+                auth_protocol,
+                priv_protocol,
+                ..
+            } => f
+                .debug_struct("V3")
+                .field("user_name", user_name)
+                .field("mode", mode)
+                .field("auth_passphrase", &REDACTED)
+                .field("auth_protocol", auth_protocol)
+                .field("priv_passphrase", &REDACTED)
+                .field("priv_protocol", priv_protocol)
+                .finish(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DiscoveryJob {
+    pub id: String,
+    pub name: String,
+    #[serde(rename = "discoveryCondition")]
+    pub discovery_condition: Option<String>,
+    #[serde(rename = "discoveryStatus")]
+    pub discovery_status: Option<String>,
+    #[serde(rename = "numDevices")]
+    pub num_devices: Option<u64>,
+    #[serde(rename = "discoveryType")]
+    pub discovery_type: Option<String>,
+    #[serde(rename = "ipAddressList")]
+    pub ip_address_list: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DiscoveredDevice {
+    pub id: Option<String>,
+    #[serde(rename = "managementIpAddress")]
+    pub management_ip_address: String,
+    pub hostname: Option<String>,
+    #[serde(rename = "reachabilityStatus")]
+    pub reachability_status: Option<String>,
+    #[serde(rename = "reachabilityFailureReason")]
+    pub reachability_failure_reason: Option<String>,
+    #[serde(rename = "cliStatus")]
+    pub cli_status: Option<String>,
+    #[serde(rename = "snmpStatus")]
+    pub snmp_status: Option<String>,
+    #[serde(rename = "netconfStatus")]
+    pub netconf_status: Option<String>,
+    #[serde(rename = "platformId")]
+    pub platform_id: Option<String>,
+    #[serde(rename = "softwareVersion")]
+    pub software_version: Option<String>,
+    #[serde(rename = "inventoryCollectionStatus")]
+    pub inventory_collection_status: Option<String>,
+}
+
+#[derive(Serialize)]
+struct StopDiscovery<'a> {
+    id: &'a str,
+    #[serde(rename = "discoveryStatus")]
+    discovery_status: &'a str,
+}
+
+// the discovery list is paged by path, the start index is 1 based
+const DISCOVERY_PAGE_SIZE: usize = 500;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum PreflightVerdict {
+    Ok,
+    Unreachable(String),
+    WrongCliCredentials,
+    WrongSnmpCredentials,
+    NotDiscovered,
+}
+
+#[derive(Debug)]
+pub struct PreflightResult {
+    pub ip_address: String,
+    pub verdict: PreflightVerdict,
+    pub device: Option<DiscoveredDevice>,
+}
+
+#[derive(Debug, Error)]
+pub enum DiscoveryError {
+    #[error("General Discovery Error")]
+    GeneralError,
+    #[error("Task failed")]
+    TaskFailed,
+    #[error("Discovery ended with condition {0}")]
+    DiscoveryFailed(String),
+    #[error("Discovery did not complete within {0:?}")]
+    Timeout(Duration),
+    #[error("SNMP {0:?} is not supported by the preflight")]
+    UnsupportedSnmpVersion(SnmpVersion),
+}
+
+impl Discovery {
+    // starts a discovery and returns the id of the created discovery job
+    pub async fn start<C: ApiClient>(
+        dnac: &C,
+        request: DiscoveryRequest<'_>,
+    ) -> Result<String, DiscoveryError> {
+        let path = "/dna/intent/api/v1/discovery";
+
+        let task_info = dnac.post_task(path, request).await.map_err(|e| {
+            event!(Level::ERROR, "{e}");
+            DiscoveryError::GeneralError
+        })?;
+        let task_id = task_info.task_id.clone();
+
+        let tasks = dnac.poll_task(task_info).await.map_err(|e| {
+            event!(Level::ERROR, "{e}");
+            DiscoveryError::TaskFailed
+        })?;
+
+        // the discovery id is returned as progress of the root task
+        tasks
+            .into_iter()
+            .find(|t| t.id == task_id)
+            .map(|t| t.progress)
+            .ok_or(DiscoveryError::GeneralError)
+    }
+
+    pub async fn list<C: ApiClient>(dnac: &C) -> Result<Vec<DiscoveryJob>, DiscoveryError> {
+        let mut discoveries = vec![];
+
+        loop {
+            let path = format!(
+                "/dna/intent/api/v1/discovery/{}/{DISCOVERY_PAGE_SIZE}",
+                discoveries.len() + 1
+            );
+            let discovery_data = dnac.get::<DiscoveryJob>(path.as_str(), None, None).await;
+
+            let page = match discovery_data {
+                Ok(discovery_data) => match discovery_data.response {
+                    ResponseType::Array(data) => data,
+                    ResponseType::Item(data) => vec![data],
+                },
+                Err(e) => {
+                    event!(Level::ERROR, "{e}");
+                    return Err(DiscoveryError::GeneralError);
+                }
+            };
+
+            let last_page = page.len() < DISCOVERY_PAGE_SIZE;
+            discoveries.extend(page);
+            if last_page {
+                return Ok(discoveries);
+            }
+        }
+    }
+
+    pub async fn get<C: ApiClient>(
+        dnac: &C,
+        discovery_id: &str,
+    ) -> Result<DiscoveryJob, DiscoveryError> {
+        let path = format!("/dna/intent/api/v1/discovery/{discovery_id}");
+
+        let discovery_data = dnac.get::<DiscoveryJob>(path.as_str(), None, None).await;
+
+        match discovery_data {
+            Ok(discovery_data) => match discovery_data.response {
+                ResponseType::Item(data) => Ok(data),
+                ResponseType::Array(_) => Err(DiscoveryError::GeneralError),
+            },
+            Err(e) => {
+                event!(Level::ERROR, "{e}");
+                Err(DiscoveryError::GeneralError)
+            }
+        }
+    }
+
+    pub async fn get_devices<C: ApiClient>(
+        dnac: &C,
+        discovery_id: &str,
+    ) -> Result<Vec<DiscoveredDevice>, DiscoveryError> {
+        let path = format!("/dna/intent/api/v1/discovery/{discovery_id}/network-device");
+
+        let device_data = dnac
+            .get::<DiscoveredDevice>(path.as_str(), None, None)
+            .await;
+
+        match device_data {
+            Ok(device_data) => match device_data.response {
+                ResponseType::Array(data) => Ok(data),
+                ResponseType::Item(data) => Ok(vec![data]),
+            },
+            Err(e) => {
+                event!(Level::ERROR, "{e}");
+                Err(DiscoveryError::GeneralError)
+            }
+        }
+    }
+
+    // stops a running discovery, the devices found so far are kept
+    pub async fn stop<C: ApiClient>(dnac: &C, discovery_id: &str) -> Result<(), DiscoveryError> {
+        let path = "/dna/intent/api/v1/discovery";
+        let request = StopDiscovery {
+            id: discovery_id,
+            discovery_status: "Inactive",
+        };
+
+        dnac.put(path, request, true).await.map_err(|e| {
+            event!(Level::ERROR, "{e}");
+            DiscoveryError::GeneralError
+        })
+    }
+
+    pub async fn delete<C: ApiClient>(dnac: &C, discovery_id: &str) -> Result<(), DiscoveryError> {
+        let path = format!("/dna/intent/api/v1/discovery/{discovery_id}");
+
+        dnac.delete(path.as_str(), None, true).await.map_err(|e| {
+            event!(Level::ERROR, "{e}");
+            DiscoveryError::GeneralError
+        })
+    }
+
+    // waits until the discovery reached the Complete condition, fails if it
+    // was aborted or didn't complete within max_wait
+    pub async fn wait_for_completion<C: ApiClient>(
+        dnac: &C,
+        discovery_id: &str,
+        max_wait: Duration,
+    ) -> Result<DiscoveryJob, DiscoveryError> {
+        let wait = async {
+            loop {
+                let discovery = Discovery::get(dnac, discovery_id).await?;
+                match discovery.discovery_condition.as_deref() {
+                    Some("Complete") => return Ok(discovery),
+                    Some(condition @ ("Aborted" | "Stopped" | "Failed" | "Discarded")) => {
+                        return Err(DiscoveryError::DiscoveryFailed(condition.to_string()))
+                    }
+                    _ => (),
+                }
+
+                event!(Level::DEBUG, "Discovery is still running, sleep for 5 sec");
+                tokio::time::sleep(Duration::from_secs(5)).await;
+            }
+        };
+
+        tokio::time::timeout(max_wait, wait)
+            .await
+            .map_err(|_| DiscoveryError::Timeout(max_wait))?
+    }
+
+    // preflight runs a temporary discovery with the credentials of the
+    // AddDevice against all its IPs and reports a verdict per IP, the
+    // discovery is removed again afterwards. Only SNMPv3 devices can be
+    // checked, the AddDevice doesn't carry the v2 communities.
+    pub async fn preflight<C: ApiClient>(
+        dnac: &C,
+        device: &AddDevice,
+    ) -> Result<Vec<PreflightResult>, DiscoveryError> {
+        let snmp = match device.snmp_version {
+            SnmpVersion::V3 => DiscoverySnmp::V3 {
+                user_name: &device.snmp_user_name,
+                mode: device.snmp_mode,
+                auth_passphrase: &device.snmp_auth_passphrase,
+                auth_protocol: device.snmp_auth_protocol,
+                priv_passphrase: &device.snmp_priv_passphrase,
+                priv_protocol: device.snmp_priv_protocol,
+            },
+            SnmpVersion::V2 => return Err(DiscoveryError::UnsupportedSnmpVersion(SnmpVersion::V2)),
+        };
+
+        let request = DiscoveryRequest {
+            name: format!("preflight-{}", Uuid::new_v4()),
+            discovery_type: DiscoveryType::MultiRange,
+            ip_address_list: device
+                .ip_address
+                .iter()
+                .map(|ip| format!("{ip}-{ip}"))
+                .collect::<Vec<_>>()
+                .join(","),
+            protocol_order: device.cli_transport,
+            user_name_list: vec![&device.user_name],
+            password_list: vec![&device.password],
+            enable_password_list: vec![&device.enable_password],
+            snmp: Some(snmp),
+            netconf_port: Some(device.netconf_port.to_string()),
+            ..Default::default()
+        };
+
+        let discovery_id = Discovery::start(dnac, request).await?;
+        let devices =
+            match Discovery::wait_for_completion(dnac, &discovery_id, PREFLIGHT_MAX_WAIT).await {
+                Ok(_) => Discovery::get_devices(dnac, &discovery_id).await,
+                Err(e) => Err(e),
+            };
+
+        // we always try to clean up, even if fetching the result failed
+        if let Err(e) = Discovery::delete(dnac, &discovery_id).await {
+            event!(
+                Level::WARN,
+                "Failed to delete preflight discovery {discovery_id}: {e}"
+            );
+        }
+
+        let mut devices = devices?;
+        Ok(device
+            .ip_address
+            .iter()
+            .map(|ip| {
+                let device = devices
+                    .iter()
+                    .position(|d| d.management_ip_address == *ip)
+                    .map(|index| devices.swap_remove(index));
+                PreflightResult {
+                    ip_address: ip.clone(),
+                    verdict: device
+                        .as_ref()
+                        .map(DiscoveredDevice::verdict)
+                        .unwrap_or(PreflightVerdict::NotDiscovered),
+                    device,
+                }
+            })
+            .collect())
+    }
+}
+
+impl DiscoveredDevice {
+    pub fn is_reachable(&self) -> bool {
+        self.reachability_status.as_deref() == Some("Success")
+    }
+
+    pub fn verdict(&self) -> PreflightVerdict {
+        if self.reachability_status.as_deref() != Some("Success")
+            && self.cli_status.as_deref() != Some("Success")
+            && self.snmp_status.as_deref() != Some("Success")
+        {
+            return PreflightVerdict::Unreachable(
+                self.reachability_failure_reason.clone().unwrap_or_default(),
+            );
+        }
+
+        if self.cli_status.as_deref() != Some("Success") {
+            return PreflightVerdict::WrongCliCredentials;
+        }
+
+        if self.snmp_status.as_deref() != Some("Success") {
+            return PreflightVerdict::WrongSnmpCredentials;
+        }
+
+        PreflightVerdict::Ok
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{fixtures, MockDnac};
+
+    #[tokio::test]
+    async fn test_preflight_snmp_v2() {
+        let mock = MockDnac::start().await;
+        let device = AddDevice {
+            ip_address: vec!["10.0.0.1".to_string()],
+            snmp_version: SnmpVersion::V2,
+            ..Default::default()
+        };
+
+        let result = Discovery::preflight(&mock.dnac, &device).await;
+        assert!(matches!(
+            result,
+            Err(DiscoveryError::UnsupportedSnmpVersion(SnmpVersion::V2))
+        ));
+        assert!(mock.server.received_requests().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_start_cdp_discovery() {
+        let mock = MockDnac::start().await;
+        mock.mock_response(
+            "POST",
+            "/dna/intent/api/v1/discovery",
+            202,
+            fixtures::TASK_INFO,
+        )
+        .await;
+        mock.mock_response(
+            "GET",
+            &format!("/api/v1/task/{}/tree/", fixtures::TASK_ID),
+            200,
+            &format!(
+                r#"{{"response":[{{"id":"{}","progress":"42","startTime":1732811427209,"endTime":1732811429512,"serviceType":"Inventory service","isError":false,"instanceTenantId":"6307971e4289f95403c86831","version":1732811427309}}]}}"#,
+                fixtures::TASK_ID
+            ),
+        )
+        .await;
+
+        let request = DiscoveryRequest {
+            name: "zrh-core".to_string(),
+            discovery_type: DiscoveryType::Cdp,
+            ip_address_list: "10.0.0.1".to_string(),
+            cdp_level: Some(4),
+            global_credential_id_list: vec![
+                "2b7a2d44-4f0e-4a1e-80d6-c9b3e5a218c1",
+                "4f0e2b7a-2d44-4a1e-80d6-c9b3e5a218c1",
+            ],
+            snmp: Some(DiscoverySnmp::V2 {
+                read_community: "public",
+                write_community: None,
+            }),
+            ..Default::default()
+        };
+        assert!(!format!("{request:?}").contains("public"));
+
+        let discovery_id = Discovery::start(&mock.dnac, request).await.unwrap();
+        assert_eq!(discovery_id, "42");
+
+        let requests = mock.server.received_requests().await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+        assert_eq!(body["discoveryType"], "CDP");
+        assert_eq!(body["cdpLevel"], 4);
+        assert_eq!(body["snmpVersion"], "v2");
+        assert_eq!(body["snmpROCommunity"], "public");
+        assert!(body.get("userNameList").is_none());
+    }
+
+    #[test]
+    fn test_preflight_verdict() {
+        let device = r#"
+          {"managementIpAddress":"10.0.0.1","reachabilityStatus":"Success","cliStatus":"Failure","snmpStatus":"Success"}
+          "#;
+
+        let device: DiscoveredDevice = serde_json::from_str(device).unwrap();
+        assert_eq!(device.verdict(), PreflightVerdict::WrongCliCredentials);
+    }
+
+    #[tokio::test]
+    async fn test_discovery_lifecycle() {
+        let mock = MockDnac::start().await;
+        mock.mock_response(
+            "GET",
+            "/dna/intent/api/v1/discovery/1/500",
+            200,
+            r#"
+              {"response":[{"id":"42","name":"zrh-core","discoveryCondition":"In Progress","discoveryStatus":"Active","numDevices":2,"discoveryType":"CDP","ipAddressList":"10.0.0.1"}],"version":"1.0"}
+              "#,
+        )
+        .await;
+        mock.mock_response(
+            "GET",
+            "/dna/intent/api/v1/discovery/42/network-device",
+            200,
+            r#"
+              {"response":[{"id":"3f0ac5d4-6f3d-4b5e-9d2b-5b0f3a3c2f10","managementIpAddress":"10.0.0.1","hostname":"zrh-core-01","reachabilityStatus":"Success","cliStatus":"Success","snmpStatus":"Success","platformId":"C9500-24Y4C","softwareVersion":"17.9.4a","inventoryCollectionStatus":"Managed"},
+                           {"managementIpAddress":"10.0.0.2","reachabilityStatus":"Unreachable","reachabilityFailureReason":"SNMP Timeouts","cliStatus":"Not Used","snmpStatus":"Failure"}],"version":"1.0"}
+              "#,
+        )
+        .await;
+        mock.mock_task("PUT", "/dna/intent/api/v1/discovery", false)
+            .await;
+        mock.mock_task("DELETE", "/dna/intent/api/v1/discovery/42", false)
+            .await;
+
+        let discoveries = Discovery::list(&mock.dnac).await.unwrap();
+        assert_eq!(discoveries.len(), 1);
+        assert_eq!(discoveries[0].discovery_type.as_deref(), Some("CDP"));
+
+        let devices = Discovery::get_devices(&mock.dnac, &discoveries[0].id)
+            .await
+            .unwrap();
+        let reachable: Vec<&str> = devices
+            .iter()
+            .filter(|d| d.is_reachable())
+            .map(|d| d.management_ip_address.as_str())
+            .collect();
+        assert_eq!(reachable, vec!["10.0.0.1"]);
+
+        Discovery::stop(&mock.dnac, "42").await.unwrap();
+        Discovery::delete(&mock.dnac, "42").await.unwrap();
+
+        let requests = mock.server.received_requests().await.unwrap();
+        let stop = requests
+            .iter()
+            .find(|r| r.method.as_str() == "PUT")
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&stop.body).unwrap();
+        assert_eq!(body["discoveryStatus"], "Inactive");
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_completion() {
+        let mock = MockDnac::start().await;
+        mock.mock_response(
+            "GET",
+            "/dna/intent/api/v1/discovery/42",
+            200,
+            r#"{"response":{"id":"42","name":"zrh-core","discoveryCondition":"In Progress","discoveryStatus":"Active"},"version":"1.0"}"#,
+        )
+        .await;
+        mock.mock_response(
+            "GET",
+            "/dna/intent/api/v1/discovery/43",
+            200,
+            r#"{"response":{"id":"43","name":"zrh-edge","discoveryCondition":"Aborted","discoveryStatus":"Inactive"},"version":"1.0"}"#,
+        )
+        .await;
+
+        let result =
+            Discovery::wait_for_completion(&mock.dnac, "42", Duration::from_millis(100)).await;
+        assert!(matches!(result, Err(DiscoveryError::Timeout(_))));
+
+        let result =
+            Discovery::wait_for_completion(&mock.dnac, "43", Duration::from_secs(10)).await;
+        assert!(
+            matches!(result, Err(DiscoveryError::DiscoveryFailed(condition)) if condition == "Aborted")
+        );
+    }
+}
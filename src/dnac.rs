@@ -1,24 +1,46 @@
 use core::fmt;
-use std::{error::Error, fs};
+use std::{
+    error::Error,
+    fs,
+    future::Future,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use anyhow::{anyhow, Result};
-use reqwest::StatusCode;
+use reqwest::{
+    header::{HeaderMap, HeaderName, HeaderValue},
+    Method, StatusCode, Url,
+};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use serde_json::Value;
-use tracing::{event, Level};
+use thiserror::Error;
+use tokio_util::sync::CancellationToken;
+use tracing::{event, field, info_span, instrument, Instrument, Level};
 
-use super::platform::ReleaseSummary;
+use super::{
+    cache::ResponseCache,
+    capabilities::Capabilities,
+    metrics::{Metrics, MetricsSnapshot},
+    platform::ReleaseSummary,
+    rate_limit::{EndpointGroup, RateLimit, RateLimiter},
+    token_encryption::{EncryptedToken, TokenKey},
+};
 
-const SUPPORTED_VERSIONS: [&str; 2] = ["2.3.7.5", "2.3.7.6"];
+pub(crate) const SUPPORTED_VERSIONS: [&str; 2] = ["2.3.7.5", "2.3.7.6"];
 
-#[derive(Serialize, Deserialize, Debug, Default)]
+const DEFAULT_USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
+
+// used in Debug implementations instead of secrets, so they never end up in logs
+pub(crate) const REDACTED: &str = "<redacted>";
+
+#[derive(Serialize, Deserialize, Default)]
 pub struct Token {
     #[serde(rename = "Token")]
     token: String,
     exp: Option<u64>,
 }
 
-#[derive(Debug)]
 pub struct DNAC {
     pub client: reqwest::Client,
     pub token: Token,
@@ -26,8 +48,37 @@ pub struct DNAC {
     pub dnac: String,
     pub user: String,
     pub password: String,
+    pub pagination_policy: PaginationPolicy,
+    pub middleware: Middleware,
+    pub(crate) metrics: Metrics,
+    pub(crate) cache: ResponseCache,
+    pub(crate) rate_limiter: RateLimiter,
+    pub(crate) token_key: Option<Arc<dyn TokenKey>>,
+    // the supported version the cluster runs, set once it was verified
+    pub(crate) version: Option<String>,
+}
+
+pub struct DnacBuilder {
+    token_file: String,
+    dnac: String,
+    user: String,
+    password: String,
+    user_agent: String,
+    identification_header: Option<(String, String)>,
+    token_key: Option<Arc<dyn TokenKey>>,
 }
 
+// RequestInterceptor can mutate every outgoing request (e.g. add headers or
+// sign it) and observe the responses, e.g. for audit logging
+pub trait RequestInterceptor: Send + Sync {
+    fn on_request(&self, _request: &mut reqwest::Request) {}
+
+    fn on_response(&self, _method: &Method, _response: &reqwest::Response) {}
+}
+
+#[derive(Default)]
+pub struct Middleware(Vec<Box<dyn RequestInterceptor>>);
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Response<T> {
     pub response: ResponseType<T>,
@@ -65,7 +116,51 @@ pub struct PaginationBuilder {
     limit: u64,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PaginationLimits {
+    pub max_offset: u64,
+    pub max_limit: u64,
+}
+
+// PaginationPolicy holds the default limits and per endpoint overrides,
+// endpoints are matched by path prefix and the longest match wins
+#[derive(Debug, Clone, Default)]
+pub struct PaginationPolicy {
+    default: PaginationLimits,
+    endpoints: Vec<(String, PaginationLimits)>,
+}
+
+#[derive(Debug, Error)]
+pub enum PaginationError {
+    #[error(
+        "Pagination out of range for {path}: offset {offset} (max {max_offset}), \
+         limit {limit} (max {max_limit}), use a filter to narrow down the result \
+         or raise the limits via PaginationPolicy if the endpoint allows it"
+    )]
+    PaginationOutOfRange {
+        path: String,
+        offset: u64,
+        limit: u64,
+        max_offset: u64,
+        max_limit: u64,
+    },
+}
+
+// returned for responses which are neither a success nor an ApiError, the
+// status can be checked by downcasting, e.g. to back off on 429
+#[derive(Debug, Error)]
+#[error("Request failed with status {status}: {body}")]
+pub struct StatusError {
+    pub status: StatusCode,
+    pub body: String,
+}
+
+// returned by long running operations which were stopped via their CancellationToken
+#[derive(Debug, Error)]
+#[error("Operation was cancelled")]
+pub struct Cancelled;
+
+#[derive(Debug, Clone, Deserialize)]
 pub struct TaskInfo {
     #[serde(rename = "taskId")]
     pub task_id: String,
@@ -73,6 +168,28 @@ pub struct TaskInfo {
     pub url: String,
 }
 
+// ExecutionInfo is returned by the business APIs, like the site writes,
+// which report their progress through an execution status instead of a task
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecutionInfo {
+    pub execution_id: String,
+    pub execution_status_url: String,
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecutionStatus {
+    pub bapi_execution_id: Option<String>,
+    pub bapi_name: Option<String>,
+    // IN_PROGRESS, SUCCESS or FAILURE
+    pub status: String,
+    pub bapi_error: Option<String>,
+    pub start_time_epoch: Option<u64>,
+    pub end_time_epoch: Option<u64>,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct Task {
     pub id: String,
@@ -115,58 +232,133 @@ impl DNAC {
         user: String,
         password: String,
     ) -> Result<Self> {
-        let client = reqwest::Client::builder()
-            .danger_accept_invalid_certs(true)
+        DNAC::builder(token_file, dnac, user, password)
             .build()
-            .unwrap();
-
-        let token = Token::default();
-
-        let dnac = if let Some(dnac) = dnac.strip_suffix("/") {
-            dnac.to_string()
-        } else {
-            dnac
-        };
+            .await
+    }
 
-        let mut dnac = Self {
-            client,
-            token,
+    pub fn builder(
+        token_file: String,
+        dnac: String,
+        user: String,
+        password: String,
+    ) -> DnacBuilder {
+        DnacBuilder {
             token_file,
             dnac,
             user,
             password,
-        };
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            identification_header: None,
+            token_key: None,
+        }
+    }
 
-        let token = {
-            if let Ok(mut token) = dnac.load_token() {
-                token.parse();
-                // if the token is still valid and valid for more than 10 min we use it
-                if token.valid() && token.valid_for() > 60 * 10 {
-                    event!(
-                        Level::INFO,
-                        "Loaded token is still valid for {} sec and will be used",
-                        token.valid_for()
-                    );
-                    token
-                } else {
-                    event!(
-                        Level::INFO,
-                        "Loaded token is no longer valid, generate a new one"
-                    );
-                    dnac.get_token().await.unwrap()
-                }
-            } else {
-                // if we can't load a token and don't get one from the API we fail hard
-                event!(Level::INFO, "Token file not found, generate a new one");
-                dnac.get_token().await.unwrap()
-            }
-        };
+    // interceptors are run in the order they were added
+    pub fn with_middleware(mut self, interceptor: Box<dyn RequestInterceptor>) -> Self {
+        self.add_middleware(interceptor);
+        self
+    }
 
-        dnac.token = token;
+    pub fn add_middleware(&mut self, interceptor: Box<dyn RequestInterceptor>) {
+        self.middleware.0.push(interceptor);
+    }
+
+    // all requests are sent through send, so the middleware sees every request
+    async fn send(&self, request: reqwest::RequestBuilder) -> Result<reqwest::Response> {
+        let mut request = request.build()?;
+        for interceptor in &self.middleware.0 {
+            interceptor.on_request(&mut request);
+        }
 
-        dnac.verify_version().await?;
+        let method = request.method().clone();
+        let path = request.url().path().to_string();
+        let span = info_span!(
+            "dnac_request",
+            method = %method,
+            path = %path,
+            status = field::Empty,
+            duration_ms = field::Empty,
+        );
+        self.rate_limiter.acquire(&path).await;
 
-        Ok(dnac)
+        let start = Instant::now();
+        let response = self.client.execute(request).instrument(span.clone()).await;
+        let elapsed = start.elapsed();
+        let status = response.as_ref().ok().map(|r| r.status().as_u16());
+
+        span.record("duration_ms", elapsed.as_millis() as u64);
+        if let Some(status) = status {
+            span.record("status", status);
+        }
+        span.in_scope(|| event!(Level::DEBUG, "Request finished"));
+        self.metrics
+            .record_request(method.as_str(), &path, status, elapsed);
+
+        let response = response?;
+        for interceptor in &self.middleware.0 {
+            interceptor.on_response(&method, &response);
+        }
+
+        Ok(response)
+    }
+
+    // sends a request which changes data on DNAC, the cached reads of the
+    // written resource are dropped. Reads sent as POST or PUT and the token
+    // request use send and leave the cache alone.
+    async fn send_write(
+        &self,
+        path: &str,
+        request: reqwest::RequestBuilder,
+    ) -> Result<reqwest::Response> {
+        let response = self.send(request).await?;
+        if response.status().is_success() {
+            self.cache.invalidate_resource(path);
+        }
+
+        Ok(response)
+    }
+
+    // throttles requests to the endpoint group, None removes the limit
+    pub fn with_rate_limit(mut self, group: EndpointGroup, limit: Option<RateLimit>) -> Self {
+        self.rate_limiter.set_limit(group, limit);
+        self
+    }
+
+    // applies RateLimiter::with_default_limits, replacing all configured limits
+    pub fn with_default_rate_limits(mut self) -> Self {
+        self.rate_limiter = RateLimiter::with_default_limits();
+        self
+    }
+
+    // caches GET responses for ttl, writes through this client drop the cached
+    // responses of the resource they change
+    pub fn with_cache(mut self, ttl: Duration) -> Self {
+        self.set_cache_ttl(Some(ttl));
+        self
+    }
+
+    // None disables the cache
+    pub fn set_cache_ttl(&mut self, ttl: Option<Duration>) {
+        self.cache.set_ttl(ttl);
+    }
+
+    // removes the cached responses of all paths starting with path_prefix
+    pub fn invalidate_cache(&self, path_prefix: &str) {
+        self.cache.invalidate(path_prefix);
+    }
+
+    pub fn clear_cache(&self) {
+        self.cache.clear();
+    }
+
+    // the endpoints the crate supports for the version of the cluster
+    pub fn capabilities(&self) -> Capabilities {
+        Capabilities::for_version(self.version.as_deref())
+    }
+
+    pub fn metrics(&self) -> MetricsSnapshot {
+        self.metrics.snapshot()
     }
 
     // We make sure that the client is run against a supported Version
@@ -185,28 +377,54 @@ impl DNAC {
     pub async fn get_token(&self) -> Result<Token> {
         let path = "/dna/system/api/v1/auth/token";
 
-        let mut token = self
-            .client
-            .post(format!("{}{}", self.dnac, path))
-            .basic_auth(&self.user, Some(&self.password))
-            .send()
-            .await?
-            .json::<Token>()
+        let response = self
+            .send(
+                self.client
+                    .post(format!("{}{}", self.dnac, path))
+                    .basic_auth(&self.user, Some(&self.password)),
+            )
             .await?;
+        let mut token = check_status(response).await?.json::<Token>().await?;
 
-        token.parse();
-        token.save()?;
+        if let Err(e) = token.parse() {
+            event!(
+                Level::WARN,
+                "{e}, the token will be used without expiration"
+            );
+        }
+        match (self.token_path(), &self.token_key) {
+            (Some(token_file), Some(key)) => token.save_encrypted(&token_file, &key.key()?)?,
+            (Some(token_file), None) => token.save(&token_file)?,
+            (None, _) => event!(Level::DEBUG, "No token file configured, token is not saved"),
+        }
 
         Ok(token)
     }
 
+    // the token_file passed to DNAC::new, DNAC_TOKEN_FILE is only used if it is empty
+    pub fn token_path(&self) -> Option<String> {
+        if !self.token_file.is_empty() {
+            return Some(self.token_file.clone());
+        }
+
+        std::env::var("DNAC_TOKEN_FILE")
+            .ok()
+            .filter(|f| !f.is_empty())
+    }
+
     pub fn load_token(&self) -> Result<Token> {
-        let file = fs::File::open(&self.token_file)?;
-        let token = serde_json::from_reader(file)?;
+        let token_file = self
+            .token_path()
+            .ok_or(anyhow!("No token file configured"))?;
+        let key = match &self.token_key {
+            Some(key) => Some(key.key()?),
+            None => None,
+        };
 
-        Ok(token)
+        Token::load(&token_file, key.as_ref())
     }
 
+    #[instrument(level = "debug", skip(self, input_query, pagination))]
     pub async fn get<T>(
         &self,
         path: &str,
@@ -216,223 +434,1148 @@ impl DNAC {
     where
         T: DeserializeOwned,
     {
-        let query = {
-            let mut query = vec![];
-            if let Some(pagination) = pagination {
-                query.push(("offset", pagination.offset.to_string()));
-                query.push(("limit", pagination.limit.to_string()));
-            }
+        let body = self.get_body(path, input_query, pagination, &[]).await?;
 
-            match input_query {
-                Some(input_query) => query.extend_from_slice(input_query),
-                None => (),
-            }
-            query
-        };
+        // an empty body is treated as an empty result
+        Ok(parse_bytes(&body)?.unwrap_or(Response {
+            response: ResponseType::Array(vec![]),
+        }))
+    }
 
-        let data = self
-            .client
-            .get(format!("{}{}", self.dnac, path))
-            .header("X-Auth-Token", &self.token.token)
-            .query(&query)
-            .send()
-            .await?;
+    // get_raw returns the whole body as json, for endpoints which are not
+    // modeled by the crate yet, an empty body is returned as Null
+    #[instrument(level = "debug", skip(self, input_query, pagination))]
+    pub async fn get_raw(
+        &self,
+        path: &str,
+        input_query: Option<&[(&str, String)]>,
+        pagination: Option<Pagination>,
+    ) -> Result<Value> {
+        let body = self.get_body(path, input_query, pagination, &[]).await?;
 
-        match data.status() {
-            StatusCode::INTERNAL_SERVER_ERROR => {
-                let data = data.json::<ApiError>().await?;
-                return Err(data.into());
-            }
-            _ => {
-                let data = data.json().await?;
-                Ok(data)
-            }
-        }
+        Ok(parse_bytes(&body)?.unwrap_or(Value::Null))
     }
 
-    // poll is a flag to indicate if we should poll the API for the result
-    pub async fn post<T>(&self, path: &str, data: T, poll: bool) -> Result<()>
-    where
-        T: Serialize,
-    {
-        let response = self
-            .client
-            .post(format!("{}{}", self.dnac, path))
-            .header("X-Auth-Token", &self.token.token)
-            .json(&data)
-            .send()
-            .await?;
+    // like get_raw, for endpoints which take their parameters as headers,
+    // e.g. the entity_type and entity_value of the enrichment details
+    #[instrument(level = "debug", skip(self, headers))]
+    pub async fn get_raw_with_headers(
+        &self,
+        path: &str,
+        headers: &[(&str, String)],
+    ) -> Result<Value> {
+        let body = self.get_body(path, None, None, headers).await?;
 
-        match response.status() {
-            StatusCode::INTERNAL_SERVER_ERROR => {
-                let data = response.json::<ApiError>().await?;
-                return Err(data.into());
-            }
-            _ => {
-                // we assume that we got a TaskInfo back
-                if poll {
-                    let response = response.json::<Response<TaskInfo>>().await?;
-                    match response.response {
-                        ResponseType::Item(task_info) => {
-                            self.poll_task(task_info).await?;
-                        }
-                        _ => {
-                            return Err(anyhow!("Unexpected response"));
-                        }
-                    }
-                }
-                Ok(())
-            }
-        }
+        Ok(parse_bytes(&body)?.unwrap_or(Value::Null))
     }
 
-    async fn poll_task(&self, mut task_info: TaskInfo) -> Result<()> {
-        event!(Level::DEBUG, "Polling Task: {:?}", task_info);
-        task_info.url.push_str("/tree/");
-        let mut task = self
-            .get::<Task>(task_info.url.as_str(), None, None)
-            .await?
-            .response;
+    // get_all_raw pages through an endpoint which returns a list in the
+    // response field and collects all entries
+    pub async fn get_all_raw(
+        &self,
+        path: &str,
+        input_query: Option<&[(&str, String)]>,
+    ) -> Result<Vec<Value>> {
+        let mut pagination = Pagination::default();
+        let mut entries = vec![];
 
         loop {
-            match task {
-                ResponseType::Array(ref inner_tasks) => {
-                    if inner_tasks.iter().all(|t| t.end_time.is_some()) {
-                        if inner_tasks.iter().any(|t| t.is_error) {
-                            inner_tasks.iter().for_each(|t| {
-                                if t.is_error {
-                                    event!(Level::ERROR, "{task:?}");
-                                }
-                            });
-
-                            return Err(anyhow!("Task failed"));
-                        }
-                        break;
-                    } else {
-                        task = self.get::<Task>(&task_info.url, None, None).await?.response;
-                    }
-                }
-                _ => {
-                    return Err(anyhow!("Unexpected response"));
-                }
+            let page = self.get_raw(path, input_query, Some(pagination)).await?;
+            let page = match page {
+                Value::Null => vec![],
+                Value::Object(mut page) => match page.remove("response") {
+                    Some(Value::Array(page)) => page,
+                    _ => return Err(anyhow!("Unexpected response")),
+                },
+                _ => return Err(anyhow!("Unexpected response")),
+            };
+
+            let done = (page.len() as u64) < pagination.limit;
+            entries.extend(page);
+            if done {
+                break;
             }
 
-            event!(Level::DEBUG, "Task is still running, sleep for 5 sec");
-            event!(Level::DEBUG, "Task: {:?}", task);
-            tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+            pagination.set_offset(pagination.offset + pagination.limit);
         }
 
-        Ok(())
+        Ok(entries)
     }
-}
 
-impl Token {
-    pub fn parse(&mut self) {
-        let unverified: jwt::Token<jwt::Header, jwt::RegisteredClaims, _> =
-            jwt::Token::parse_unverified(&self.token).unwrap();
-        self.exp = Some(unverified.claims().expiration.unwrap());
-    }
+    async fn get_body(
+        &self,
+        path: &str,
+        input_query: Option<&[(&str, String)]>,
+        pagination: Option<Pagination>,
+        headers: &[(&str, String)],
+    ) -> Result<Vec<u8>> {
+        if let Some(pagination) = pagination {
+            pagination.validate(path, self.pagination_policy.limits_for(path))?;
+        }
 
-    pub fn save(&self) -> Result<()> {
-        let token_file =
-            std::env::var("DNAC_TOKEN_FILE").expect("Missing 'DNAC_TOKEN_FILE' env var!");
-        let file = fs::File::create(token_file)?;
-        serde_json::to_writer(file, self)?;
+        let query = {
+            let mut query = vec![];
+            if let Some(pagination) = pagination {
+                query.push(("offset", pagination.offset.to_string()));
+                query.push(("limit", pagination.limit.to_string()));
+            }
 
-        Ok(())
-    }
+            if let Some(input_query) = input_query {
+                query.extend_from_slice(input_query);
+            }
+            query
+        };
 
-    pub fn valid(&self) -> bool {
-        if let Some(exp) = self.exp {
-            exp > chrono::offset::Local::now().timestamp() as u64
-        } else {
-            false
+        let mut url = Url::parse(&format!("{}{}", self.dnac, path))?;
+        if !query.is_empty() {
+            url.query_pairs_mut().extend_pairs(&query);
         }
-    }
 
-    pub fn valid_for(&self) -> u64 {
-        if let Some(exp) = self.exp {
-            let now = chrono::offset::Local::now().timestamp() as u64;
-            if exp > now {
-                exp - now
-            } else {
-                0
+        // the cache is keyed by url, so requests with headers bypass it
+        let cacheable = headers.is_empty();
+        if cacheable {
+            if let Some(body) = self.cache.get(&url) {
+                event!(Level::DEBUG, "Cache hit for {path}");
+                return Ok(body);
             }
-        } else {
-            0
         }
-    }
-}
 
-impl Default for Pagination {
-    fn default() -> Self {
-        Self {
-            limit: 500,
-            offset: 1,
+        let mut request = self
+            .client
+            .get(url.clone())
+            .header("X-Auth-Token", &self.token.token);
+        for (key, value) in headers {
+            request = request.header(*key, value);
         }
-    }
-}
+        let data = check_status(self.send(request).await?).await?;
 
-impl Pagination {
-    pub fn builder() -> PaginationBuilder {
-        let pagination = Pagination::default();
-        PaginationBuilder {
-            offset: pagination.offset,
-            limit: pagination.limit,
+        let body = data.bytes().await?.to_vec();
+        if cacheable {
+            self.cache.insert(&url, body.clone());
         }
-    }
 
-    pub fn set_limit(&mut self, limit: u64) {
-        self.limit = limit;
+        Ok(body)
     }
 
-    pub fn set_offset(&mut self, offset: u64) {
-        self.offset = offset;
+    // download returns the body as is, for endpoints which return files
+    // instead of json. Files are not cached.
+    #[instrument(level = "debug", skip(self))]
+    pub async fn download(&self, path: &str) -> Result<Vec<u8>> {
+        let response = self
+            .send(
+                self.client
+                    .get(format!("{}{}", self.dnac, path))
+                    .header("X-Auth-Token", &self.token.token),
+            )
+            .await?;
+
+        let response = check_status(response).await?;
+        Ok(response.bytes().await?.to_vec())
     }
-}
 
-impl PaginationBuilder {
-    pub fn with_limit(mut self, limit: u64) -> Self {
-        self.limit = limit;
-        self
+    // upload posts the file as multipart form under the field file and returns
+    // the body, bodies which aren't json are returned as string
+    #[instrument(level = "debug", skip(self, data))]
+    pub async fn upload(&self, path: &str, file_name: &str, data: Vec<u8>) -> Result<Value> {
+        let part = reqwest::multipart::Part::bytes(data).file_name(file_name.to_string());
+        self.send_multipart(path, part).await
     }
 
-    pub fn with_offset(mut self, offset: u64) -> Self {
-        self.offset = offset;
-        self
+    // upload_file is like upload, but streams the file from disk, so large
+    // files like software images aren't loaded into memory
+    #[instrument(level = "debug", skip(self))]
+    pub async fn upload_file(&self, path: &str, file: &std::path::Path) -> Result<Value> {
+        let file_name = file
+            .file_name()
+            .and_then(|name| name.to_str())
+            .ok_or_else(|| anyhow!("Invalid file name: {}", file.display()))?
+            .to_string();
+        let content = tokio::fs::File::open(file).await?;
+        let length = content.metadata().await?.len();
+
+        let body = reqwest::Body::wrap_stream(tokio_util::io::ReaderStream::new(content));
+        let part = reqwest::multipart::Part::stream_with_length(body, length).file_name(file_name);
+        self.send_multipart(path, part).await
     }
 
-    pub fn build(self) -> Pagination {
-        Pagination {
-            offset: self.offset,
-            limit: self.limit,
+    async fn send_multipart(&self, path: &str, part: reqwest::multipart::Part) -> Result<Value> {
+        let form = reqwest::multipart::Form::new().part("file", part);
+        let response = self
+            .send_write(
+                path,
+                self.client
+                    .post(format!("{}{}", self.dnac, path))
+                    .header("X-Auth-Token", &self.token.token)
+                    .multipart(form),
+            )
+            .await?;
+
+        let body = check_status(response).await?.bytes().await?;
+        match parse_bytes(&body) {
+            Ok(value) => Ok(value.unwrap_or(Value::Null)),
+            Err(_) => Ok(Value::String(
+                String::from_utf8_lossy(&body).trim().to_string(),
+            )),
         }
     }
-}
 
-impl fmt::Display for ApiError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "Error with the API request")
+    // post_raw sends the json as is and returns the whole body without polling
+    #[instrument(level = "debug", skip(self, data))]
+    pub async fn post_raw(&self, path: &str, data: &Value) -> Result<Value> {
+        let response = self
+            .send_write(
+                path,
+                self.client
+                    .post(format!("{}{}", self.dnac, path))
+                    .header("X-Auth-Token", &self.token.token)
+                    .json(data),
+            )
+            .await?;
+
+        let response = check_status(response).await?;
+        Ok(parse_body(response).await?.unwrap_or(Value::Null))
     }
-}
 
-impl Error for ApiError {}
+    // post_query is for read only endpoints which take the query as body,
+    // like most of the assurance data APIs
+    #[instrument(level = "debug", skip(self, data))]
+    pub async fn post_query<T, R>(&self, path: &str, data: T) -> Result<Response<R>>
+    where
+        T: Serialize,
+        R: DeserializeOwned,
+    {
+        let response = self
+            .send(
+                self.client
+                    .post(format!("{}{}", self.dnac, path))
+                    .header("X-Auth-Token", &self.token.token)
+                    .json(&data),
+            )
+            .await?;
 
-#[async_trait::async_trait]
-pub trait FetchableType: Sized {
-    type Filter;
-    type Error;
+        let response = check_status(response).await?;
+        Ok(parse_body(response).await?.unwrap_or(Response {
+            response: ResponseType::Array(vec![]),
+        }))
+    }
 
-    async fn fetch_list(
-        dnac: &DNAC,
-        filter: Option<Self::Filter>,
-        pagination: Option<Pagination>,
-    ) -> Result<Vec<Self>, Self::Error>;
-}
+    // put_query is for the few read only endpoints which use PUT, like the
+    // template preview. Their body isn't wrapped, so it's returned as is.
+    #[instrument(level = "debug", skip(self, data))]
+    pub async fn put_query<T>(&self, path: &str, data: T) -> Result<Value>
+    where
+        T: Serialize,
+    {
+        let response = self
+            .send(
+                self.client
+                    .put(format!("{}{}", self.dnac, path))
+                    .header("X-Auth-Token", &self.token.token)
+                    .json(&data),
+            )
+            .await?;
 
-pub trait GetAll {
-    fn get_all<T, E>(dnac: &DNAC) -> Result<Vec<T>, E>;
-}
+        let response = check_status(response).await?;
+        Ok(parse_body(response).await?.unwrap_or(Value::Null))
+    }
+
+    // poll is a flag to indicate if we should poll the API for the result
+    #[instrument(level = "debug", skip(self, data))]
+    pub async fn post<T>(&self, path: &str, data: T, poll: bool) -> Result<()>
+    where
+        T: Serialize,
+    {
+        let response = self
+            .send_write(
+                path,
+                self.client
+                    .post(format!("{}{}", self.dnac, path))
+                    .header("X-Auth-Token", &self.token.token)
+                    .json(&data),
+            )
+            .await?;
+
+        self.handle_write_response(response, poll).await
+    }
+
+    // post_task returns the TaskInfo without polling, useful when the caller
+    // needs the task id to query an additional status endpoint afterwards
+    #[instrument(level = "debug", skip(self, data))]
+    pub async fn post_task<T>(&self, path: &str, data: T) -> Result<TaskInfo>
+    where
+        T: Serialize,
+    {
+        let response = self
+            .send_write(
+                path,
+                self.client
+                    .post(format!("{}{}", self.dnac, path))
+                    .header("X-Auth-Token", &self.token.token)
+                    .json(&data),
+            )
+            .await?;
+
+        task_response(response).await
+    }
+
+    // post_execution returns the ExecutionInfo of a business API write
+    // without polling, see poll_execution
+    #[instrument(level = "debug", skip(self, data))]
+    pub async fn post_execution<T>(&self, path: &str, data: T) -> Result<ExecutionInfo>
+    where
+        T: Serialize,
+    {
+        let response = self
+            .send_write(
+                path,
+                self.client
+                    .post(format!("{}{}", self.dnac, path))
+                    .header("X-Auth-Token", &self.token.token)
+                    .json(&data),
+            )
+            .await?;
+
+        execution_response(response).await
+    }
+
+    // put_execution is the PUT variant of post_execution
+    #[instrument(level = "debug", skip(self, data))]
+    pub async fn put_execution<T>(&self, path: &str, data: T) -> Result<ExecutionInfo>
+    where
+        T: Serialize,
+    {
+        let response = self
+            .send_write(
+                path,
+                self.client
+                    .put(format!("{}{}", self.dnac, path))
+                    .header("X-Auth-Token", &self.token.token)
+                    .json(&data),
+            )
+            .await?;
+
+        execution_response(response).await
+    }
+
+    // delete_execution is the DELETE variant of post_execution
+    #[instrument(level = "debug", skip(self))]
+    pub async fn delete_execution(&self, path: &str) -> Result<ExecutionInfo> {
+        let response = self
+            .send_write(
+                path,
+                self.client
+                    .delete(format!("{}{}", self.dnac, path))
+                    .header("X-Auth-Token", &self.token.token),
+            )
+            .await?;
+
+        execution_response(response).await
+    }
+
+    // put_task is the PUT variant of post_task
+    #[instrument(level = "debug", skip(self, data))]
+    pub async fn put_task<T>(
+        &self,
+        path: &str,
+        input_query: Option<&[(&str, String)]>,
+        data: T,
+    ) -> Result<TaskInfo>
+    where
+        T: Serialize,
+    {
+        let query = input_query.unwrap_or_default();
+
+        let response = self
+            .send_write(
+                path,
+                self.client
+                    .put(format!("{}{}", self.dnac, path))
+                    .header("X-Auth-Token", &self.token.token)
+                    .query(query)
+                    .json(&data),
+            )
+            .await?;
+
+        task_response(response).await
+    }
+
+    // poll is a flag to indicate if we should poll the API for the result
+    #[instrument(level = "debug", skip(self, data))]
+    pub async fn put<T>(&self, path: &str, data: T, poll: bool) -> Result<()>
+    where
+        T: Serialize,
+    {
+        let response = self
+            .send_write(
+                path,
+                self.client
+                    .put(format!("{}{}", self.dnac, path))
+                    .header("X-Auth-Token", &self.token.token)
+                    .json(&data),
+            )
+            .await?;
+
+        self.handle_write_response(response, poll).await
+    }
+
+    // poll is a flag to indicate if we should poll the API for the result
+    #[instrument(level = "debug", skip(self, input_query))]
+    pub async fn delete(
+        &self,
+        path: &str,
+        input_query: Option<&[(&str, String)]>,
+        poll: bool,
+    ) -> Result<()> {
+        let query = input_query.unwrap_or_default();
+
+        let response = self
+            .send_write(
+                path,
+                self.client
+                    .delete(format!("{}{}", self.dnac, path))
+                    .header("X-Auth-Token", &self.token.token)
+                    .query(query),
+            )
+            .await?;
+
+        self.handle_write_response(response, poll).await
+    }
+
+    // a successful write without a body (e.g. 204 No Content) has no task to
+    // poll and is treated as done
+    async fn handle_write_response(&self, response: reqwest::Response, poll: bool) -> Result<()> {
+        let response = check_status(response).await?;
+        if poll {
+            match parse_body::<Response<TaskInfo>>(response).await? {
+                Some(Response {
+                    response: ResponseType::Item(task_info),
+                }) => {
+                    self.poll_task(task_info).await?;
+                }
+                Some(_) => {
+                    return Err(anyhow!("Unexpected response"));
+                }
+                None => event!(Level::DEBUG, "Empty response, nothing to poll"),
+            }
+        }
+        Ok(())
+    }
+
+    // returns the finished task tree, the root task is the one with the id of the TaskInfo
+    #[instrument(level = "debug", skip(self))]
+    pub async fn poll_task(&self, task_info: TaskInfo) -> Result<Vec<Task>> {
+        let _guard = self.metrics.task_poll_guard();
+        self.poll_task_tree(task_info).await
+    }
+
+    async fn poll_task_tree(&self, task_info: TaskInfo) -> Result<Vec<Task>> {
+        event!(Level::DEBUG, "Polling Task: {:?}", task_info);
+        let url = format!("{}/tree/", task_info.url);
+
+        loop {
+            let tasks = match self.get::<Task>(&url, None, None).await?.response {
+                ResponseType::Array(tasks) => tasks,
+                _ => return Err(anyhow!("Unexpected response")),
+            };
+
+            if check_task_tree(&tasks)? {
+                return Ok(tasks);
+            }
+
+            event!(Level::DEBUG, "Task is still running, sleep for 5 sec");
+            event!(Level::DEBUG, "Task: {:?}", tasks);
+            tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+        }
+    }
+
+    // polls the execution status until it finished, fails if the execution failed
+    pub async fn poll_execution(&self, execution_info: ExecutionInfo) -> Result<ExecutionStatus> {
+        event!(Level::DEBUG, "Polling Execution: {:?}", execution_info);
+
+        loop {
+            let status = self
+                .get_raw(&execution_info.execution_status_url, None, None)
+                .await?;
+            let status: ExecutionStatus = serde_json::from_value(status)?;
+
+            if check_execution(&status)? {
+                return Ok(status);
+            }
+
+            event!(Level::DEBUG, "Execution is still running, sleep for 5 sec");
+            tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+        }
+    }
+}
+
+// returns true once the execution finished, fails if it failed
+pub(crate) fn check_execution(status: &ExecutionStatus) -> Result<bool> {
+    match status.status.as_str() {
+        "SUCCESS" => Ok(true),
+        "FAILURE" => {
+            event!(Level::ERROR, "{status:?}");
+            Err(anyhow!(
+                "Execution failed: {}",
+                status.bapi_error.as_deref().unwrap_or("unknown error")
+            ))
+        }
+        _ => Ok(false),
+    }
+}
+
+// returns true once all tasks of the tree have finished, fails if any of them failed
+pub(crate) fn check_task_tree(tasks: &[Task]) -> Result<bool> {
+    if !tasks.iter().all(|t| t.end_time.is_some()) {
+        return Ok(false);
+    }
+
+    if tasks.iter().any(|t| t.is_error) {
+        tasks
+            .iter()
+            .filter(|t| t.is_error)
+            .for_each(|t| event!(Level::ERROR, "{t:?}"));
+
+        return Err(anyhow!("Task failed"));
+    }
+
+    Ok(true)
+}
+
+// reads the TaskInfo of a write which is processed asynchronously
+async fn task_response(response: reqwest::Response) -> Result<TaskInfo> {
+    let response = check_status(response).await?;
+    match parse_body::<Response<TaskInfo>>(response).await? {
+        Some(Response {
+            response: ResponseType::Item(task_info),
+        }) => Ok(task_info),
+        Some(_) => Err(anyhow!("Unexpected response")),
+        None => Err(anyhow!("Empty response, expected a task")),
+    }
+}
+
+// reads the ExecutionInfo of a business API write, it isn't wrapped in a response field
+async fn execution_response(response: reqwest::Response) -> Result<ExecutionInfo> {
+    let response = check_status(response).await?;
+    match parse_body::<ExecutionInfo>(response).await? {
+        Some(execution_info) => Ok(execution_info),
+        None => Err(anyhow!("Empty response, expected an execution")),
+    }
+}
+
+// only a 2xx status is a success, a 500 carries an ApiError while any other
+// status (e.g. 401, 404 or 429) is returned as StatusError
+async fn check_status(response: reqwest::Response) -> Result<reqwest::Response> {
+    match response.status() {
+        StatusCode::INTERNAL_SERVER_ERROR => {
+            let data = response.json::<ApiError>().await?;
+            Err(data.into())
+        }
+        status if !status.is_success() => {
+            let body = response.text().await.unwrap_or_default();
+            Err(StatusError {
+                status,
+                body: body.trim().to_string(),
+            }
+            .into())
+        }
+        _ => Ok(response),
+    }
+}
+
+// parses the json body of a response, an empty body is returned as None
+async fn parse_body<T>(response: reqwest::Response) -> Result<Option<T>>
+where
+    T: DeserializeOwned,
+{
+    let body = response.bytes().await?;
+    parse_bytes(&body)
+}
+
+fn parse_bytes<T>(body: &[u8]) -> Result<Option<T>>
+where
+    T: DeserializeOwned,
+{
+    if body.iter().all(u8::is_ascii_whitespace) {
+        return Ok(None);
+    }
+
+    Ok(Some(serde_json::from_slice(body)?))
+}
+
+impl DnacBuilder {
+    // the default user agent is the crate name and version, e.g. dnac/0.1.0
+    pub fn with_user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = user_agent.into();
+        self
+    }
+
+    // an additional header sent with every request, e.g. to identify the tool
+    // in the audit logs of DNAC or an API gateway
+    pub fn with_identification_header(
+        mut self,
+        name: impl Into<String>,
+        value: impl Into<String>,
+    ) -> Self {
+        self.identification_header = Some((name.into(), value.into()));
+        self
+    }
+
+    // encrypts the token file with the key, plaintext token files are still
+    // loaded and replaced with an encrypted one on the next token refresh
+    pub fn with_token_key(mut self, key: impl TokenKey + 'static) -> Self {
+        self.token_key = Some(Arc::new(key));
+        self
+    }
+
+    pub async fn build(self) -> Result<DNAC> {
+        let mut headers = HeaderMap::new();
+        if let Some((name, value)) = &self.identification_header {
+            headers.insert(
+                HeaderName::from_bytes(name.as_bytes())?,
+                HeaderValue::from_str(value)?,
+            );
+        }
+
+        let client = reqwest::Client::builder()
+            .danger_accept_invalid_certs(true)
+            .user_agent(&self.user_agent)
+            .default_headers(headers)
+            .build()?;
+
+        let token = Token::default();
+
+        let dnac = if let Some(dnac) = self.dnac.strip_suffix("/") {
+            dnac.to_string()
+        } else {
+            self.dnac
+        };
+
+        let mut dnac = DNAC {
+            client,
+            token,
+            token_file: self.token_file,
+            dnac,
+            user: self.user,
+            password: self.password,
+            pagination_policy: PaginationPolicy::default(),
+            middleware: Middleware::default(),
+            metrics: Metrics::default(),
+            cache: ResponseCache::default(),
+            rate_limiter: RateLimiter::default(),
+            token_key: self.token_key,
+            version: None,
+        };
+
+        let token = {
+            if let Ok(mut token) = dnac.load_token() {
+                // a token which can't be parsed is replaced like an expired one
+                let parsed = token.parse().is_ok();
+                // if the token is still valid and valid for more than 10 min we use it
+                if parsed && token.valid() && token.valid_for() > 60 * 10 {
+                    event!(
+                        Level::INFO,
+                        "Loaded token is still valid for {} sec and will be used",
+                        token.valid_for()
+                    );
+                    token
+                } else {
+                    event!(
+                        Level::INFO,
+                        "Loaded token is no longer valid, generate a new one"
+                    );
+                    dnac.get_token().await?
+                }
+            } else {
+                event!(Level::INFO, "Token file not found, generate a new one");
+                dnac.get_token().await?
+            }
+        };
+
+        dnac.token = token;
+
+        dnac.version = Some(dnac.verify_version().await?.to_string());
+
+        Ok(dnac)
+    }
+}
+
+impl Token {
+    // reads the expiration from the JWT, a token without exp is kept but
+    // treated as expired, so it gets replaced on the next start
+    pub fn parse(&mut self) -> Result<()> {
+        let unverified: jwt::Token<jwt::Header, jwt::RegisteredClaims, _> =
+            jwt::Token::parse_unverified(&self.token).map_err(|e| anyhow!("Invalid token: {e}"))?;
+        self.exp = unverified.claims().expiration;
+
+        Ok(())
+    }
+
+    pub fn save(&self, token_file: &str) -> Result<()> {
+        let file = fs::File::create(token_file)?;
+        serde_json::to_writer(file, self)?;
+
+        Ok(())
+    }
+
+    pub fn save_encrypted(&self, token_file: &str, key: &[u8; 32]) -> Result<()> {
+        let encrypted = EncryptedToken::encrypt(key, &serde_json::to_vec(self)?)?;
+        let file = fs::File::create(token_file)?;
+        serde_json::to_writer(file, &encrypted)?;
+
+        Ok(())
+    }
+
+    // loads a plaintext or encrypted token file, the key is only needed for
+    // encrypted ones
+    pub fn load(token_file: &str, key: Option<&[u8; 32]>) -> Result<Self> {
+        let content = fs::read(token_file)?;
+        match serde_json::from_slice::<EncryptedToken>(&content) {
+            Ok(encrypted) => {
+                let key = key.ok_or(anyhow!("Token file is encrypted but no key is configured"))?;
+                Ok(serde_json::from_slice(&encrypted.decrypt(key)?)?)
+            }
+            Err(_) => Ok(serde_json::from_slice(&content)?),
+        }
+    }
+
+    pub fn valid(&self) -> bool {
+        if let Some(exp) = self.exp {
+            exp > chrono::offset::Local::now().timestamp() as u64
+        } else {
+            false
+        }
+    }
+
+    pub fn valid_for(&self) -> u64 {
+        if let Some(exp) = self.exp {
+            let now = chrono::offset::Local::now().timestamp() as u64;
+            exp.saturating_sub(now)
+        } else {
+            0
+        }
+    }
+}
+
+impl Default for Pagination {
+    fn default() -> Self {
+        Self {
+            limit: 500,
+            offset: 1,
+        }
+    }
+}
+
+impl Pagination {
+    pub fn builder() -> PaginationBuilder {
+        let pagination = Pagination::default();
+        PaginationBuilder {
+            offset: pagination.offset,
+            limit: pagination.limit,
+        }
+    }
+
+    pub fn set_limit(&mut self, limit: u64) {
+        self.limit = limit;
+    }
+
+    pub fn set_offset(&mut self, offset: u64) {
+        self.offset = offset;
+    }
+
+    pub fn validate(&self, path: &str, limits: PaginationLimits) -> Result<(), PaginationError> {
+        if self.offset > limits.max_offset || self.limit > limits.max_limit {
+            return Err(PaginationError::PaginationOutOfRange {
+                path: path.to_string(),
+                offset: self.offset,
+                limit: self.limit,
+                max_offset: limits.max_offset,
+                max_limit: limits.max_limit,
+            });
+        }
+
+        Ok(())
+    }
+
+    // clamp reduces the limit to the allowed maximum, the offset is left as is
+    // as skipping entries silently would return wrong data
+    pub fn clamp(mut self, limits: PaginationLimits) -> Self {
+        self.limit = self.limit.min(limits.max_limit);
+        self
+    }
+}
+
+impl Default for PaginationLimits {
+    fn default() -> Self {
+        Self {
+            max_offset: 50_000,
+            max_limit: 500,
+        }
+    }
+}
+
+impl PaginationPolicy {
+    pub fn new(default: PaginationLimits) -> Self {
+        Self {
+            default,
+            endpoints: vec![],
+        }
+    }
+
+    pub fn set_default(&mut self, limits: PaginationLimits) {
+        self.default = limits;
+    }
+
+    pub fn set_endpoint_limits(
+        &mut self,
+        path_prefix: impl Into<String>,
+        limits: PaginationLimits,
+    ) {
+        let path_prefix = path_prefix.into();
+        self.endpoints.retain(|(p, _)| *p != path_prefix);
+        self.endpoints.push((path_prefix, limits));
+    }
+
+    pub fn limits_for(&self, path: &str) -> PaginationLimits {
+        self.endpoints
+            .iter()
+            .filter(|(p, _)| path.starts_with(p.as_str()))
+            .max_by_key(|(p, _)| p.len())
+            .map(|(_, limits)| *limits)
+            .unwrap_or(self.default)
+    }
+}
+
+impl PaginationBuilder {
+    pub fn with_limit(mut self, limit: u64) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    pub fn with_offset(mut self, offset: u64) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    pub fn build(self) -> Pagination {
+        Pagination {
+            offset: self.offset,
+            limit: self.limit,
+        }
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Error with the API request")
+    }
+}
+
+impl Error for ApiError {}
+
+impl fmt::Debug for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Token")
+            .field("token", &REDACTED)
+            .field("exp", &self.exp)
+            .finish()
+    }
+}
+
+impl fmt::Debug for DNAC {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DNAC")
+            .field("token", &self.token)
+            .field("token_file", &self.token_file)
+            .field("dnac", &self.dnac)
+            .field("user", &self.user)
+            .field("password", &REDACTED)
+            .field("pagination_policy", &self.pagination_policy)
+            .field("middleware", &self.middleware)
+            .finish_non_exhaustive()
+    }
+}
+
+impl fmt::Debug for Middleware {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Middleware({} interceptors)", self.0.len())
+    }
+}
+
+// ApiClient abstracts the HTTP verbs used by the modules, DNAC is the real
+// implementation, downstream crates can implement it to inject mocks
+#[async_trait::async_trait]
+pub trait ApiClient: Sync {
+    async fn get<T>(
+        &self,
+        path: &str,
+        input_query: Option<&[(&str, String)]>,
+        pagination: Option<Pagination>,
+    ) -> Result<Response<T>>
+    where
+        T: DeserializeOwned + Send;
+
+    // for endpoints which don't wrap their data in a response field
+    async fn get_raw(
+        &self,
+        path: &str,
+        input_query: Option<&[(&str, String)]>,
+        pagination: Option<Pagination>,
+    ) -> Result<Value>;
+
+    async fn post<T>(&self, path: &str, data: T, poll: bool) -> Result<()>
+    where
+        T: Serialize + Send;
+
+    async fn post_task<T>(&self, path: &str, data: T) -> Result<TaskInfo>
+    where
+        T: Serialize + Send;
+
+    async fn put<T>(&self, path: &str, data: T, poll: bool) -> Result<()>
+    where
+        T: Serialize + Send;
+
+    async fn delete(
+        &self,
+        path: &str,
+        input_query: Option<&[(&str, String)]>,
+        poll: bool,
+    ) -> Result<()>;
+
+    async fn poll_task(&self, task_info: TaskInfo) -> Result<Vec<Task>>;
+
+    // the detected Catalyst Center version, if known
+    fn version(&self) -> Option<&str> {
+        None
+    }
+
+    // counts a retry of a failed request in the metrics, if the client keeps any
+    fn record_retry(&self) {}
+
+    // like poll_task, but stops polling with a Cancelled error once cancel is triggered
+    async fn poll_task_with_cancel(
+        &self,
+        task_info: TaskInfo,
+        cancel: Option<&CancellationToken>,
+    ) -> Result<Vec<Task>> {
+        with_cancel(cancel, self.poll_task(task_info)).await?
+    }
+}
+
+// ApiClientExt covers the requests only some workflows need, like business
+// API executions, queries sent as POST or file transfers. Workflows using
+// them require it, so a client without them is rejected at compile time.
+#[async_trait::async_trait]
+pub trait ApiClientExt: ApiClient {
+    async fn get_raw_with_headers(&self, path: &str, headers: &[(&str, String)]) -> Result<Value>;
+
+    // for writes which return their result directly instead of a task
+    async fn post_raw(&self, path: &str, data: &Value) -> Result<Value>;
+
+    async fn post_execution<T>(&self, path: &str, data: T) -> Result<ExecutionInfo>
+    where
+        T: Serialize + Send;
+
+    async fn post_query<T, R>(&self, path: &str, data: T) -> Result<Response<R>>
+    where
+        T: Serialize + Send,
+        R: DeserializeOwned + Send;
+
+    async fn put_query<T>(&self, path: &str, data: T) -> Result<Value>
+    where
+        T: Serialize + Send;
+
+    async fn put_task<T>(
+        &self,
+        path: &str,
+        input_query: Option<&[(&str, String)]>,
+        data: T,
+    ) -> Result<TaskInfo>
+    where
+        T: Serialize + Send;
+
+    async fn put_execution<T>(&self, path: &str, data: T) -> Result<ExecutionInfo>
+    where
+        T: Serialize + Send;
+
+    async fn delete_execution(&self, path: &str) -> Result<ExecutionInfo>;
+
+    async fn poll_execution(&self, execution_info: ExecutionInfo) -> Result<ExecutionStatus>;
+
+    async fn download(&self, path: &str) -> Result<Vec<u8>>;
+
+    async fn upload(&self, path: &str, file_name: &str, data: Vec<u8>) -> Result<Value>;
+
+    async fn upload_file(&self, path: &str, file: &std::path::Path) -> Result<Value>;
+}
+
+#[async_trait::async_trait]
+impl ApiClient for DNAC {
+    async fn get<T>(
+        &self,
+        path: &str,
+        input_query: Option<&[(&str, String)]>,
+        pagination: Option<Pagination>,
+    ) -> Result<Response<T>>
+    where
+        T: DeserializeOwned + Send,
+    {
+        DNAC::get(self, path, input_query, pagination).await
+    }
+
+    async fn get_raw(
+        &self,
+        path: &str,
+        input_query: Option<&[(&str, String)]>,
+        pagination: Option<Pagination>,
+    ) -> Result<Value> {
+        DNAC::get_raw(self, path, input_query, pagination).await
+    }
+
+    async fn post<T>(&self, path: &str, data: T, poll: bool) -> Result<()>
+    where
+        T: Serialize + Send,
+    {
+        DNAC::post(self, path, data, poll).await
+    }
+
+    async fn post_task<T>(&self, path: &str, data: T) -> Result<TaskInfo>
+    where
+        T: Serialize + Send,
+    {
+        DNAC::post_task(self, path, data).await
+    }
+
+    async fn put<T>(&self, path: &str, data: T, poll: bool) -> Result<()>
+    where
+        T: Serialize + Send,
+    {
+        DNAC::put(self, path, data, poll).await
+    }
+
+    async fn delete(
+        &self,
+        path: &str,
+        input_query: Option<&[(&str, String)]>,
+        poll: bool,
+    ) -> Result<()> {
+        DNAC::delete(self, path, input_query, poll).await
+    }
+
+    async fn poll_task(&self, task_info: TaskInfo) -> Result<Vec<Task>> {
+        DNAC::poll_task(self, task_info).await
+    }
+
+    fn version(&self) -> Option<&str> {
+        self.version.as_deref()
+    }
+
+    fn record_retry(&self) {
+        self.metrics.record_retry();
+    }
+}
+
+#[async_trait::async_trait]
+impl ApiClientExt for DNAC {
+    async fn get_raw_with_headers(&self, path: &str, headers: &[(&str, String)]) -> Result<Value> {
+        DNAC::get_raw_with_headers(self, path, headers).await
+    }
+
+    async fn post_raw(&self, path: &str, data: &Value) -> Result<Value> {
+        DNAC::post_raw(self, path, data).await
+    }
+
+    async fn post_execution<T>(&self, path: &str, data: T) -> Result<ExecutionInfo>
+    where
+        T: Serialize + Send,
+    {
+        DNAC::post_execution(self, path, data).await
+    }
+
+    async fn post_query<T, R>(&self, path: &str, data: T) -> Result<Response<R>>
+    where
+        T: Serialize + Send,
+        R: DeserializeOwned + Send,
+    {
+        DNAC::post_query(self, path, data).await
+    }
+
+    async fn put_query<T>(&self, path: &str, data: T) -> Result<Value>
+    where
+        T: Serialize + Send,
+    {
+        DNAC::put_query(self, path, data).await
+    }
+
+    async fn put_task<T>(
+        &self,
+        path: &str,
+        input_query: Option<&[(&str, String)]>,
+        data: T,
+    ) -> Result<TaskInfo>
+    where
+        T: Serialize + Send,
+    {
+        DNAC::put_task(self, path, input_query, data).await
+    }
+
+    async fn put_execution<T>(&self, path: &str, data: T) -> Result<ExecutionInfo>
+    where
+        T: Serialize + Send,
+    {
+        DNAC::put_execution(self, path, data).await
+    }
+
+    async fn delete_execution(&self, path: &str) -> Result<ExecutionInfo> {
+        DNAC::delete_execution(self, path).await
+    }
+
+    async fn poll_execution(&self, execution_info: ExecutionInfo) -> Result<ExecutionStatus> {
+        DNAC::poll_execution(self, execution_info).await
+    }
+
+    async fn download(&self, path: &str) -> Result<Vec<u8>> {
+        DNAC::download(self, path).await
+    }
+
+    async fn upload(&self, path: &str, file_name: &str, data: Vec<u8>) -> Result<Value> {
+        DNAC::upload(self, path, file_name, data).await
+    }
+
+    async fn upload_file(&self, path: &str, file: &std::path::Path) -> Result<Value> {
+        DNAC::upload_file(self, path, file).await
+    }
+}
+
+#[async_trait::async_trait]
+pub trait FetchableType: Sized {
+    type Filter;
+    type Error;
+
+    async fn fetch_list<C: ApiClient>(
+        dnac: &C,
+        filter: Option<Self::Filter>,
+        pagination: Option<Pagination>,
+    ) -> Result<Vec<Self>, Self::Error>;
+}
+
+// runs fut until it finishes or cancel is triggered, the future is dropped on cancel
+pub async fn with_cancel<F: Future>(
+    cancel: Option<&CancellationToken>,
+    fut: F,
+) -> Result<F::Output, Cancelled> {
+    match cancel {
+        Some(cancel) => tokio::select! {
+            biased;
+            _ = cancel.cancelled() => Err(Cancelled),
+            output = fut => Ok(output),
+        },
+        None => Ok(fut.await),
+    }
+}
+
+pub trait GetAll {
+    fn get_all<T, E, C: ApiClient>(dnac: &C) -> Result<Vec<T>, E>;
+}
 
 #[cfg(test)]
 mod tests {
@@ -445,6 +1588,241 @@ mod tests {
           "#;
 
         let task: Task = serde_json::from_str(task).unwrap();
-        assert_eq!(task.id, "");
+        assert_eq!(task.id, "0193739c-0d88-78e4-ba0f-d82889fca555");
+    }
+
+    #[tokio::test]
+    async fn test_empty_response() {
+        use wiremock::{matchers::method, Mock, ResponseTemplate};
+
+        let mock = crate::test_util::MockDnac::start().await;
+        Mock::given(method("DELETE"))
+            .respond_with(ResponseTemplate::new(204))
+            .mount(&mock.server)
+            .await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(204))
+            .mount(&mock.server)
+            .await;
+
+        mock.dnac
+            .delete("/dna/intent/api/v1/some-resource", None, true)
+            .await
+            .unwrap();
+        let response = mock
+            .dnac
+            .get::<Task>("/dna/intent/api/v1/some-resource", None, None)
+            .await
+            .unwrap();
+        assert!(matches!(response.response, ResponseType::Array(tasks) if tasks.is_empty()));
+    }
+
+    #[tokio::test]
+    async fn test_error_status() {
+        use wiremock::{matchers::method, Mock, ResponseTemplate};
+
+        let mock = crate::test_util::MockDnac::start().await;
+        Mock::given(method("DELETE"))
+            .respond_with(ResponseTemplate::new(403))
+            .mount(&mock.server)
+            .await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(429).set_body_string("Too Many Requests"))
+            .mount(&mock.server)
+            .await;
+
+        let e = mock
+            .dnac
+            .delete("/dna/intent/api/v1/some-resource", None, false)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            e.downcast::<StatusError>().unwrap().status,
+            StatusCode::FORBIDDEN
+        );
+
+        let e = mock
+            .dnac
+            .get::<Task>("/dna/intent/api/v1/some-resource", None, None)
+            .await
+            .unwrap_err();
+        let e = e.downcast::<StatusError>().unwrap();
+        assert_eq!(e.status, StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(e.body, "Too Many Requests");
+    }
+
+    #[tokio::test]
+    async fn test_cache_invalidation() {
+        let mut mock = crate::test_util::MockDnac::start().await;
+        mock.dnac
+            .set_cache_ttl(Some(std::time::Duration::from_secs(60)));
+        let path = "/dna/intent/api/v1/network-device";
+        mock.mock_response("GET", path, 200, r#"{"response":[],"version":"1.0"}"#)
+            .await;
+        mock.mock_response(
+            "POST",
+            "/dna/intent/api/v1/network-device/count",
+            200,
+            r#"{"response":[]}"#,
+        )
+        .await;
+        mock.mock_task("PUT", path, false).await;
+
+        let devices_fetched = |requests: Vec<wiremock::Request>| {
+            requests
+                .iter()
+                .filter(|r| r.method.as_str() == "GET" && r.url.path() == path)
+                .count()
+        };
+
+        mock.dnac.get_raw(path, None, None).await.unwrap();
+        // a read sent as POST keeps the cached response
+        mock.dnac
+            .post_query::<_, Value>("/dna/intent/api/v1/network-device/count", ())
+            .await
+            .unwrap();
+        mock.dnac.get_raw(path, None, None).await.unwrap();
+        assert_eq!(
+            devices_fetched(mock.server.received_requests().await.unwrap()),
+            1
+        );
+
+        mock.dnac.put_task(path, None, ()).await.unwrap();
+        mock.dnac.get_raw(path, None, None).await.unwrap();
+        assert_eq!(
+            devices_fetched(mock.server.received_requests().await.unwrap()),
+            2
+        );
+    }
+
+    #[tokio::test]
+    async fn test_token_error_status() {
+        let mock = crate::test_util::MockDnac::start().await;
+        mock.mock_response(
+            "POST",
+            "/dna/system/api/v1/auth/token",
+            401,
+            r#"{"error":"Authentication has failed. Please provide valid credentials."}"#,
+        )
+        .await;
+
+        let e = mock.dnac.get_token().await.unwrap_err();
+        assert_eq!(
+            e.downcast::<StatusError>().unwrap().status,
+            StatusCode::UNAUTHORIZED
+        );
+    }
+
+    #[tokio::test]
+    async fn test_middleware() {
+        use wiremock::{matchers::header, Mock, ResponseTemplate};
+
+        struct GatewayHeader;
+
+        impl RequestInterceptor for GatewayHeader {
+            fn on_request(&self, request: &mut reqwest::Request) {
+                request
+                    .headers_mut()
+                    .insert("X-Gateway-Key", "secret".parse().unwrap());
+            }
+        }
+
+        let mut mock = crate::test_util::MockDnac::start().await;
+        Mock::given(header("X-Gateway-Key", "secret"))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&mock.server)
+            .await;
+
+        mock.dnac.add_middleware(Box::new(GatewayHeader));
+        mock.dnac
+            .delete("/dna/intent/api/v1/some-resource", None, false)
+            .await
+            .unwrap();
+    }
+
+    #[test]
+    fn test_debug_redacts_secrets() {
+        let token = Token {
+            token: "eyJhbGciOiJSUzI1NiJ9".to_string(),
+            exp: None,
+        };
+
+        assert!(!format!("{token:?}").contains("eyJhbGciOiJSUzI1NiJ9"));
+    }
+
+    #[tokio::test]
+    async fn test_get_raw() {
+        let mock = crate::test_util::MockDnac::start().await;
+        mock.mock_devices().await;
+
+        let devices = mock
+            .dnac
+            .get_all_raw("/dna/intent/api/v1/network-device", None)
+            .await
+            .unwrap();
+        assert_eq!(devices.len(), 3);
+        assert_eq!(devices[0]["hostname"], "zrh-acc-sw01");
+    }
+
+    #[tokio::test]
+    async fn test_poll_task_with_cancel() {
+        let mock = crate::test_util::MockDnac::start().await;
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+
+        let task_info = TaskInfo {
+            task_id: crate::test_util::fixtures::TASK_ID.to_string(),
+            url: format!("/api/v1/task/{}", crate::test_util::fixtures::TASK_ID),
+        };
+        let result = mock
+            .dnac
+            .poll_task_with_cancel(task_info, Some(&cancel))
+            .await;
+        assert!(result.unwrap_err().downcast::<Cancelled>().is_ok());
+        assert_eq!(mock.dnac.metrics().active_task_polls, 0);
+    }
+
+    #[test]
+    fn test_token_parse() {
+        let mut token = Token {
+            token: "eyJhbGciOiJIUzI1NiIsInR5cCI6IkpXVCJ9.eyJzdWIiOiJhZG1pbiIsImV4cCI6NDEwMjQ0NDgwMH0.c2ln".to_string(),
+            exp: None,
+        };
+        token.parse().unwrap();
+        assert_eq!(token.exp, Some(4102444800));
+
+        token.token = "eyJhbGciOiJIUzI1NiIsInR5cCI6IkpXVCJ9.eyJzdWIiOiJhZG1pbiJ9.c2ln".to_string();
+        token.parse().unwrap();
+        assert_eq!(token.exp, None);
+        assert!(!token.valid());
+
+        token.token = "opaque-token".to_string();
+        assert!(token.parse().is_err());
+    }
+
+    #[test]
+    fn test_pagination_policy() {
+        let mut policy = PaginationPolicy::default();
+        let limits = PaginationLimits {
+            max_offset: 100,
+            max_limit: 10,
+        };
+        policy.set_endpoint_limits("/dna/intent/api/v1/network-device", limits);
+
+        assert_eq!(
+            policy.limits_for("/dna/intent/api/v1/network-device"),
+            limits
+        );
+        assert_eq!(
+            policy.limits_for("/dna/intent/api/v2/site"),
+            PaginationLimits::default()
+        );
+
+        let pagination = Pagination::builder().with_offset(101).build();
+        assert!(pagination
+            .validate("/dna/intent/api/v1/network-device", limits)
+            .is_err());
+        assert_eq!(pagination.clamp(limits).limit, 10);
     }
 }
@@ -0,0 +1,379 @@
+use std::sync::Mutex;
+
+use anyhow::Result;
+use reqwest::Method;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use tracing::{event, Level};
+
+use crate::dnac::{
+    ApiClient, ApiClientExt, ExecutionInfo, ExecutionStatus, Pagination, Response, Task, TaskInfo,
+};
+
+// task id returned by post_task in dry run, no task exists for it
+pub const DRY_RUN_TASK_ID: &str = "dry-run";
+// stands in for the ids a task would report, e.g. of a created template, a
+// deployment, a discovery or an exported file
+pub const DRY_RUN_PLACEHOLDER_ID: &str = "00000000-0000-0000-0000-000000000000";
+
+// DryRun wraps a client and can be passed to every workflow instead of it.
+// Reads are forwarded so all validation runs against the real cluster, while
+// writes are only recorded and can be inspected with planned. Whatever a
+// workflow reads from the result of a write is a placeholder: task trees
+// report DRY_RUN_PLACEHOLDER_ID, its file is empty and uploads return a task.
+// Endpoints which answer an upload with something else, like the context id
+// of the map import, fail after the upload was recorded.
+pub struct DryRun<'a, C> {
+    client: &'a C,
+    planned: Mutex<Vec<PlannedRequest>>,
+}
+
+// PlannedRequest is a write which would have been sent, body is the exact
+// payload including secrets, so be careful where it gets stored
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PlannedRequest {
+    #[serde(serialize_with = "serialize_method")]
+    pub method: Method,
+    pub path: String,
+    pub query: Vec<(String, String)>,
+    pub body: Option<Value>,
+}
+
+impl<'a, C: ApiClient> DryRun<'a, C> {
+    pub fn new(client: &'a C) -> Self {
+        Self {
+            client,
+            planned: Mutex::new(vec![]),
+        }
+    }
+
+    // returns the writes recorded so far, in the order they were issued
+    pub fn planned(&self) -> Vec<PlannedRequest> {
+        self.planned.lock().unwrap().clone()
+    }
+
+    pub fn into_planned(self) -> Vec<PlannedRequest> {
+        self.planned.into_inner().unwrap()
+    }
+
+    fn record<T: Serialize>(
+        &self,
+        method: Method,
+        path: &str,
+        query: Option<&[(&str, String)]>,
+        data: Option<T>,
+    ) -> Result<()> {
+        let body = data.map(serde_json::to_value).transpose()?;
+        event!(Level::INFO, "Dry run: {method} {path}");
+
+        self.planned.lock().unwrap().push(PlannedRequest {
+            method,
+            path: path.to_string(),
+            query: query
+                .unwrap_or_default()
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.clone()))
+                .collect(),
+            body,
+        });
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl<C: ApiClient> ApiClient for DryRun<'_, C> {
+    async fn get<T>(
+        &self,
+        path: &str,
+        input_query: Option<&[(&str, String)]>,
+        pagination: Option<Pagination>,
+    ) -> Result<Response<T>>
+    where
+        T: DeserializeOwned + Send,
+    {
+        self.client.get(path, input_query, pagination).await
+    }
+
+    async fn get_raw(
+        &self,
+        path: &str,
+        input_query: Option<&[(&str, String)]>,
+        pagination: Option<Pagination>,
+    ) -> Result<Value> {
+        self.client.get_raw(path, input_query, pagination).await
+    }
+
+    async fn post<T>(&self, path: &str, data: T, _poll: bool) -> Result<()>
+    where
+        T: Serialize + Send,
+    {
+        self.record(Method::POST, path, None, Some(data))
+    }
+
+    async fn post_task<T>(&self, path: &str, data: T) -> Result<TaskInfo>
+    where
+        T: Serialize + Send,
+    {
+        self.record(Method::POST, path, None, Some(data))?;
+        Ok(dry_run_task())
+    }
+
+    async fn put<T>(&self, path: &str, data: T, _poll: bool) -> Result<()>
+    where
+        T: Serialize + Send,
+    {
+        self.record(Method::PUT, path, None, Some(data))
+    }
+
+    async fn delete(
+        &self,
+        path: &str,
+        input_query: Option<&[(&str, String)]>,
+        _poll: bool,
+    ) -> Result<()> {
+        self.record::<()>(Method::DELETE, path, input_query, None)
+    }
+
+    fn version(&self) -> Option<&str> {
+        self.client.version()
+    }
+
+    fn record_retry(&self) {
+        self.client.record_retry();
+    }
+
+    // nothing was sent, so a finished task tree with placeholders is returned
+    async fn poll_task(&self, task_info: TaskInfo) -> Result<Vec<Task>> {
+        Ok(dry_run_task_tree(task_info))
+    }
+}
+
+#[async_trait::async_trait]
+impl<C: ApiClientExt> ApiClientExt for DryRun<'_, C> {
+    async fn get_raw_with_headers(&self, path: &str, headers: &[(&str, String)]) -> Result<Value> {
+        self.client.get_raw_with_headers(path, headers).await
+    }
+
+    // nothing was written, so there is no result
+    async fn post_raw(&self, path: &str, data: &Value) -> Result<Value> {
+        self.record(Method::POST, path, None, Some(data))?;
+        Ok(Value::Null)
+    }
+
+    async fn post_execution<T>(&self, path: &str, data: T) -> Result<ExecutionInfo>
+    where
+        T: Serialize + Send,
+    {
+        self.record(Method::POST, path, None, Some(data))?;
+        Ok(dry_run_execution())
+    }
+
+    // queries don't change anything, so they are forwarded like reads
+    async fn post_query<T, R>(&self, path: &str, data: T) -> Result<Response<R>>
+    where
+        T: Serialize + Send,
+        R: DeserializeOwned + Send,
+    {
+        self.client.post_query(path, data).await
+    }
+
+    async fn put_query<T>(&self, path: &str, data: T) -> Result<Value>
+    where
+        T: Serialize + Send,
+    {
+        self.client.put_query(path, data).await
+    }
+
+    async fn put_task<T>(
+        &self,
+        path: &str,
+        input_query: Option<&[(&str, String)]>,
+        data: T,
+    ) -> Result<TaskInfo>
+    where
+        T: Serialize + Send,
+    {
+        self.record(Method::PUT, path, input_query, Some(data))?;
+        Ok(dry_run_task())
+    }
+
+    async fn put_execution<T>(&self, path: &str, data: T) -> Result<ExecutionInfo>
+    where
+        T: Serialize + Send,
+    {
+        self.record(Method::PUT, path, None, Some(data))?;
+        Ok(dry_run_execution())
+    }
+
+    async fn delete_execution(&self, path: &str) -> Result<ExecutionInfo> {
+        self.record::<()>(Method::DELETE, path, None, None)?;
+        Ok(dry_run_execution())
+    }
+
+    async fn poll_execution(&self, execution_info: ExecutionInfo) -> Result<ExecutionStatus> {
+        Ok(ExecutionStatus {
+            bapi_execution_id: Some(execution_info.execution_id),
+            bapi_name: None,
+            status: "SUCCESS".to_string(),
+            bapi_error: None,
+            start_time_epoch: None,
+            end_time_epoch: None,
+        })
+    }
+
+    // the file of a dry run task doesn't exist, it is returned empty
+    async fn download(&self, path: &str) -> Result<Vec<u8>> {
+        if path.ends_with(&format!("/file/{DRY_RUN_PLACEHOLDER_ID}")) {
+            return Ok(vec![]);
+        }
+        self.client.download(path).await
+    }
+
+    // only the name and size of the file are recorded
+    async fn upload(&self, path: &str, file_name: &str, data: Vec<u8>) -> Result<Value> {
+        let file = serde_json::json!({"fileName": file_name, "size": data.len()});
+        self.record(Method::POST, path, None, Some(file))?;
+        Ok(dry_run_upload())
+    }
+
+    async fn upload_file(&self, path: &str, file: &std::path::Path) -> Result<Value> {
+        let size = tokio::fs::metadata(file).await?.len();
+        let file = serde_json::json!({"fileName": file.display().to_string(), "size": size});
+        self.record(Method::POST, path, None, Some(file))?;
+        Ok(dry_run_upload())
+    }
+}
+
+fn dry_run_task() -> TaskInfo {
+    TaskInfo {
+        task_id: DRY_RUN_TASK_ID.to_string(),
+        url: format!("/api/v1/task/{DRY_RUN_TASK_ID}"),
+    }
+}
+
+// the root task carries the ids in data and progress, the export workflows
+// read their json from the data of any task, so a child returns an empty export
+fn dry_run_task_tree(task_info: TaskInfo) -> Vec<Task> {
+    let task = |id: String, parent_id: Option<String>, data: &str, progress: &str| Task {
+        id,
+        additional_status_url: Some(format!("/file/{DRY_RUN_PLACEHOLDER_ID}")),
+        data: Some(data.to_string()),
+        end_time: Some(0),
+        error_code: None,
+        error_key: None,
+        failure_reason: None,
+        instance_tenant_id: String::new(),
+        is_error: false,
+        last_update: None,
+        operation_id_list: None,
+        parent_id,
+        progress: progress.to_string(),
+        root_id: Some(task_info.task_id.clone()),
+        service_type: "dry-run".to_string(),
+        start_time: 0,
+        username: None,
+        version: 0,
+    };
+
+    vec![
+        task(
+            task_info.task_id.clone(),
+            None,
+            DRY_RUN_PLACEHOLDER_ID,
+            DRY_RUN_PLACEHOLDER_ID,
+        ),
+        task(
+            format!("{}-export", task_info.task_id),
+            Some(task_info.task_id.clone()),
+            "[]",
+            "dry run",
+        ),
+    ]
+}
+
+// the response most upload endpoints return
+fn dry_run_upload() -> Value {
+    let task = dry_run_task();
+    serde_json::json!({"response": {"taskId": task.task_id, "url": task.url}})
+}
+
+fn dry_run_execution() -> ExecutionInfo {
+    ExecutionInfo {
+        execution_id: DRY_RUN_TASK_ID.to_string(),
+        execution_status_url: format!(
+            "/dna/platform/management/business-api/v1/execution-status/{DRY_RUN_TASK_ID}"
+        ),
+        message: None,
+    }
+}
+
+fn serialize_method<S: serde::Serializer>(
+    method: &Method,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error> {
+    serializer.serialize_str(method.as_str())
+}
+
+#[cfg(all(test, feature = "devices"))]
+mod tests {
+    use super::*;
+    use crate::{
+        devices::{AddDevice, Device, ExportOperation, ExportRequest},
+        discovery::{Discovery, DiscoveryRequest},
+        test_util::MockDnac,
+    };
+
+    #[tokio::test]
+    async fn test_dry_run() {
+        let mock = MockDnac::start().await;
+        mock.mock_devices().await;
+
+        let dry_run = DryRun::new(&mock.dnac);
+        let devices = Device::get_device_list(&dry_run, None, None).await.unwrap();
+        assert_eq!(devices.len(), 3);
+
+        let device = AddDevice {
+            ip_address: vec!["10.0.0.1".to_string()],
+            ..Default::default()
+        };
+        Device::add_device(&dry_run, device).await.unwrap();
+
+        let planned = dry_run.into_planned();
+        assert_eq!(planned.len(), 1);
+        assert_eq!(planned[0].method, Method::POST);
+        assert_eq!(planned[0].path, "/dna/intent/api/v1/network-device");
+        assert_eq!(
+            planned[0].body.as_ref().unwrap()["ipAddress"][0],
+            "10.0.0.1"
+        );
+
+        // only the device list was requested, the version is already known
+        assert_eq!(mock.server.received_requests().await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_task_tree() {
+        let mock = MockDnac::start().await;
+        let dry_run = DryRun::new(&mock.dnac);
+
+        let request = ExportRequest {
+            device_ids: vec![],
+            operation: ExportOperation::DeviceDetails,
+            password: None,
+            parameters: vec![],
+        };
+        assert!(Device::export(&dry_run, request).await.unwrap().is_empty());
+
+        let request = DiscoveryRequest {
+            name: "dry run".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            Discovery::start(&dry_run, request).await.unwrap(),
+            DRY_RUN_PLACEHOLDER_ID
+        );
+
+        assert_eq!(dry_run.planned().len(), 2);
+        assert!(mock.server.received_requests().await.unwrap().is_empty());
+    }
+}
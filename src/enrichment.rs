@@ -0,0 +1,288 @@
+use std::{collections::HashMap, fmt};
+
+use serde::Deserialize;
+use thiserror::Error;
+use tracing::{event, Level};
+
+#[cfg(feature = "assurance")]
+use crate::clients::ClientDetail;
+use crate::{
+    devices::{Device, DeviceModule},
+    dnac::ApiClientExt,
+    topology::{TopologyLink, TopologyNode},
+};
+
+// SerialEnricher is implemented by the caller to attach external data (e.g.
+// support contracts) to serial numbers. enrich is called with batches of at
+// most batch_size serials, serials without data can be left out of the result.
+#[async_trait::async_trait]
+pub trait SerialEnricher: Sync {
+    type Data: Clone + Send;
+
+    fn batch_size(&self) -> usize {
+        100
+    }
+
+    async fn enrich(&self, serials: &[String]) -> anyhow::Result<HashMap<String, Self::Data>>;
+}
+
+// SerialNumbers is implemented by the inventory models that carry serials,
+// a stack or chassis can report multiple serials for a single device
+pub trait SerialNumbers {
+    fn serial_numbers(&self) -> Vec<String>;
+}
+
+#[derive(Debug)]
+pub struct Enriched<T, D> {
+    pub item: T,
+    // the data per serial of the item, in the order of serial_numbers
+    pub enrichment: Vec<(String, Option<D>)>,
+}
+
+impl SerialNumbers for Device {
+    // DNAC reports the serials of a stack as comma separated list
+    fn serial_numbers(&self) -> Vec<String> {
+        split_serials(self.serial_number.as_deref())
+    }
+}
+
+impl SerialNumbers for DeviceModule {
+    fn serial_numbers(&self) -> Vec<String> {
+        split_serials(self.serial_number.as_deref())
+    }
+}
+
+// enrich collects the unique serials of all items, calls the enricher in
+// batches and joins the results back onto the items
+pub async fn enrich<T, E>(items: Vec<T>, enricher: &E) -> anyhow::Result<Vec<Enriched<T, E::Data>>>
+where
+    T: SerialNumbers,
+    E: SerialEnricher,
+{
+    let mut serials: Vec<String> = items.iter().flat_map(|i| i.serial_numbers()).collect();
+    serials.sort();
+    serials.dedup();
+
+    let mut data = HashMap::new();
+    for batch in serials.chunks(enricher.batch_size().max(1)) {
+        data.extend(enricher.enrich(batch).await?);
+    }
+
+    Ok(items
+        .into_iter()
+        .map(|item| {
+            let enrichment = item
+                .serial_numbers()
+                .into_iter()
+                .map(|serial| {
+                    let entry = data.get(&serial).cloned();
+                    (serial, entry)
+                })
+                .collect();
+            Enriched { item, enrichment }
+        })
+        .collect())
+}
+
+// Enrichment wraps the enrichment details of assurance, they combine the
+// details of a client or device with its neighbor topology
+pub struct Enrichment;
+
+// the entity is sent as entity_type and entity_value header
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntityType {
+    Mac,
+    Ip,
+    Username,
+    Uuid,
+}
+
+impl fmt::Display for EntityType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self {
+            Self::Mac => write!(f, "mac_address"),
+            Self::Ip => write!(f, "ip_address"),
+            Self::Username => write!(f, "network_user_id"),
+            Self::Uuid => write!(f, "uuid"),
+        }
+    }
+}
+
+#[cfg(feature = "assurance")]
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClientEnrichment {
+    pub user_details: ClientDetail,
+    // the AP or switch the client is connected to
+    #[serde(default)]
+    pub connected_device: Vec<DeviceEnrichment>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceEnrichment {
+    pub device_details: EnrichedDevice,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnrichedDevice {
+    #[serde(flatten)]
+    pub device: Device,
+    pub neighbor_topology: Option<Vec<NeighborTopology>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NeighborTopology {
+    #[serde(default)]
+    pub nodes: Vec<TopologyNode>,
+    #[serde(default)]
+    pub links: Vec<TopologyLink>,
+}
+
+#[derive(Debug, Error)]
+pub enum EnrichmentError {
+    #[error("General Enrichment Error")]
+    GeneralError,
+}
+
+async fn get_enrichment<C, T>(
+    dnac: &C,
+    path: &str,
+    entity_type: EntityType,
+    value: &str,
+) -> Result<Vec<T>, EnrichmentError>
+where
+    C: ApiClientExt,
+    T: serde::de::DeserializeOwned,
+{
+    let headers = [
+        ("entity_type", entity_type.to_string()),
+        ("entity_value", value.to_string()),
+    ];
+
+    match dnac.get_raw_with_headers(path, &headers).await {
+        Ok(enrichment_data) => serde_json::from_value(enrichment_data).map_err(|e| {
+            event!(Level::ERROR, "{e}");
+            EnrichmentError::GeneralError
+        }),
+        Err(e) => {
+            event!(Level::ERROR, "{e}");
+            Err(EnrichmentError::GeneralError)
+        }
+    }
+}
+
+impl Enrichment {
+    // clients are looked up by Mac or Username
+    #[cfg(feature = "assurance")]
+    pub async fn client<C: ApiClientExt>(
+        dnac: &C,
+        entity_type: EntityType,
+        value: &str,
+    ) -> Result<Vec<ClientEnrichment>, EnrichmentError> {
+        let path = "/dna/intent/api/v1/client-enrichment-details";
+        get_enrichment(dnac, path, entity_type, value).await
+    }
+
+    // devices are looked up by Mac, Ip or Uuid
+    pub async fn device<C: ApiClientExt>(
+        dnac: &C,
+        entity_type: EntityType,
+        value: &str,
+    ) -> Result<Vec<DeviceEnrichment>, EnrichmentError> {
+        let path = "/dna/intent/api/v1/device-enrichment-details";
+        get_enrichment(dnac, path, entity_type, value).await
+    }
+}
+
+fn split_serials(serials: Option<&str>) -> Vec<String> {
+    serials
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::MockDnac;
+    use wiremock::{
+        matchers::{header, method, path},
+        Mock, ResponseTemplate,
+    };
+
+    struct Contracts;
+
+    #[async_trait::async_trait]
+    impl SerialEnricher for Contracts {
+        type Data = String;
+
+        fn batch_size(&self) -> usize {
+            1
+        }
+
+        async fn enrich(&self, serials: &[String]) -> anyhow::Result<HashMap<String, String>> {
+            assert_eq!(serials.len(), 1);
+            Ok(serials
+                .iter()
+                .filter(|s| s.starts_with("FOC"))
+                .map(|s| (s.clone(), format!("contract-{s}")))
+                .collect())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_enrich() {
+        let module = r#"
+          {"id":"1","serialNumber":"FOC1234, JAE5678"}
+          "#;
+        let module: DeviceModule = serde_json::from_str(module).unwrap();
+
+        let enriched = enrich(vec![module], &Contracts).await.unwrap();
+        assert_eq!(
+            enriched[0].enrichment,
+            vec![
+                ("FOC1234".to_string(), Some("contract-FOC1234".to_string())),
+                ("JAE5678".to_string(), None),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_device_enrichment() {
+        let mock = MockDnac::start().await;
+        let body: serde_json::Value = serde_json::from_str(
+            r#"
+              [{"deviceDetails":{"id":"3f0ac5d4-6f3d-4b5e-9d2b-5b0f3a3c2f10","collectionStatus":"Managed","managementIpAddress":"10.10.1.11","hostname":"zrh-acc-sw01","family":"Switches and Hubs","platformId":"C9300-48P",
+                 "neighborTopology":[{"nodes":[{"id":"3f0ac5d4-6f3d-4b5e-9d2b-5b0f3a3c2f10","name":"zrh-acc-sw01","role":"ACCESS"},{"id":"5b0f3a3c-2f10-4f0a-8c5d-46f3d4b5e9d2","name":"zrh-dist-sw01","role":"DISTRIBUTION"}],
+                                      "links":[{"source":"3f0ac5d4-6f3d-4b5e-9d2b-5b0f3a3c2f10","target":"5b0f3a3c-2f10-4f0a-8c5d-46f3d4b5e9d2","linkStatus":"UP","id":"1"}]}]}}]
+              "#,
+        )
+        .unwrap();
+        // the entity is only matched by header, the url has no parameters
+        Mock::given(method("GET"))
+            .and(path("/dna/intent/api/v1/device-enrichment-details"))
+            .and(header("entity_type", "ip_address"))
+            .and(header("entity_value", "10.10.1.11"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(body))
+            .mount(&mock.server)
+            .await;
+
+        let enrichment = Enrichment::device(&mock.dnac, EntityType::Ip, "10.10.1.11")
+            .await
+            .unwrap();
+        let device = &enrichment[0].device_details;
+        assert_eq!(device.device.hostname.as_deref(), Some("zrh-acc-sw01"));
+
+        let topology = &device.neighbor_topology.as_ref().unwrap()[0];
+        assert_eq!(topology.nodes.len(), 2);
+        assert_eq!(
+            topology.links[0].link_status,
+            Some(crate::topology::LinkStatus::Up)
+        );
+    }
+}
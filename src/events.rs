@@ -0,0 +1,614 @@
+use std::{convert::Infallible, fmt, str::FromStr};
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use thiserror::Error;
+use tracing::{event, Level};
+
+#[cfg(feature = "assurance")]
+use crate::{
+    devices::DeviceFamily,
+    dnac::ResponseType,
+    ids::{DeviceId, SiteId},
+};
+use crate::{
+    dnac::{ApiClient, Pagination, PaginationError, REDACTED},
+    query::Query,
+};
+
+// the device family of the wireless client events, it's not a family of
+// the inventory and therefore not part of DeviceFamily
+#[cfg(feature = "assurance")]
+const WIRELESS_CLIENT: &str = "Wireless Client";
+
+// AssuranceEvent is a single event of the event viewer, e.g. a syslog
+// message of a switch or the onboarding event of a wireless client
+#[cfg(feature = "assurance")]
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssuranceEvent {
+    pub id: String,
+    pub device_family: Option<DeviceFamily>,
+    pub timestamp: Option<u64>,
+    pub message_type: Option<MessageType>,
+    // the syslog severity, 0 is emergency and 7 debug
+    pub severity: Option<u8>,
+    pub name: Option<String>,
+    pub details: Option<String>,
+    pub site_id: Option<String>,
+    pub site_hierarchy_id: Option<String>,
+    pub network_device_name: Option<String>,
+    pub network_device_id: Option<String>,
+    pub client_mac: Option<String>,
+    pub ap_mac: Option<String>,
+    // the remaining attributes depend on the message type
+    #[serde(flatten)]
+    pub other: Map<String, Value>,
+}
+
+// message types DNAC reports which are not listed are kept as Other
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum MessageType {
+    Syslog,
+    Trap,
+    NetFlow,
+    BulkStats,
+    Other(String),
+}
+
+// AssuranceEventQuery selects the events, DNAC requires the device family
+// and defaults the time window to the last hour
+#[cfg(feature = "assurance")]
+#[derive(Debug, Clone)]
+pub struct AssuranceEventQuery {
+    pub device_family: DeviceFamily,
+    pub message_types: Vec<MessageType>,
+    pub severities: Vec<u8>,
+    pub start_time: Option<u64>,
+    pub end_time: Option<u64>,
+    pub site_id: Option<SiteId>,
+    pub network_device_id: Option<DeviceId>,
+    pub client_mac: Option<String>,
+    pub ap_mac: Option<String>,
+}
+
+// EventSubscription registers destinations for events of DNAC, the events
+// are selected by their id, e.g. NETWORK-DEVICES-3-251
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventSubscription {
+    // assigned by DNAC, has to be set for update
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subscription_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub subscription_endpoints: Vec<SubscriptionEndpoint>,
+    pub filter: SubscriptionFilter,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubscriptionEndpoint {
+    // the id of the destination, e.g. the webhook_id of a WebhookDestination
+    pub instance_id: String,
+    pub subscription_details: SubscriptionDetails,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubscriptionDetails {
+    pub connector_type: ConnectorType,
+    // only returned by DNAC, the destination is configured separately
+    #[serde(skip_serializing)]
+    pub name: Option<String>,
+    #[serde(skip_serializing)]
+    pub url: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum ConnectorType {
+    Rest,
+    Email,
+    Syslog,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubscriptionFilter {
+    #[serde(default)]
+    pub event_ids: Vec<String>,
+    // the remaining filters, e.g. domainsSubdomains or severities, are kept
+    // so a listed subscription can be updated without losing them
+    #[serde(flatten)]
+    pub other: Map<String, Value>,
+}
+
+// WebhookDestination is a REST endpoint the events are sent to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookDestination {
+    // assigned by DNAC, has to be set for update
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub webhook_id: Option<String>,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub url: String,
+    // POST or PUT
+    pub method: String,
+    pub trust_cert: bool,
+    #[serde(default)]
+    pub headers: Vec<WebhookHeader>,
+    #[serde(default)]
+    pub is_proxy_route: bool,
+}
+
+// headers with encrypt set are stored encrypted and not returned by DNAC,
+// e.g. the Authorization header of the destination
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookHeader {
+    pub name: String,
+    #[serde(default)]
+    pub value: String,
+    #[serde(default)]
+    pub default_value: String,
+    #[serde(default)]
+    pub encrypt: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct WebhookData {
+    #[serde(default)]
+    status_message: Vec<WebhookDestination>,
+}
+
+#[derive(Debug, Error)]
+pub enum EventError {
+    #[error("General Event Error")]
+    GeneralError,
+    #[error(transparent)]
+    PaginationOutOfRange(#[from] PaginationError),
+}
+
+impl fmt::Display for MessageType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self {
+            Self::Syslog => write!(f, "Syslog"),
+            Self::Trap => write!(f, "Trap"),
+            Self::NetFlow => write!(f, "NetFlow"),
+            Self::BulkStats => write!(f, "Bulk Stats"),
+            Self::Other(message_type) => write!(f, "{message_type}"),
+        }
+    }
+}
+
+impl FromStr for MessageType {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "Syslog" => Self::Syslog,
+            "Trap" => Self::Trap,
+            "NetFlow" => Self::NetFlow,
+            "Bulk Stats" => Self::BulkStats,
+            other => Self::Other(other.to_string()),
+        })
+    }
+}
+
+impl From<String> for MessageType {
+    fn from(message_type: String) -> Self {
+        let Ok(message_type) = message_type.parse();
+        message_type
+    }
+}
+
+impl From<MessageType> for String {
+    fn from(message_type: MessageType) -> Self {
+        message_type.to_string()
+    }
+}
+
+#[cfg(feature = "assurance")]
+impl AssuranceEvent {
+    pub async fn query<C: ApiClient>(
+        dnac: &C,
+        query: &AssuranceEventQuery,
+        pagination: Option<Pagination>,
+    ) -> Result<Vec<AssuranceEvent>, EventError> {
+        let path = "/dna/data/api/v1/assuranceEvents";
+        let query = query.to_query();
+
+        let event_data = dnac
+            .get::<AssuranceEvent>(path, Some(query.as_slice()), pagination)
+            .await;
+
+        match event_data {
+            Ok(event_data) => match event_data.response {
+                ResponseType::Array(data) => Ok(data),
+                ResponseType::Item(data) => Ok(vec![data]),
+            },
+            Err(e) => match e.downcast::<PaginationError>() {
+                Ok(pagination_error) => Err(pagination_error.into()),
+                Err(e) => {
+                    event!(Level::ERROR, "{e}");
+                    Err(EventError::GeneralError)
+                }
+            },
+        }
+    }
+
+    pub fn is_client_event(&self) -> bool {
+        self.device_family
+            .as_ref()
+            .is_some_and(|family| family.to_string() == WIRELESS_CLIENT)
+    }
+}
+
+#[cfg(feature = "assurance")]
+impl AssuranceEventQuery {
+    pub fn new(device_family: DeviceFamily) -> Self {
+        Self {
+            device_family,
+            message_types: vec![],
+            severities: vec![],
+            start_time: None,
+            end_time: None,
+            site_id: None,
+            network_device_id: None,
+            client_mac: None,
+            ap_mac: None,
+        }
+    }
+
+    // the events of the wireless clients, e.g. onboarding and roaming
+    pub fn clients() -> Self {
+        Self::new(DeviceFamily::Other(WIRELESS_CLIENT.to_string()))
+    }
+
+    // the message types and severities are sent as comma separated lists
+    pub fn to_query(&self) -> Query<'static> {
+        let message_types = (!self.message_types.is_empty()).then(|| join(&self.message_types));
+        let severities = (!self.severities.is_empty()).then(|| join(&self.severities));
+
+        Query::new()
+            .eq("deviceFamily", &self.device_family)
+            .eq_opt("messageType", message_types)
+            .eq_opt("severity", severities)
+            .eq_opt("startTime", self.start_time)
+            .eq_opt("endTime", self.end_time)
+            .eq_opt("siteId", self.site_id)
+            .eq_opt("networkDeviceId", self.network_device_id)
+            .eq_opt("clientMac", self.client_mac.as_ref())
+            .eq_opt("apMac", self.ap_mac.as_ref())
+    }
+}
+
+impl fmt::Debug for WebhookHeader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = if self.encrypt { REDACTED } else { &self.value };
+        f.debug_struct("WebhookHeader")
+            .field("name", &self.name)
+            .field("value", &value)
+            .field("default_value", &self.default_value)
+            .field("encrypt", &self.encrypt)
+            .finish()
+    }
+}
+
+impl EventSubscription {
+    // the subscriptions which include any of the event ids, all if empty
+    pub async fn list<C: ApiClient>(
+        dnac: &C,
+        event_ids: &[&str],
+        pagination: Option<Pagination>,
+    ) -> Result<Vec<EventSubscription>, EventError> {
+        let path = "/dna/intent/api/v1/event/subscription";
+        let event_ids = (!event_ids.is_empty()).then(|| join(event_ids));
+        let query = Query::new().eq_opt("eventIds", event_ids);
+
+        get_event_data(dnac, path, Some(query.as_slice()), pagination).await
+    }
+
+    // DNAC validates the subscription asynchronously, it only shows up in
+    // list once it was accepted
+    pub async fn create<C: ApiClient>(dnac: &C, subscription: &EventSubscription) -> Result<()> {
+        let path = "/dna/intent/api/v1/event/subscription";
+        dnac.post(path, [subscription], false).await
+    }
+
+    pub async fn update<C: ApiClient>(dnac: &C, subscription: &EventSubscription) -> Result<()> {
+        if subscription.subscription_id.is_none() {
+            return Err(anyhow!("Subscription {} has no id", subscription.name));
+        }
+
+        let path = "/dna/intent/api/v1/event/subscription";
+        dnac.put(path, [subscription], false).await
+    }
+
+    pub async fn delete<C: ApiClient>(dnac: &C, subscription_ids: &[&str]) -> Result<()> {
+        let path = "/dna/intent/api/v1/event/subscription";
+        let query = Query::new().eq("subscriptions", join(subscription_ids));
+        dnac.delete(path, Some(query.as_slice()), false).await
+    }
+}
+
+impl SubscriptionEndpoint {
+    pub fn rest(webhook_id: &str) -> Self {
+        Self {
+            instance_id: webhook_id.to_string(),
+            subscription_details: SubscriptionDetails {
+                connector_type: ConnectorType::Rest,
+                name: None,
+                url: None,
+            },
+        }
+    }
+}
+
+impl WebhookDestination {
+    pub async fn list<C: ApiClient>(dnac: &C) -> Result<Vec<WebhookDestination>, EventError> {
+        let path = "/dna/system/api/v1/event/webhook";
+        let webhook_data: WebhookData = get_event_data(dnac, path, None, None).await?;
+        Ok(webhook_data.status_message)
+    }
+
+    pub async fn create<C: ApiClient>(dnac: &C, destination: &WebhookDestination) -> Result<()> {
+        let path = "/dna/system/api/v1/event/webhook";
+        dnac.post(path, destination, false).await
+    }
+
+    pub async fn update<C: ApiClient>(dnac: &C, destination: &WebhookDestination) -> Result<()> {
+        if destination.webhook_id.is_none() {
+            return Err(anyhow!("Webhook {} has no id", destination.name));
+        }
+
+        let path = "/dna/system/api/v1/event/webhook";
+        dnac.put(path, destination, false).await
+    }
+}
+
+// the event management endpoints don't wrap their body in a response field
+async fn get_event_data<C, T>(
+    dnac: &C,
+    path: &str,
+    query: Option<&[(&str, String)]>,
+    pagination: Option<Pagination>,
+) -> Result<T, EventError>
+where
+    C: ApiClient,
+    T: serde::de::DeserializeOwned,
+{
+    match dnac.get_raw(path, query, pagination).await {
+        Ok(event_data) => serde_json::from_value(event_data).map_err(|e| {
+            event!(Level::ERROR, "{e}");
+            EventError::GeneralError
+        }),
+        Err(e) => match e.downcast::<PaginationError>() {
+            Ok(pagination_error) => Err(pagination_error.into()),
+            Err(e) => {
+                event!(Level::ERROR, "{e}");
+                Err(EventError::GeneralError)
+            }
+        },
+    }
+}
+
+fn join<T: ToString>(values: &[T]) -> String {
+    values
+        .iter()
+        .map(|v| v.to_string())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::MockDnac;
+
+    #[cfg(feature = "assurance")]
+    #[tokio::test]
+    async fn test_query() {
+        let mock = MockDnac::start().await;
+        mock.mock_response(
+            "GET",
+            "/dna/data/api/v1/assuranceEvents",
+            200,
+            r#"
+              {"response":[
+                {"id":"1732811427209_10.10.1.11_syslog","deviceFamily":"Switches and Hubs","timestamp":1732811427209,"messageType":"Syslog","severity":3,"name":"LINK-3-UPDOWN","details":"Interface GigabitEthernet1/0/12, changed state to down","siteId":"a4f3a3c2-f103-4f0a-8c5d-46f3d4b5e9d2","siteHierarchyId":"00000000-0000-4000-8000-000000000001/a4f3a3c2-f103-4f0a-8c5d-46f3d4b5e9d2","networkDeviceName":"zrh-acc-sw01","networkDeviceId":"3f0ac5d4-6f3d-4b5e-9d2b-5b0f3a3c2f10","facility":"LINK","mnemonic":"UPDOWN"},
+                {"id":"1732811429512_00:50:56:a1:b2:c3","deviceFamily":"Wireless Client","timestamp":1732811429512,"messageType":"Client Onboarding","name":"Client Onboarding","clientMac":"00:50:56:a1:b2:c3","apMac":"70:0f:6a:12:34:50"}],
+               "page":{"limit":25,"offset":1,"count":2},"version":"1.0"}
+              "#,
+        )
+        .await;
+
+        let mut query = AssuranceEventQuery::new(DeviceFamily::SwitchesAndHubs);
+        query.message_types = vec![MessageType::Syslog, MessageType::Trap];
+        query.severities = vec![1, 2, 3];
+        query.start_time = Some(1732807827209);
+        let events = AssuranceEvent::query(&mock.dnac, &query, None)
+            .await
+            .unwrap();
+
+        assert_eq!(events[0].message_type, Some(MessageType::Syslog));
+        assert_eq!(events[0].severity, Some(3));
+        assert_eq!(events[0].other["mnemonic"], "UPDOWN");
+        assert!(!events[0].is_client_event());
+        assert_eq!(
+            events[1].message_type,
+            Some(MessageType::Other("Client Onboarding".to_string()))
+        );
+        assert!(events[1].is_client_event());
+
+        let requests = mock.server.received_requests().await.unwrap();
+        let request = requests
+            .iter()
+            .find(|r| r.url.path().ends_with("assuranceEvents"))
+            .unwrap();
+        let query: Vec<(String, String)> = request.url.query_pairs().into_owned().collect();
+        assert_eq!(
+            query,
+            vec![
+                ("deviceFamily".to_string(), "Switches and Hubs".to_string()),
+                ("messageType".to_string(), "Syslog,Trap".to_string()),
+                ("severity".to_string(), "1,2,3".to_string()),
+                ("startTime".to_string(), "1732807827209".to_string()),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_list_subscriptions() {
+        let mock = MockDnac::start().await;
+        mock.mock_response(
+            "GET",
+            "/dna/intent/api/v1/event/subscription",
+            200,
+            r#"
+              [{"version":"1.0.0","subscriptionId":"c2d4e6f8-1a3b-4c5d-8e7f-9a0b1c2d3e4f","name":"noc-device-down","description":"Device unreachable to the NOC","subscriptionEndpoints":[{"instanceId":"7b9d1f3a-5c7e-4a9b-b1d3-f5a7c9e1b3d5","subscriptionDetails":{"connectorType":"REST","instanceId":"7b9d1f3a-5c7e-4a9b-b1d3-f5a7c9e1b3d5","name":"noc-webhook","description":"","url":"https://noc.example.com/dnac","method":"POST"}}],"filter":{"eventIds":["NETWORK-DEVICES-3-251"],"others":[],"domainsSubdomains":[],"types":[],"categories":[],"severities":[],"sources":[],"siteIds":[]},"isPrivate":false,"tenantId":"6307971e4289f95403c86831"}]
+              "#,
+        )
+        .await;
+        mock.mock_response(
+            "PUT",
+            "/dna/intent/api/v1/event/subscription",
+            202,
+            r#"{"statusUri":"/dna/intent/api/v1/event/api-status/0193739c-0d88-78e4-ba0f-d82889fca555"}"#,
+        )
+        .await;
+
+        let mut subscriptions =
+            EventSubscription::list(&mock.dnac, &["NETWORK-DEVICES-3-251"], None)
+                .await
+                .unwrap();
+        let subscription = &mut subscriptions[0];
+        assert_eq!(
+            subscription.subscription_endpoints[0]
+                .subscription_details
+                .connector_type,
+            ConnectorType::Rest
+        );
+
+        subscription
+            .filter
+            .event_ids
+            .push("NETWORK-DEVICES-2-251".to_string());
+        EventSubscription::update(&mock.dnac, subscription)
+            .await
+            .unwrap();
+
+        let requests = mock.server.received_requests().await.unwrap();
+        let request = requests.iter().find(|r| r.method == "GET").unwrap();
+        assert_eq!(request.url.query(), Some("eventIds=NETWORK-DEVICES-3-251"));
+        let request = requests.iter().find(|r| r.method == "PUT").unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&request.body).unwrap();
+        assert_eq!(
+            body[0]["filter"]["eventIds"],
+            serde_json::json!(["NETWORK-DEVICES-3-251", "NETWORK-DEVICES-2-251"])
+        );
+        assert!(body[0]["filter"]["domainsSubdomains"].is_array());
+        assert_eq!(
+            body[0]["subscriptionEndpoints"][0],
+            serde_json::json!({"instanceId":"7b9d1f3a-5c7e-4a9b-b1d3-f5a7c9e1b3d5","subscriptionDetails":{"connectorType":"REST"}})
+        );
+    }
+
+    #[tokio::test]
+    async fn test_create_and_delete_subscription() {
+        let mock = MockDnac::start().await;
+        mock.mock_response(
+            "POST",
+            "/dna/intent/api/v1/event/subscription",
+            202,
+            r#"{"statusUri":"/dna/intent/api/v1/event/api-status/0193739c-0d88-78e4-ba0f-d82889fca555"}"#,
+        )
+        .await;
+        mock.mock_response(
+            "DELETE",
+            "/dna/intent/api/v1/event/subscription",
+            202,
+            r#"{"statusUri":"/dna/intent/api/v1/event/api-status/0193739c-0d88-78e4-ba0f-d82889fca555"}"#,
+        )
+        .await;
+
+        let subscription = EventSubscription {
+            subscription_id: None,
+            version: None,
+            name: "noc-device-down".to_string(),
+            description: String::new(),
+            subscription_endpoints: vec![SubscriptionEndpoint::rest(
+                "7b9d1f3a-5c7e-4a9b-b1d3-f5a7c9e1b3d5",
+            )],
+            filter: SubscriptionFilter {
+                event_ids: vec!["NETWORK-DEVICES-3-251".to_string()],
+                ..Default::default()
+            },
+        };
+        EventSubscription::create(&mock.dnac, &subscription)
+            .await
+            .unwrap();
+        assert!(EventSubscription::update(&mock.dnac, &subscription)
+            .await
+            .is_err());
+        EventSubscription::delete(
+            &mock.dnac,
+            &[
+                "c2d4e6f8-1a3b-4c5d-8e7f-9a0b1c2d3e4f",
+                "e4f6a8b0-2c4d-4e6f-9a1b-3c5d7e9f1a2b",
+            ],
+        )
+        .await
+        .unwrap();
+
+        let requests = mock.server.received_requests().await.unwrap();
+        let request = requests.iter().find(|r| r.method == "POST").unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&request.body).unwrap();
+        assert_eq!(body[0]["name"], "noc-device-down");
+        assert!(body[0].get("subscriptionId").is_none());
+        let request = requests.iter().find(|r| r.method == "DELETE").unwrap();
+        assert_eq!(
+            request.url.query(),
+            Some("subscriptions=c2d4e6f8-1a3b-4c5d-8e7f-9a0b1c2d3e4f%2Ce4f6a8b0-2c4d-4e6f-9a1b-3c5d7e9f1a2b")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_webhook_destinations() {
+        let mock = MockDnac::start().await;
+        mock.mock_response(
+            "GET",
+            "/dna/system/api/v1/event/webhook",
+            200,
+            r#"
+              {"errorMessage":null,"apiStatus":"SUCCESS","statusMessage":[{"version":"1.0.0","tenantId":"6307971e4289f95403c86831","webhookId":"7b9d1f3a-5c7e-4a9b-b1d3-f5a7c9e1b3d5","name":"noc-webhook","description":"","url":"https://noc.example.com/dnac","method":"POST","trustCert":true,"headers":[{"name":"Authorization","value":"","defaultValue":"","encrypt":true}],"isProxyRoute":false}]}
+              "#,
+        )
+        .await;
+
+        let destinations = WebhookDestination::list(&mock.dnac).await.unwrap();
+        assert_eq!(
+            destinations[0].webhook_id.as_deref(),
+            Some("7b9d1f3a-5c7e-4a9b-b1d3-f5a7c9e1b3d5")
+        );
+        assert!(destinations[0].headers[0].encrypt);
+
+        let header = WebhookHeader {
+            name: "Authorization".to_string(),
+            value: "Bearer secret".to_string(),
+            default_value: String::new(),
+            encrypt: true,
+        };
+        assert!(!format!("{header:?}").contains("secret"));
+    }
+}
@@ -0,0 +1,83 @@
+use serde::Deserialize;
+use thiserror::Error;
+use tracing::{event, Level};
+
+use crate::dnac::{ApiClientExt, Task};
+
+pub struct File;
+
+#[derive(Debug, Error)]
+pub enum FileError {
+    #[error("General File Error")]
+    GeneralError,
+    #[error("Task did not produce a file")]
+    NoFile,
+}
+
+impl File {
+    // returns the raw content of the file, e.g. a CSV or a zip archive
+    pub async fn download<C: ApiClientExt>(dnac: &C, file_id: &str) -> Result<Vec<u8>, FileError> {
+        let path = format!("/dna/intent/api/v1/file/{file_id}");
+
+        dnac.download(path.as_str()).await.map_err(|e| {
+            event!(Level::ERROR, "{e}");
+            FileError::GeneralError
+        })
+    }
+
+    // tasks which create a file report it in the additionalStatusURL as
+    // /file/{id}, some services only put the id or {"fileId": id} into the progress
+    pub fn file_id(tasks: &[Task]) -> Option<String> {
+        tasks.iter().find_map(|task| {
+            task.additional_status_url
+                .as_deref()
+                .and_then(|url| url.rsplit_once("/file/"))
+                .map(|(_, id)| id.to_string())
+                .or_else(|| progress_file_id(task.progress.trim()))
+        })
+    }
+
+    // downloads the file created by the task
+    pub async fn from_tasks<C: ApiClientExt>(
+        dnac: &C,
+        tasks: &[Task],
+    ) -> Result<Vec<u8>, FileError> {
+        let file_id = File::file_id(tasks).ok_or(FileError::NoFile)?;
+        File::download(dnac, &file_id).await
+    }
+}
+
+fn progress_file_id(progress: &str) -> Option<String> {
+    if uuid::Uuid::parse_str(progress).is_ok() {
+        return Some(progress.to_string());
+    }
+
+    #[derive(Deserialize)]
+    struct Progress {
+        #[serde(rename = "fileId")]
+        file_id: String,
+    }
+
+    serde_json::from_str::<Progress>(progress)
+        .ok()
+        .map(|p| p.file_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_id() {
+        let tasks = r#"
+          [{"id":"1","progress":"Export started","serviceType":"Inventory service","isError":false,"instanceTenantId":"t","startTime":1,"version":1},
+           {"id":"2","progress":"Export done","additionalStatusURL":"/file/6f3d4b5e-9d2b-5b0f-3a3c-2f103f0ac5d4","serviceType":"Inventory service","isError":false,"instanceTenantId":"t","startTime":1,"version":1}]
+          "#;
+        let tasks: Vec<Task> = serde_json::from_str(tasks).unwrap();
+        assert_eq!(
+            File::file_id(&tasks).as_deref(),
+            Some("6f3d4b5e-9d2b-5b0f-3a3c-2f103f0ac5d4")
+        );
+        assert_eq!(File::file_id(&tasks[..1]), None);
+    }
+}
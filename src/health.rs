@@ -0,0 +1,562 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::{event, Level};
+
+use crate::{
+    dnac::{ApiClient, Pagination, PaginationError, ResponseType},
+    ids::{DeviceId, SiteId},
+    query::Query,
+    sites::SiteType,
+};
+
+// DeviceHealth is the assurance view of a device, the health scores range
+// from 1 to 10, missing scores mean assurance has no data for the device
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceHealth {
+    #[serde(rename = "uuid")]
+    pub id: DeviceId,
+    pub name: Option<String>,
+    pub model: Option<String>,
+    pub os_version: Option<String>,
+    pub ip_address: Option<String>,
+    pub mac_address: Option<String>,
+    pub device_family: Option<String>,
+    pub device_type: Option<String>,
+    pub location: Option<String>,
+    pub reachability_health: Option<String>,
+    pub issue_count: Option<u32>,
+    pub overall_health: Option<f64>,
+    pub cpu_health: Option<f64>,
+    pub cpu_utilization: Option<f64>,
+    pub memory_utilization_health: Option<f64>,
+    pub memory_utilization: Option<f64>,
+    #[serde(rename = "interfaceLinkErrHealth")]
+    pub interface_link_error_health: Option<f64>,
+    #[serde(rename = "interDeviceLinkAvailHealth")]
+    pub inter_device_link_availability_health: Option<f64>,
+}
+
+// SiteHealth aggregates the health of the devices and clients of a site,
+// the scores are percentages of healthy devices or clients
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SiteHealth {
+    pub site_id: SiteId,
+    pub site_name: Option<String>,
+    pub site_type: Option<String>,
+    pub parent_site_id: Option<SiteId>,
+    pub parent_site_name: Option<String>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub network_health_average: Option<f64>,
+    pub network_health_access: Option<f64>,
+    pub network_health_distribution: Option<f64>,
+    pub network_health_core: Option<f64>,
+    pub network_health_router: Option<f64>,
+    pub network_health_wireless: Option<f64>,
+    pub network_health_others: Option<f64>,
+    pub healthy_network_device_percentage: Option<f64>,
+    pub healthy_clients_percentage: Option<f64>,
+    pub client_health_wired: Option<f64>,
+    pub client_health_wireless: Option<f64>,
+    #[serde(rename = "numberOfNetworkDevice")]
+    pub number_of_network_devices: Option<u32>,
+    pub number_of_clients: Option<u32>,
+    pub number_of_wired_clients: Option<u32>,
+    pub number_of_wireless_clients: Option<u32>,
+    pub network_number_of_issues: Option<u32>,
+    pub client_number_of_issues: Option<u32>,
+}
+
+// NetworkHealth is the overall health of the network devices, the scores
+// are percentages of healthy devices. The counters and the distribution are
+// returned next to the response, so the whole body is parsed.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkHealth {
+    // the health over time, one entry per 5 minute interval
+    #[serde(rename = "response", default)]
+    pub scores: Vec<HealthScore>,
+    pub measured_by: Option<String>,
+    pub latest_health_score: Option<f64>,
+    pub monitored_devices: Option<u32>,
+    pub monitored_healthy_devices: Option<u32>,
+    #[serde(rename = "monitoredUnHealthyDevices")]
+    pub monitored_unhealthy_devices: Option<u32>,
+    #[serde(rename = "unMonitoredDevices")]
+    pub unmonitored_devices: Option<u32>,
+    // DNAC returns the field as healthDistirubution
+    #[serde(rename = "healthDistirubution", alias = "healthDistribution", default)]
+    pub health_distribution: Vec<CategoryHealth>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthScore {
+    pub time: Option<String>,
+    #[serde(rename = "timeinMillis")]
+    pub time_in_millis: Option<u64>,
+    pub health_score: Option<f64>,
+    pub total_count: Option<u32>,
+    pub good_count: Option<u32>,
+    pub fair_count: Option<u32>,
+    pub bad_count: Option<u32>,
+    #[serde(rename = "unmonCount")]
+    pub unmonitored_count: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CategoryHealth {
+    pub category: HealthCategory,
+    pub health_score: Option<f64>,
+    pub total_count: Option<u32>,
+    pub good_count: Option<u32>,
+    pub fair_count: Option<u32>,
+    pub bad_count: Option<u32>,
+    #[serde(rename = "unmonCount")]
+    pub unmonitored_count: Option<u32>,
+    pub good_percentage: Option<f64>,
+    pub fair_percentage: Option<f64>,
+    pub bad_percentage: Option<f64>,
+    #[serde(rename = "unmonPercentage")]
+    pub unmonitored_percentage: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+pub enum HealthCategory {
+    Access,
+    Distribution,
+    Core,
+    Router,
+    #[serde(rename = "WLC")]
+    Wlc,
+    #[serde(rename = "AP")]
+    Ap,
+    Wireless,
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HealthLevel {
+    #[serde(rename = "POOR")]
+    Poor,
+    #[serde(rename = "FAIR")]
+    Fair,
+    #[serde(rename = "GOOD")]
+    Good,
+    #[serde(rename = "NODATA")]
+    NoData,
+}
+
+impl fmt::Display for HealthLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self {
+            Self::Poor => write!(f, "POOR"),
+            Self::Fair => write!(f, "FAIR"),
+            Self::Good => write!(f, "GOOD"),
+            Self::NoData => write!(f, "NODATA"),
+        }
+    }
+}
+
+impl fmt::Display for HealthDeviceFamily {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self {
+            Self::Router => write!(f, "ROUTER"),
+            Self::SwitchAndHub => write!(f, "SWITCH_AND_HUB"),
+            Self::WirelessController => write!(f, "WIRELESS_CONTROLLER"),
+            Self::UnifiedAp => write!(f, "UNIFIED_AP"),
+            Self::WirelessClient => write!(f, "WIRELESS_CLIENT"),
+            Self::WiredClient => write!(f, "WIRED_CLIENT"),
+        }
+    }
+}
+
+impl HealthLevel {
+    // the ranges used by the assurance dashboards
+    pub fn from_score(score: Option<f64>) -> Self {
+        match score {
+            Some(score) if score >= 8.0 => Self::Good,
+            Some(score) if score >= 4.0 => Self::Fair,
+            Some(score) if score >= 1.0 => Self::Poor,
+            _ => Self::NoData,
+        }
+    }
+}
+
+// DeviceHealthFilter limits the devices of the device-health endpoint, the
+// times are epoch milliseconds and default to the last available data
+#[derive(Debug, Clone, Default)]
+pub struct DeviceHealthFilter {
+    // e.g. AP, ACCESS, CORE, DISTRIBUTION, ROUTER or WLC
+    pub device_role: Option<String>,
+    pub site_id: Option<SiteId>,
+    pub health: Option<HealthLevel>,
+    pub start_time: Option<u64>,
+    pub end_time: Option<u64>,
+}
+
+// HealthScoreSettings is the definition of a KPI of the health score, the
+// KPI counts towards the overall health if included and is poor once its
+// threshold is crossed
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthScoreSettings {
+    pub id: String,
+    // the KPI, e.g. linkErrorThreshold or cpuUtilizationThreshold
+    pub name: String,
+    pub display_name: Option<String>,
+    pub device_family: HealthDeviceFamily,
+    pub description: Option<String>,
+    pub include_for_overall_health: bool,
+    // DEFAULT until the definition was updated, USER_DEFINED afterwards
+    pub definition_status: Option<String>,
+    pub threshold_value: Option<f64>,
+    pub synchronize_to_issue_threshold: Option<bool>,
+    pub last_modified: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum HealthDeviceFamily {
+    Router,
+    SwitchAndHub,
+    WirelessController,
+    UnifiedAp,
+    WirelessClient,
+    WiredClient,
+}
+
+// HealthScoreUpdate changes the KPI, fields which are None are kept
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthScoreUpdate {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include_for_overall_health: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub threshold_value: Option<f64>,
+    // updates the threshold of the matching issue as well
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub synchronize_to_issue_threshold: Option<bool>,
+}
+
+#[derive(Debug, Error)]
+pub enum HealthError {
+    #[error("General Health Error")]
+    GeneralError,
+    #[error(transparent)]
+    PaginationOutOfRange(#[from] PaginationError),
+    #[error("KPI {0} not found")]
+    KpiNotFound(String),
+}
+
+impl DeviceHealth {
+    pub async fn get<C: ApiClient>(
+        dnac: &C,
+        filter: Option<&DeviceHealthFilter>,
+        pagination: Option<Pagination>,
+    ) -> Result<Vec<DeviceHealth>, HealthError> {
+        let path = "/dna/intent/api/v1/device-health";
+        let query = filter.map(DeviceHealthFilter::to_query).unwrap_or_default();
+
+        let health_data = dnac
+            .get::<DeviceHealth>(path, Some(query.as_slice()), pagination)
+            .await;
+
+        match health_data {
+            Ok(health_data) => match health_data.response {
+                ResponseType::Array(data) => Ok(data),
+                ResponseType::Item(data) => Ok(vec![data]),
+            },
+            Err(e) => match e.downcast::<PaginationError>() {
+                Ok(pagination_error) => Err(pagination_error.into()),
+                Err(e) => {
+                    event!(Level::ERROR, "{e}");
+                    Err(HealthError::GeneralError)
+                }
+            },
+        }
+    }
+
+    pub fn level(&self) -> HealthLevel {
+        HealthLevel::from_score(self.overall_health)
+    }
+}
+
+impl SiteHealth {
+    // timestamp is in epoch milliseconds and defaults to the latest data,
+    // site-health only covers areas and buildings
+    pub async fn get<C: ApiClient>(
+        dnac: &C,
+        timestamp: Option<u64>,
+        site_type: Option<SiteType>,
+        pagination: Option<Pagination>,
+    ) -> Result<Vec<SiteHealth>, HealthError> {
+        let path = "/dna/intent/api/v1/site-health";
+        let query = Query::new()
+            .eq_opt("timestamp", timestamp)
+            .eq_opt("siteType", site_type.map(|t| t.to_string().to_uppercase()));
+
+        let health_data = dnac
+            .get::<SiteHealth>(path, Some(query.as_slice()), pagination)
+            .await;
+
+        match health_data {
+            Ok(health_data) => match health_data.response {
+                ResponseType::Array(data) => Ok(data),
+                ResponseType::Item(data) => Ok(vec![data]),
+            },
+            Err(e) => match e.downcast::<PaginationError>() {
+                Ok(pagination_error) => Err(pagination_error.into()),
+                Err(e) => {
+                    event!(Level::ERROR, "{e}");
+                    Err(HealthError::GeneralError)
+                }
+            },
+        }
+    }
+}
+
+impl NetworkHealth {
+    // timestamp is in epoch milliseconds and defaults to the latest data
+    pub async fn get<C: ApiClient>(
+        dnac: &C,
+        timestamp: Option<u64>,
+    ) -> Result<NetworkHealth, HealthError> {
+        let path = "/dna/intent/api/v1/network-health";
+        let query = Query::new().eq_opt("timestamp", timestamp);
+
+        let health_data = dnac.get_raw(path, Some(query.as_slice()), None).await;
+
+        match health_data {
+            Ok(health_data) => serde_json::from_value(health_data).map_err(|e| {
+                event!(Level::ERROR, "{e}");
+                HealthError::GeneralError
+            }),
+            Err(e) => {
+                event!(Level::ERROR, "{e}");
+                Err(HealthError::GeneralError)
+            }
+        }
+    }
+
+    pub fn category(&self, category: HealthCategory) -> Option<&CategoryHealth> {
+        self.health_distribution
+            .iter()
+            .find(|c| c.category == category)
+    }
+}
+
+impl HealthScoreSettings {
+    // all KPIs of the device family
+    pub async fn list<C: ApiClient>(
+        dnac: &C,
+        device_family: HealthDeviceFamily,
+    ) -> Result<Vec<HealthScoreSettings>, HealthError> {
+        let path = "/dna/intent/api/v1/healthScoreDefinitions";
+        let query = Query::new().eq("deviceType", device_family);
+
+        let settings_data = dnac
+            .get::<HealthScoreSettings>(path, Some(query.as_slice()), None)
+            .await;
+
+        match settings_data {
+            Ok(settings_data) => match settings_data.response {
+                ResponseType::Array(data) => Ok(data),
+                ResponseType::Item(data) => Ok(vec![data]),
+            },
+            Err(e) => {
+                event!(Level::ERROR, "{e}");
+                Err(HealthError::GeneralError)
+            }
+        }
+    }
+
+    pub async fn get<C: ApiClient>(
+        dnac: &C,
+        device_family: HealthDeviceFamily,
+        kpi: &str,
+    ) -> Result<HealthScoreSettings, HealthError> {
+        Self::list(dnac, device_family)
+            .await?
+            .into_iter()
+            .find(|s| s.name == kpi)
+            .ok_or_else(|| HealthError::KpiNotFound(kpi.to_string()))
+    }
+
+    // the definitions are only addressable by id, the KPI is looked up first
+    pub async fn update<C: ApiClient>(
+        dnac: &C,
+        device_family: HealthDeviceFamily,
+        kpi: &str,
+        update: &HealthScoreUpdate,
+    ) -> anyhow::Result<()> {
+        let settings = Self::get(dnac, device_family, kpi).await?;
+        let path = format!("/dna/intent/api/v1/healthScoreDefinitions/{}", settings.id);
+        dnac.put(path.as_str(), update, false).await
+    }
+}
+
+impl DeviceHealthFilter {
+    pub fn to_query(&self) -> Query<'static> {
+        Query::new()
+            .eq_opt("deviceRole", self.device_role.as_ref())
+            .eq_opt("siteId", self.site_id)
+            .eq_opt("health", self.health)
+            .eq_opt("startTime", self.start_time)
+            .eq_opt("endTime", self.end_time)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::MockDnac;
+
+    #[tokio::test]
+    async fn test_get_device_health() {
+        let mock = MockDnac::start().await;
+        mock.mock_response(
+            "GET",
+            "/dna/intent/api/v1/device-health",
+            200,
+            r#"
+              {"version":"1.0","totalCount":2,"response":[{"name":"zrh-acc-sw01","model":"C9300-48P","osVersion":"17.9.4a","ipAddress":"10.10.1.11","overallHealth":10,"issueCount":0,"location":"Global/CH/ZRH/HQ","deviceFamily":"SWITCHES_AND_HUBS","deviceType":"Cisco Catalyst 9300 Switch","macAddress":"70:1f:53:aa:10:80","interfaceLinkErrHealth":10,"cpuUtilization":4.5,"cpuHealth":10,"memoryUtilizationHealth":9,"memoryUtilization":48,"interDeviceLinkAvailHealth":100,"reachabilityHealth":"REACHABLE","uuid":"3f0ac5d4-6f3d-4b5e-9d2b-5b0f3a3c2f10"},{"name":"zrh-acc-sw02","overallHealth":-1,"reachabilityHealth":"UNREACHABLE","uuid":"8c1e2b7a-2d44-4f0e-a1e3-0d6c9b3e5a21"}]}
+              "#,
+        )
+        .await;
+
+        let filter = DeviceHealthFilter {
+            device_role: Some("ACCESS".to_string()),
+            health: Some(HealthLevel::Good),
+            ..Default::default()
+        };
+        let health = DeviceHealth::get(&mock.dnac, Some(&filter), None)
+            .await
+            .unwrap();
+        assert_eq!(health.len(), 2);
+        assert_eq!(health[0].cpu_utilization, Some(4.5));
+        assert_eq!(health[0].level(), HealthLevel::Good);
+        assert_eq!(health[1].level(), HealthLevel::NoData);
+    }
+
+    #[tokio::test]
+    async fn test_get_site_health() {
+        let mock = MockDnac::start().await;
+        mock.mock_response(
+            "GET",
+            "/dna/intent/api/v1/site-health",
+            200,
+            r#"
+              {"response":[{"siteName":"HQ","siteId":"a4f3a3c2-f103-4f0a-8c5d-46f3d4b5e9d2","parentSiteId":"1f0ac5d4-6f3d-4b5e-9d2b-5b0f3a3c2f10","parentSiteName":"ZRH","siteType":"building","latitude":47.37,"longitude":8.54,"healthyNetworkDevicePercentage":92,"healthyClientsPercentage":88,"clientHealthWired":95,"clientHealthWireless":85,"numberOfClients":412,"numberOfNetworkDevice":25,"networkHealthAverage":92,"networkHealthAccess":90,"networkHealthCore":100,"networkHealthDistribution":null,"networkHealthRouter":100,"networkHealthWireless":91,"networkHealthOthers":null,"numberOfWiredClients":120,"numberOfWirelessClients":292,"networkNumberOfIssues":3,"clientNumberOfIssues":null}]}
+              "#,
+        )
+        .await;
+
+        let health = SiteHealth::get(&mock.dnac, None, Some(SiteType::Building), None)
+            .await
+            .unwrap();
+        assert_eq!(health.len(), 1);
+        assert_eq!(health[0].number_of_network_devices, Some(25));
+        assert_eq!(health[0].network_health_distribution, None);
+        assert_eq!(health[0].network_number_of_issues, Some(3));
+        assert_eq!(health[0].latitude, Some(47.37));
+    }
+
+    #[tokio::test]
+    async fn test_get_network_health() {
+        let mock = MockDnac::start().await;
+        mock.mock_response(
+            "GET",
+            "/dna/intent/api/v1/network-health",
+            200,
+            r#"
+              {"version":"1.0","response":[{"time":"2024-11-28T16:30:00.000+0000","healthScore":92,"totalCount":25,"goodCount":23,"unmonCount":0,"fairCount":1,"badCount":1,"entity":null,"timeinMillis":1732811400000}],
+               "measuredBy":"global","latestMeasuredByEntity":null,"latestHealthScore":92,"monitoredDevices":25,"monitoredHealthyDevices":23,"monitoredUnHealthyDevices":2,"unMonitoredDevices":0,
+               "healthDistirubution":[{"category":"Access","totalCount":20,"healthScore":90,"goodPercentage":90,"badPercentage":5,"fairPercentage":5,"unmonPercentage":0,"goodCount":18,"badCount":1,"fairCount":1,"unmonCount":0,"kpiMetrics":[]},
+                                      {"category":"Core","totalCount":2,"healthScore":100,"goodPercentage":100,"badPercentage":0,"fairPercentage":0,"unmonPercentage":0,"goodCount":2,"badCount":0,"fairCount":0,"unmonCount":0},
+                                      {"category":"WLC","totalCount":2,"healthScore":100,"goodCount":2},
+                                      {"category":"UnifiedAP","totalCount":1,"healthScore":0}]}
+              "#,
+        )
+        .await;
+
+        let health = NetworkHealth::get(&mock.dnac, Some(1732811400000))
+            .await
+            .unwrap();
+        assert_eq!(health.latest_health_score, Some(92.0));
+        assert_eq!(health.monitored_unhealthy_devices, Some(2));
+        assert_eq!(health.scores[0].time_in_millis, Some(1732811400000));
+        assert_eq!(
+            health.category(HealthCategory::Access).unwrap().bad_count,
+            Some(1)
+        );
+        assert!(health.category(HealthCategory::Wlc).is_some());
+        assert_eq!(
+            health.health_distribution[3].category,
+            HealthCategory::Other
+        );
+    }
+
+    #[tokio::test]
+    async fn test_health_score_settings() {
+        let mock = MockDnac::start().await;
+        mock.mock_response(
+            "GET",
+            "/dna/intent/api/v1/healthScoreDefinitions",
+            200,
+            r#"
+              {"response":[
+                {"id":"37f3d6c2-1b4e-4a0f-9c8d-2e5b7a1f3c90","name":"linkErrorThreshold","displayName":"Link Error","deviceFamily":"SWITCH_AND_HUB","description":"Interface link errors","includeForOverallHealth":true,"definitionStatus":"DEFAULT","thresholdValue":1.0,"synchronizeToIssueThreshold":true,"lastModified":""},
+                {"id":"5a1e9c3d-7b2f-4e8a-b0c6-d4f2a8e6b1c7","name":"cpuUtilizationThreshold","displayName":"CPU Utilization","deviceFamily":"SWITCH_AND_HUB","includeForOverallHealth":true,"definitionStatus":"DEFAULT","thresholdValue":95.0,"synchronizeToIssueThreshold":false}],
+               "version":"1.0"}
+              "#,
+        )
+        .await;
+        mock.mock_response(
+            "PUT",
+            "/dna/intent/api/v1/healthScoreDefinitions/5a1e9c3d-7b2f-4e8a-b0c6-d4f2a8e6b1c7",
+            200,
+            r#"{"response":{},"version":"1.0"}"#,
+        )
+        .await;
+
+        let settings = HealthScoreSettings::get(
+            &mock.dnac,
+            HealthDeviceFamily::SwitchAndHub,
+            "linkErrorThreshold",
+        )
+        .await
+        .unwrap();
+        assert_eq!(settings.threshold_value, Some(1.0));
+        assert!(matches!(
+            HealthScoreSettings::get(&mock.dnac, HealthDeviceFamily::SwitchAndHub, "unknown").await,
+            Err(HealthError::KpiNotFound(_))
+        ));
+
+        let update = HealthScoreUpdate {
+            threshold_value: Some(90.0),
+            ..Default::default()
+        };
+        HealthScoreSettings::update(
+            &mock.dnac,
+            HealthDeviceFamily::SwitchAndHub,
+            "cpuUtilizationThreshold",
+            &update,
+        )
+        .await
+        .unwrap();
+
+        let requests = mock.server.received_requests().await.unwrap();
+        let request = requests.iter().find(|r| r.method == "GET").unwrap();
+        assert_eq!(request.url.query(), Some("deviceType=SWITCH_AND_HUB"));
+        let request = requests.iter().find(|r| r.method == "PUT").unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&request.body).unwrap();
+        assert_eq!(body, serde_json::json!({"thresholdValue": 90.0}));
+    }
+}
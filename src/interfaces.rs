@@ -0,0 +1,447 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use thiserror::Error;
+use tracing::{event, Level};
+use uuid::Uuid;
+
+use crate::{
+    dnac::{ApiClient, ApiClientExt, Pagination, PaginationError, ResponseType},
+    ids::DeviceId,
+    query::Query,
+};
+
+// attributes requested from the trend analytics of an interface
+const TREND_ATTRIBUTES: [&str; 7] = [
+    "rxError",
+    "txError",
+    "rxDiscards",
+    "txDiscards",
+    "rxCrcError",
+    "operStatus",
+    "adminStatus",
+];
+
+// Interface is the inventory record of an interface
+#[derive(Debug, Deserialize)]
+pub struct Interface {
+    pub id: Uuid,
+    #[serde(rename = "deviceId")]
+    pub device_id: DeviceId,
+    #[serde(rename = "portName")]
+    pub port_name: Option<String>,
+    pub description: Option<String>,
+    // operational status, e.g. up or down
+    pub status: Option<String>,
+    #[serde(rename = "adminStatus")]
+    pub admin_status: Option<String>,
+    #[serde(rename = "vlanId")]
+    pub vlan_id: Option<String>,
+    #[serde(rename = "nativeVlanId")]
+    pub native_vlan_id: Option<String>,
+    #[serde(rename = "voiceVlan")]
+    pub voice_vlan: Option<String>,
+    pub speed: Option<String>,
+    pub duplex: Option<String>,
+    #[serde(rename = "portType")]
+    pub port_type: Option<String>,
+    #[serde(rename = "portMode")]
+    pub port_mode: Option<String>,
+    #[serde(rename = "mediaType")]
+    pub media_type: Option<String>,
+    #[serde(rename = "interfaceType")]
+    pub interface_type: Option<String>,
+    #[serde(rename = "macAddress")]
+    pub mac_address: Option<String>,
+    #[serde(rename = "ipv4Address")]
+    pub ipv4_address: Option<String>,
+    #[serde(rename = "ipv4Mask")]
+    pub ipv4_mask: Option<String>,
+    #[serde(rename = "ifIndex")]
+    pub if_index: Option<String>,
+    #[serde(rename = "lastUpdated")]
+    pub last_updated: Option<String>,
+}
+
+// UpdateInterface changes the settings of an interface, only the fields
+// which are set get sent
+#[derive(Debug, Default, Serialize)]
+pub struct UpdateInterface {
+    #[serde(skip)]
+    pub deployment_mode: DeploymentMode,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(rename = "adminStatus", skip_serializing_if = "Option::is_none")]
+    pub admin_status: Option<AdminStatus>,
+    #[serde(rename = "vlanId", skip_serializing_if = "Option::is_none")]
+    pub vlan_id: Option<u16>,
+    #[serde(rename = "voiceVlanId", skip_serializing_if = "Option::is_none")]
+    pub voice_vlan_id: Option<u16>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum AdminStatus {
+    #[serde(rename = "UP")]
+    Up,
+    #[serde(rename = "DOWN")]
+    Down,
+}
+
+// Preview only validates the change, Deploy pushes it to the device
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum DeploymentMode {
+    #[default]
+    Deploy,
+    Preview,
+}
+
+impl fmt::Display for DeploymentMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DeploymentMode::Deploy => write!(f, "Deploy"),
+            DeploymentMode::Preview => write!(f, "Preview"),
+        }
+    }
+}
+
+pub struct InterfaceStats;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum TrendInterval {
+    #[serde(rename = "1HR")]
+    OneHour,
+    #[serde(rename = "3HR")]
+    ThreeHours,
+    #[serde(rename = "24HR")]
+    OneDay,
+}
+
+// InterfaceTimeSeries holds the samples of a single interface, ordered by time
+#[derive(Debug, Clone, Default)]
+pub struct InterfaceTimeSeries {
+    pub interface_id: Uuid,
+    pub samples: Vec<InterfaceSample>,
+}
+
+// counters are the errors within the interval of the sample, attributes the
+// API didn't report for the interface are None
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct InterfaceSample {
+    pub timestamp: i64,
+    pub rx_errors: Option<u64>,
+    pub tx_errors: Option<u64>,
+    pub rx_discards: Option<u64>,
+    pub tx_discards: Option<u64>,
+    pub crc_errors: Option<u64>,
+    pub oper_status: Option<String>,
+    pub admin_status: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct TrendRequest {
+    #[serde(rename = "startTime")]
+    start_time: i64,
+    #[serde(rename = "endTime")]
+    end_time: i64,
+    #[serde(rename = "trendInterval")]
+    trend_interval: TrendInterval,
+    attributes: Vec<&'static str>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TrendEntry {
+    timestamp: i64,
+    #[serde(default)]
+    attributes: Vec<TrendAttribute>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TrendAttribute {
+    name: String,
+    value: Value,
+}
+
+#[derive(Debug, Error)]
+pub enum InterfaceError {
+    #[error("General Interface Error")]
+    GeneralError,
+    #[error(transparent)]
+    PaginationOutOfRange(#[from] PaginationError),
+}
+
+impl Interface {
+    pub async fn get_list<C: ApiClient>(
+        dnac: &C,
+        pagination: Option<Pagination>,
+    ) -> Result<Vec<Interface>, InterfaceError> {
+        let path = "/dna/intent/api/v1/interface";
+        get_interfaces(dnac, path, None, pagination).await
+    }
+
+    // pages through the interfaces of all devices
+    pub async fn get_all<C: ApiClient>(dnac: &C) -> Result<Vec<Interface>, InterfaceError> {
+        let mut offset = 1;
+        let limit = 500;
+        let mut interfaces: Vec<Interface> = vec![];
+
+        loop {
+            event!(
+                Level::DEBUG,
+                "Fetching Interfaces with offset: {offset} and limit: {limit}"
+            );
+            let pagination = Pagination::builder()
+                .with_offset(offset)
+                .with_limit(limit)
+                .build();
+
+            let current_interfaces = Interface::get_list(dnac, Some(pagination)).await?;
+            let done = (current_interfaces.len() as u64) < limit;
+            interfaces.extend(current_interfaces);
+            if done {
+                break;
+            }
+
+            offset += limit;
+        }
+
+        Ok(interfaces)
+    }
+
+    pub async fn by_device<C: ApiClient>(
+        dnac: &C,
+        device_id: DeviceId,
+    ) -> Result<Vec<Interface>, InterfaceError> {
+        let path = format!("/dna/intent/api/v1/interface/network-device/{device_id}");
+        get_interfaces(dnac, path.as_str(), None, None).await
+    }
+
+    pub async fn by_ip<C: ApiClient>(
+        dnac: &C,
+        ip_address: &str,
+    ) -> Result<Vec<Interface>, InterfaceError> {
+        let path = format!("/dna/intent/api/v1/interface/ip-address/{ip_address}");
+        get_interfaces(dnac, path.as_str(), None, None).await
+    }
+
+    // waits until the change was deployed or previewed
+    pub async fn update<C: ApiClient>(
+        dnac: &C,
+        interface_id: Uuid,
+        update: UpdateInterface,
+    ) -> anyhow::Result<()> {
+        let path = format!(
+            "/dna/intent/api/v1/interface/{interface_id}?deploymentMode={}",
+            update.deployment_mode
+        );
+        dnac.put(path.as_str(), update, true).await
+    }
+
+    // name is the full interface name, e.g. GigabitEthernet1/0/1
+    pub async fn by_name<C: ApiClient>(
+        dnac: &C,
+        device_id: DeviceId,
+        name: &str,
+    ) -> Result<Option<Interface>, InterfaceError> {
+        let path =
+            format!("/dna/intent/api/v1/interface/network-device/{device_id}/interface-name");
+        let query = Query::new().eq("name", name);
+
+        let interfaces = get_interfaces(dnac, path.as_str(), Some(&query), None).await?;
+        Ok(interfaces.into_iter().next())
+    }
+}
+
+async fn get_interfaces<C: ApiClient>(
+    dnac: &C,
+    path: &str,
+    query: Option<&Query<'_>>,
+    pagination: Option<Pagination>,
+) -> Result<Vec<Interface>, InterfaceError> {
+    let interface_data = dnac
+        .get::<Interface>(path, query.map(Query::as_slice), pagination)
+        .await;
+
+    match interface_data {
+        Ok(interface_data) => match interface_data.response {
+            ResponseType::Array(data) => Ok(data),
+            ResponseType::Item(data) => Ok(vec![data]),
+        },
+        Err(e) => match e.downcast::<PaginationError>() {
+            Ok(pagination_error) => Err(pagination_error.into()),
+            Err(e) => {
+                event!(Level::ERROR, "{e}");
+                Err(InterfaceError::GeneralError)
+            }
+        },
+    }
+}
+
+impl InterfaceStats {
+    // start_time and end_time are epoch milliseconds
+    pub async fn get_trend<C: ApiClientExt>(
+        dnac: &C,
+        interface_id: Uuid,
+        start_time: i64,
+        end_time: i64,
+        interval: TrendInterval,
+    ) -> Result<InterfaceTimeSeries, InterfaceError> {
+        let path = format!("/dna/data/api/v1/interfaces/{interface_id}/trendAnalytics");
+        let request = TrendRequest {
+            start_time,
+            end_time,
+            trend_interval: interval,
+            attributes: TREND_ATTRIBUTES.to_vec(),
+        };
+
+        let trend_data = dnac
+            .post_query::<_, TrendEntry>(path.as_str(), request)
+            .await;
+
+        let entries = match trend_data {
+            Ok(trend_data) => match trend_data.response {
+                ResponseType::Array(data) => data,
+                ResponseType::Item(data) => vec![data],
+            },
+            Err(e) => {
+                event!(Level::ERROR, "{e}");
+                return Err(InterfaceError::GeneralError);
+            }
+        };
+
+        let mut samples: Vec<InterfaceSample> = entries.into_iter().map(Into::into).collect();
+        samples.sort_by_key(|s| s.timestamp);
+
+        Ok(InterfaceTimeSeries {
+            interface_id,
+            samples,
+        })
+    }
+}
+
+impl InterfaceTimeSeries {
+    pub fn total_errors(&self) -> u64 {
+        self.samples
+            .iter()
+            .map(|s| s.rx_errors.unwrap_or_default() + s.tx_errors.unwrap_or_default())
+            .sum()
+    }
+
+    pub fn total_crc_errors(&self) -> u64 {
+        self.samples.iter().filter_map(|s| s.crc_errors).sum()
+    }
+
+    // number of changes of the operational status between samples
+    pub fn flaps(&self) -> usize {
+        let statuses: Vec<&str> = self
+            .samples
+            .iter()
+            .filter_map(|s| s.oper_status.as_deref())
+            .collect();
+
+        statuses.windows(2).filter(|w| w[0] != w[1]).count()
+    }
+}
+
+impl From<TrendEntry> for InterfaceSample {
+    fn from(entry: TrendEntry) -> Self {
+        let mut sample = InterfaceSample {
+            timestamp: entry.timestamp,
+            ..Default::default()
+        };
+
+        for attribute in entry.attributes {
+            let counter = attribute.value.as_f64().map(|v| v as u64);
+            let status = attribute.value.as_str().map(String::from);
+            match attribute.name.as_str() {
+                "rxError" => sample.rx_errors = counter,
+                "txError" => sample.tx_errors = counter,
+                "rxDiscards" => sample.rx_discards = counter,
+                "txDiscards" => sample.tx_discards = counter,
+                "rxCrcError" => sample.crc_errors = counter,
+                "operStatus" => sample.oper_status = status,
+                "adminStatus" => sample.admin_status = status,
+                _ => {}
+            }
+        }
+
+        sample
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::MockDnac;
+
+    #[tokio::test]
+    async fn test_by_name() {
+        let mock = MockDnac::start().await;
+        let device_id: DeviceId = "3f0ac5d4-6f3d-4b5e-9d2b-5b0f3a3c2f10".parse().unwrap();
+        mock.mock_response(
+            "GET",
+            &format!("/dna/intent/api/v1/interface/network-device/{device_id}/interface-name"),
+            200,
+            r#"
+              {"response":{"id":"5b0f3a3c-2f10-4b5e-9d2b-3f0ac5d46f3d","deviceId":"3f0ac5d4-6f3d-4b5e-9d2b-5b0f3a3c2f10","portName":"GigabitEthernet1/0/1","status":"up","adminStatus":"UP","vlanId":"10","speed":"1000000","duplex":"FullDuplex","portType":"Ethernet Port","portMode":"access","mediaType":"10/100/1000BaseTX","interfaceType":"Physical"}}
+              "#,
+        )
+        .await;
+
+        let interface = Interface::by_name(&mock.dnac, device_id, "GigabitEthernet1/0/1")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(interface.device_id, device_id);
+        assert_eq!(interface.duplex.as_deref(), Some("FullDuplex"));
+    }
+
+    #[test]
+    fn test_update_interface() {
+        let update = UpdateInterface {
+            deployment_mode: DeploymentMode::Preview,
+            admin_status: Some(AdminStatus::Down),
+            vlan_id: Some(20),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            serde_json::to_string(&update).unwrap(),
+            r#"{"adminStatus":"DOWN","vlanId":20}"#
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_trend() {
+        let mock = MockDnac::start().await;
+        let interface_id = Uuid::parse_str("5b0f3a3c-2f10-4b5e-9d2b-3f0ac5d46f3d").unwrap();
+        mock.mock_response(
+            "POST",
+            &format!("/dna/data/api/v1/interfaces/{interface_id}/trendAnalytics"),
+            200,
+            r#"
+              {"response":[
+                {"timestamp":1732813200000,"attributes":[{"name":"rxError","value":4},{"name":"txError","value":1},{"name":"operStatus","value":"down"}]},
+                {"timestamp":1732809600000,"attributes":[{"name":"rxError","value":0},{"name":"rxCrcError","value":2},{"name":"operStatus","value":"up"}]},
+                {"timestamp":1732816800000,"attributes":[{"name":"operStatus","value":"up"}]}
+              ]}
+              "#,
+        )
+        .await;
+
+        let series = InterfaceStats::get_trend(
+            &mock.dnac,
+            interface_id,
+            1732809600000,
+            1732816800000,
+            TrendInterval::OneHour,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(series.samples[0].timestamp, 1732809600000);
+        assert_eq!(series.total_errors(), 5);
+        assert_eq!(series.total_crc_errors(), 2);
+        assert_eq!(series.flaps(), 2);
+    }
+}
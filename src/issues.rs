@@ -0,0 +1,535 @@
+use std::{collections::HashMap, fmt};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use thiserror::Error;
+use tracing::{event, Level};
+
+use crate::{
+    dnac::{ApiClient, ApiClientExt, ResponseType},
+    file::File,
+    ids::{DeviceId, SiteId},
+    query::Query,
+};
+
+// Issue is an assurance issue of a device or client, the issue is raised
+// once and counts its occurrences until it's resolved
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Issue {
+    pub issue_id: String,
+    pub name: String,
+    pub priority: Option<IssuePriority>,
+    pub category: Option<IssueCategory>,
+    pub status: Option<IssueStatus>,
+    // empty if the issue isn't scoped to a site or device
+    pub site_id: Option<String>,
+    pub device_id: Option<String>,
+    pub device_role: Option<String>,
+    pub client_mac: Option<String>,
+    #[serde(rename = "aiDriven")]
+    pub ai_driven: Option<String>,
+    // DNAC misspells occurrence in both fields
+    #[serde(rename = "issue_occurence_count")]
+    pub occurrence_count: Option<u32>,
+    #[serde(rename = "last_occurence_time")]
+    pub last_occurrence_time: Option<u64>,
+}
+
+// P1 is the most severe priority
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize)]
+pub enum IssuePriority {
+    P1,
+    P2,
+    P3,
+    P4,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+pub enum IssueCategory {
+    Availability,
+    Connected,
+    Connectivity,
+    Device,
+    Onboarding,
+    Utilization,
+    Application,
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum IssueStatus {
+    Active,
+    Ignored,
+    Resolved,
+    #[serde(other)]
+    Unknown,
+}
+
+// IssueFilter limits the issues, the times are epoch milliseconds and
+// default to the last 24 hours. DNAC only allows one of site_id, device_id
+// and mac_address. The category isn't a query parameter and is filtered
+// after the issues are returned.
+#[derive(Debug, Clone, Default)]
+pub struct IssueFilter {
+    pub priority: Option<IssuePriority>,
+    pub category: Option<IssueCategory>,
+    pub status: Option<IssueStatus>,
+    pub site_id: Option<SiteId>,
+    pub device_id: Option<DeviceId>,
+    pub mac_address: Option<String>,
+    pub ai_driven: Option<bool>,
+    pub start_time: Option<u64>,
+    pub end_time: Option<u64>,
+}
+
+// IssueEnrichment are the details of an issue with the suggested actions to
+// resolve it and the hosts impacted by it
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IssueEnrichment {
+    pub issue_id: String,
+    pub issue_name: Option<String>,
+    pub issue_description: Option<String>,
+    pub issue_summary: Option<String>,
+    pub issue_source: Option<String>,
+    pub issue_category: Option<IssueCategory>,
+    pub issue_priority: Option<IssuePriority>,
+    pub issue_severity: Option<String>,
+    // the entity the issue is raised for, e.g. network_device or client
+    pub issue_entity: Option<String>,
+    pub issue_entity_value: Option<String>,
+    pub issue_timestamp: Option<u64>,
+    #[serde(default)]
+    pub suggested_actions: Vec<SuggestedAction>,
+    #[serde(default)]
+    pub impacted_hosts: Vec<ImpactedHost>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SuggestedAction {
+    pub message: String,
+    #[serde(default)]
+    pub steps: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImpactedHost {
+    pub host_name: Option<String>,
+    pub host_type: Option<String>,
+    pub host_os_type: Option<String>,
+    pub host_device_type: Option<String>,
+    pub host_vendor: Option<String>,
+    pub mac_address: Option<String>,
+    pub connected_interface: Option<String>,
+    pub failed_attempts: Option<u32>,
+    pub location: Option<HostLocation>,
+    pub timestamp: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HostLocation {
+    pub site_id: Option<String>,
+    pub site_type: Option<String>,
+    pub area: Option<String>,
+    pub building: Option<String>,
+    pub floor: Option<String>,
+    #[serde(default)]
+    pub aps_impacted: Vec<String>,
+}
+
+// the result of resolve and ignore, DNAC updates the issues one by one
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IssueUpdate {
+    #[serde(default)]
+    pub successful_issue_ids: Vec<String>,
+    #[serde(default)]
+    pub failure_issue_ids: Vec<String>,
+}
+
+// the output of a suggested action, every action runs one or more commands
+// on the device of the issue
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActionOutput {
+    pub action_info: Option<String>,
+    pub steps_count: Option<u32>,
+    pub steps_description: Option<String>,
+    pub entity_id: Option<String>,
+    pub hostname: Option<String>,
+    pub command: Option<String>,
+    // the output keyed by command
+    #[serde(default)]
+    pub command_output: HashMap<String, String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct IssueEnrichmentData {
+    issue_details: IssueDetails,
+}
+
+#[derive(Deserialize)]
+struct IssueDetails {
+    #[serde(default)]
+    issue: Vec<IssueEnrichment>,
+}
+
+#[derive(Serialize)]
+struct IssueEntity<'a> {
+    entity_type: &'a str,
+    entity_value: &'a str,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct IssueIds<'a> {
+    issue_ids: &'a [&'a str],
+}
+
+#[derive(Debug, Error)]
+pub enum IssueError {
+    #[error("General Issue Error")]
+    GeneralError,
+    #[error("Issue {0} not found")]
+    IssueNotFound(String),
+    #[error("Task failed")]
+    TaskFailed,
+}
+
+impl fmt::Display for IssuePriority {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self {
+            Self::P1 => write!(f, "P1"),
+            Self::P2 => write!(f, "P2"),
+            Self::P3 => write!(f, "P3"),
+            Self::P4 => write!(f, "P4"),
+        }
+    }
+}
+
+impl fmt::Display for IssueStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self {
+            Self::Active => write!(f, "ACTIVE"),
+            Self::Ignored => write!(f, "IGNORED"),
+            Self::Resolved => write!(f, "RESOLVED"),
+            Self::Unknown => write!(f, "UNKNOWN"),
+        }
+    }
+}
+
+impl Issue {
+    pub async fn list<C: ApiClient>(
+        dnac: &C,
+        filter: Option<&IssueFilter>,
+    ) -> Result<Vec<Issue>, IssueError> {
+        let path = "/dna/intent/api/v1/issues";
+        let query = filter.map(IssueFilter::to_query).unwrap_or_default();
+
+        let issue_data = dnac.get::<Issue>(path, Some(query.as_slice()), None).await;
+
+        match issue_data {
+            Ok(issue_data) => {
+                let issues = match issue_data.response {
+                    ResponseType::Array(data) => data,
+                    ResponseType::Item(data) => vec![data],
+                };
+                let category = filter.and_then(|f| f.category);
+                Ok(issues
+                    .into_iter()
+                    .filter(|i| category.is_none() || i.category == category)
+                    .collect())
+            }
+            Err(e) => {
+                event!(Level::ERROR, "{e}");
+                Err(IssueError::GeneralError)
+            }
+        }
+    }
+
+    // the issue is passed as entity_type and entity_value header
+    pub async fn enrichment<C: ApiClientExt>(
+        dnac: &C,
+        issue_id: &str,
+    ) -> Result<IssueEnrichment, IssueError> {
+        let path = "/dna/intent/api/v1/issue-enrichment-details";
+        let headers = [
+            ("entity_type", "issue_id".to_string()),
+            ("entity_value", issue_id.to_string()),
+        ];
+
+        let enrichment_data = match dnac.get_raw_with_headers(path, &headers).await {
+            Ok(enrichment_data) => serde_json::from_value::<IssueEnrichmentData>(enrichment_data)
+                .map_err(|e| {
+                event!(Level::ERROR, "{e}");
+                IssueError::GeneralError
+            })?,
+            Err(e) => {
+                event!(Level::ERROR, "{e}");
+                return Err(IssueError::GeneralError);
+            }
+        };
+
+        enrichment_data
+            .issue_details
+            .issue
+            .into_iter()
+            .next()
+            .ok_or_else(|| IssueError::IssueNotFound(issue_id.to_string()))
+    }
+
+    // runs the commands of the suggested actions on the device of the issue
+    // and downloads their outputs once the task finished
+    pub async fn execute_suggested_actions<C: ApiClientExt>(
+        dnac: &C,
+        issue_id: &str,
+    ) -> Result<Vec<ActionOutput>, IssueError> {
+        let path = "/dna/intent/api/v1/execute-suggested-actions-commands";
+        let request = IssueEntity {
+            entity_type: "issue_id",
+            entity_value: issue_id,
+        };
+
+        let task_info = dnac.post_task(path, request).await.map_err(|e| {
+            event!(Level::ERROR, "{e}");
+            IssueError::GeneralError
+        })?;
+        let tasks = dnac.poll_task(task_info).await.map_err(|e| {
+            event!(Level::ERROR, "{e}");
+            IssueError::TaskFailed
+        })?;
+
+        let file = File::from_tasks(dnac, &tasks).await.map_err(|e| {
+            event!(Level::ERROR, "{e}");
+            IssueError::GeneralError
+        })?;
+
+        serde_json::from_slice(&file).map_err(|e| {
+            event!(Level::ERROR, "{e}");
+            IssueError::GeneralError
+        })
+    }
+
+    pub async fn resolve<C: ApiClientExt>(
+        dnac: &C,
+        issue_ids: &[&str],
+    ) -> anyhow::Result<IssueUpdate> {
+        update_issues(dnac, "resolve", issue_ids).await
+    }
+
+    // ignored issues are hidden until they occur again
+    pub async fn ignore<C: ApiClientExt>(
+        dnac: &C,
+        issue_ids: &[&str],
+    ) -> anyhow::Result<IssueUpdate> {
+        update_issues(dnac, "ignore", issue_ids).await
+    }
+
+    pub fn is_ai_driven(&self) -> bool {
+        self.ai_driven.as_deref() == Some("YES")
+    }
+}
+
+impl IssueFilter {
+    pub fn to_query(&self) -> Query<'static> {
+        Query::new()
+            .eq_opt("priority", self.priority)
+            .eq_opt("issueStatus", self.status)
+            .eq_opt("siteId", self.site_id)
+            .eq_opt("deviceId", self.device_id)
+            .eq_opt("macAddress", self.mac_address.as_ref())
+            .eq_opt(
+                "aiDriven",
+                self.ai_driven.map(|ai| if ai { "YES" } else { "NO" }),
+            )
+            .eq_opt("startTime", self.start_time)
+            .eq_opt("endTime", self.end_time)
+    }
+}
+
+async fn update_issues<C: ApiClientExt>(
+    dnac: &C,
+    action: &str,
+    issue_ids: &[&str],
+) -> anyhow::Result<IssueUpdate> {
+    let path = format!("/dna/data/api/v1/assuranceIssues/{action}");
+    let request = IssueIds { issue_ids };
+
+    match dnac
+        .post_raw(path.as_str(), &serde_json::to_value(request)?)
+        .await?
+    {
+        Value::Null => Ok(IssueUpdate::default()),
+        response => Ok(serde_json::from_value(response["response"].clone())?),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{fixtures, MockDnac};
+    use wiremock::{
+        matchers::{header, method, path},
+        Mock, ResponseTemplate,
+    };
+
+    #[tokio::test]
+    async fn test_list() {
+        let mock = MockDnac::start().await;
+        mock.mock_response(
+            "GET",
+            "/dna/intent/api/v1/issues",
+            200,
+            r#"
+              {"version":"1.0","totalCount":2,"response":[
+                {"issueId":"9d2b5b0f-3a3c-4f10-8f0a-c5d46f3d4b5e","name":"Switch zrh-acc-sw01 is unreachable","siteId":"a4f3a3c2-f103-4f0a-8c5d-46f3d4b5e9d2","deviceId":"3f0ac5d4-6f3d-4b5e-9d2b-5b0f3a3c2f10","deviceRole":"ACCESS","aiDriven":"No","clientMac":null,"issue_occurence_count":3,"status":"active","priority":"P1","category":"Availability","last_occurence_time":1732811427209},
+                {"issueId":"4b5e9d2b-5b0f-4a3c-8f10-3f0ac5d46f3d","name":"Wireless clients failed to connect","siteId":"","deviceId":"","deviceRole":"","aiDriven":"YES","clientMac":null,"issue_occurence_count":1,"status":"active","priority":"P3","category":"Sensor Test","last_occurence_time":1732811427209}]}
+              "#,
+        )
+        .await;
+
+        let filter = IssueFilter {
+            priority: Some(IssuePriority::P1),
+            status: Some(IssueStatus::Active),
+            ..Default::default()
+        };
+        let issues = Issue::list(&mock.dnac, Some(&filter)).await.unwrap();
+        assert_eq!(issues.len(), 2);
+        assert_eq!(issues[0].category, Some(IssueCategory::Availability));
+        assert_eq!(issues[0].occurrence_count, Some(3));
+        assert_eq!(issues[1].category, Some(IssueCategory::Other));
+        assert!(issues[1].is_ai_driven());
+
+        let requests = mock.server.received_requests().await.unwrap();
+        let request = requests
+            .iter()
+            .find(|r| r.url.path().ends_with("issues"))
+            .unwrap();
+        assert_eq!(request.url.query(), Some("priority=P1&issueStatus=ACTIVE"));
+
+        let filter = IssueFilter {
+            category: Some(IssueCategory::Availability),
+            ..Default::default()
+        };
+        let issues = Issue::list(&mock.dnac, Some(&filter)).await.unwrap();
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].priority, Some(IssuePriority::P1));
+    }
+
+    #[tokio::test]
+    async fn test_enrichment() {
+        let mock = MockDnac::start().await;
+        let body: serde_json::Value = serde_json::from_str(
+            r#"
+              {"issueDetails":{"issue":[{"issueId":"9d2b5b0f-3a3c-4f10-8f0a-c5d46f3d4b5e","issueSource":"Cisco DNA","issueCategory":"Availability","issueName":"snmp_device_down","issueDescription":"This network device zrh-acc-sw01 is unreachable from controller.","issueEntity":"network_device","issueEntityValue":"3f0ac5d4-6f3d-4b5e-9d2b-5b0f3a3c2f10","issueSeverity":"HIGH","issuePriority":"P1","issueSummary":"Network Device 10.10.1.11 Is Unreachable From Controller","issueTimestamp":1732811427209,
+                "suggestedActions":[{"message":"From the controller, verify whether the last hop is reachable.","steps":[]},{"message":"Verify that the physical port(s) on the network device associated with the network device discovery(IP) are UP.","steps":[]}],
+                "impactedHosts":[{"hostType":"wired","hostName":"zrh-pc-042","hostOsType":"Windows","hostDeviceType":"Laptop","hostVendor":"Dell","macAddress":"00:50:56:a1:b2:c3","connectedInterface":"GigabitEthernet1/0/12","failedAttempts":2,"location":{"siteId":"a4f3a3c2-f103-4f0a-8c5d-46f3d4b5e9d2","siteType":"floor","area":"EMEA","building":"Zurich","floor":"Floor 1","apsImpacted":[]},"timestamp":1732811427209}]}]}}
+              "#,
+        )
+        .unwrap();
+        Mock::given(method("GET"))
+            .and(path("/dna/intent/api/v1/issue-enrichment-details"))
+            .and(header("entity_type", "issue_id"))
+            .and(header(
+                "entity_value",
+                "9d2b5b0f-3a3c-4f10-8f0a-c5d46f3d4b5e",
+            ))
+            .respond_with(ResponseTemplate::new(200).set_body_json(body))
+            .mount(&mock.server)
+            .await;
+
+        let issue = Issue::enrichment(&mock.dnac, "9d2b5b0f-3a3c-4f10-8f0a-c5d46f3d4b5e")
+            .await
+            .unwrap();
+        assert_eq!(issue.issue_priority, Some(IssuePriority::P1));
+        assert_eq!(issue.suggested_actions.len(), 2);
+        let host = &issue.impacted_hosts[0];
+        assert_eq!(host.host_name.as_deref(), Some("zrh-pc-042"));
+        assert_eq!(
+            host.location.as_ref().unwrap().building.as_deref(),
+            Some("Zurich")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_resolve() {
+        let mock = MockDnac::start().await;
+        mock.mock_response(
+            "POST",
+            "/dna/data/api/v1/assuranceIssues/resolve",
+            200,
+            r#"
+              {"response":{"successfulIssueIds":["9d2b5b0f-3a3c-4f10-8f0a-c5d46f3d4b5e"],"failureIssueIds":["4b5e9d2b-5b0f-4a3c-8f10-3f0ac5d46f3d"]},"version":"1.0"}
+              "#,
+        )
+        .await;
+
+        let update = Issue::resolve(
+            &mock.dnac,
+            &[
+                "9d2b5b0f-3a3c-4f10-8f0a-c5d46f3d4b5e",
+                "4b5e9d2b-5b0f-4a3c-8f10-3f0ac5d46f3d",
+            ],
+        )
+        .await
+        .unwrap();
+        assert_eq!(update.successful_issue_ids.len(), 1);
+        assert_eq!(
+            update.failure_issue_ids,
+            vec!["4b5e9d2b-5b0f-4a3c-8f10-3f0ac5d46f3d"]
+        );
+
+        let requests = mock.server.received_requests().await.unwrap();
+        let request = requests.iter().find(|r| r.method == "POST").unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&request.body).unwrap();
+        assert_eq!(body["issueIds"].as_array().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_execute_suggested_actions() {
+        let mock = MockDnac::start().await;
+        mock.mock_response(
+            "POST",
+            "/dna/intent/api/v1/execute-suggested-actions-commands",
+            202,
+            fixtures::TASK_INFO,
+        )
+        .await;
+        mock.mock_response(
+            "GET",
+            &format!("/api/v1/task/{}/tree/", fixtures::TASK_ID),
+            200,
+            r#"
+              {"response":[{"id":"0193739c-0d88-78e4-ba0f-d82889fca555","progress":"Suggested actions executed","additionalStatusURL":"/file/2b7a2d44-4f0e-4a1e-80d6-c9b3e5a218c1","startTime":1732811427209,"endTime":1732811429512,"serviceType":"Machine Reasoning Engine","isError":false,"instanceTenantId":"6307971e4289f95403c86831","version":1732811427309}]}
+              "#,
+        )
+        .await;
+        mock.mock_response(
+            "GET",
+            "/dna/intent/api/v1/file/2b7a2d44-4f0e-4a1e-80d6-c9b3e5a218c1",
+            200,
+            r#"
+              [{"actionInfo":"Verify the interface status","stepsCount":1,"entityId":"3f0ac5d4-6f3d-4b5e-9d2b-5b0f3a3c2f10","hostname":"zrh-acc-sw01","stepsDescription":"Check the status of the uplink","command":"show interfaces status","commandOutput":{"show interfaces status":"Gi1/0/48  uplink  connected  trunk  a-full a-1000"}}]
+              "#,
+        )
+        .await;
+
+        let outputs =
+            Issue::execute_suggested_actions(&mock.dnac, "9d2b5b0f-3a3c-4f10-8f0a-c5d46f3d4b5e")
+                .await
+                .unwrap();
+        assert_eq!(outputs[0].hostname.as_deref(), Some("zrh-acc-sw01"));
+        assert!(outputs[0].command_output["show interfaces status"].contains("connected"));
+
+        let requests = mock.server.received_requests().await.unwrap();
+        let request = requests.iter().find(|r| r.method == "POST").unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&request.body).unwrap();
+        assert_eq!(body["entity_type"], "issue_id");
+    }
+}
@@ -1,7 +1,70 @@
+pub mod cache;
+pub mod capabilities;
+#[cfg(feature = "assurance")]
+pub mod clients;
+#[cfg(feature = "devices")]
+pub mod command_runner;
+#[cfg(feature = "devices")]
+pub mod compliance;
+#[cfg(feature = "devices")]
+pub mod config_archive;
+#[cfg(feature = "devices")]
 pub mod devices;
+#[cfg(feature = "devices")]
+pub mod discovery;
 pub mod dnac;
-pub use dnac::DNAC;
+pub mod dry_run;
+#[cfg(feature = "devices")]
+pub mod enrichment;
+#[cfg(feature = "core")]
+pub mod events;
+pub mod file;
+#[cfg(feature = "assurance")]
+pub mod health;
+pub mod ids;
+pub use dnac::{ApiClient, ApiClientExt, DNAC};
+pub use dry_run::DryRun;
+pub use ids::{DeviceId, SiteId};
+#[cfg(feature = "devices")]
+pub mod interfaces;
+#[cfg(feature = "assurance")]
+pub mod issues;
 pub mod logging;
+#[cfg(feature = "devices")]
+pub mod maintenance;
+#[cfg(feature = "sites")]
+pub mod maps;
+pub mod metrics;
+#[cfg(feature = "devices")]
+pub mod onboarding;
+#[cfg(feature = "devices")]
+pub mod pathtrace;
 pub mod platform;
+#[cfg(feature = "devices")]
+pub mod pnp;
+pub mod pool;
+pub mod query;
+pub mod rate_limit;
+pub use pool::DnacPool;
+#[cfg(feature = "sites")]
+pub mod profile;
+#[cfg(feature = "sites")]
+pub mod site_tree;
+#[cfg(feature = "sites")]
 pub mod sites;
+#[cfg(feature = "sites")]
 pub use sites::*;
+#[cfg(feature = "swim")]
+pub mod swim;
+pub mod task_poller;
+#[cfg(feature = "assurance")]
+pub mod telemetry;
+#[cfg(feature = "templates")]
+pub mod templates;
+#[cfg(any(test, feature = "test-util"))]
+pub mod test_util;
+pub mod token_encryption;
+#[cfg(feature = "devices")]
+pub mod topology;
+#[cfg(feature = "wireless")]
+pub mod wireless;
@@ -1,23 +1,84 @@
-use std::fs::OpenOptions;
-use std::sync::Arc;
-use tracing_subscriber::{prelude::*, EnvFilter};
-
-pub fn init_logging() {
-    let stdout_log = tracing_subscriber::fmt::layer();
-
-    let file = OpenOptions::new()
-        .write(true)
-        .create(true)
-        .append(true)
-        .open("debug.log")
-        .unwrap();
-    let debug_log = tracing_subscriber::fmt::layer().with_writer(Arc::new(file));
-
-    tracing_subscriber::registry()
-        .with(
-            stdout_log
-                .with_filter(EnvFilter::from_default_env())
-                .and_then(debug_log),
-        )
-        .init();
+use std::{path::PathBuf, sync::Arc};
+
+use thiserror::Error;
+use tracing_subscriber::{
+    filter::ParseError, prelude::*, util::TryInitError, EnvFilter, Layer, Registry,
+};
+
+use super::rotation::{RotatingFile, Rotation};
+
+// LoggingConfig configures the stdout and file logging, the default matches
+// init_logging: stdout filtered by RUST_LOG and everything to debug.log
+#[derive(Debug, Clone)]
+pub struct LoggingConfig {
+    // None disables the log file
+    pub file: Option<PathBuf>,
+    pub rotation: Option<Rotation>,
+    pub json: bool,
+    // filter directives like "info,dnac=debug", None uses RUST_LOG for
+    // stdout and logs everything to the file
+    pub stdout_filter: Option<String>,
+    pub file_filter: Option<String>,
+}
+
+#[derive(Debug, Error)]
+pub enum LoggingError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Filter(#[from] ParseError),
+    #[error(transparent)]
+    Init(#[from] TryInitError),
+}
+
+type BoxedLayer = Box<dyn Layer<Registry> + Send + Sync>;
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        Self {
+            file: Some(PathBuf::from("debug.log")),
+            rotation: None,
+            json: false,
+            stdout_filter: None,
+            file_filter: None,
+        }
+    }
+}
+
+pub fn init_logging() -> Result<(), LoggingError> {
+    init_logging_with(LoggingConfig::default())
+}
+
+pub fn init_logging_with(config: LoggingConfig) -> Result<(), LoggingError> {
+    let stdout_filter = match &config.stdout_filter {
+        Some(filter) => EnvFilter::try_new(filter)?,
+        None => EnvFilter::from_default_env(),
+    };
+    let mut layers: Vec<BoxedLayer> = vec![format_layer(config.json, None)
+        .with_filter(stdout_filter)
+        .boxed()];
+
+    if let Some(path) = &config.file {
+        let file = Arc::new(RotatingFile::open(path, config.rotation)?);
+        let layer = format_layer(config.json, Some(file));
+        layers.push(match &config.file_filter {
+            Some(filter) => layer.with_filter(EnvFilter::try_new(filter)?).boxed(),
+            None => layer,
+        });
+    }
+
+    tracing_subscriber::registry().with(layers).try_init()?;
+
+    Ok(())
+}
+
+// writes to stdout if no file is given
+fn format_layer(json: bool, file: Option<Arc<RotatingFile>>) -> BoxedLayer {
+    let layer = tracing_subscriber::fmt::layer();
+    match (json, file) {
+        (false, None) => layer.boxed(),
+        (true, None) => layer.json().boxed(),
+        (false, Some(file)) => layer.with_ansi(false).with_writer(file).boxed(),
+        (true, Some(file)) => layer.json().with_writer(file).boxed(),
+    }
 }
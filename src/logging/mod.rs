@@ -1,2 +1,5 @@
-pub use logging::init_logging;
+pub use logging::{init_logging, init_logging_with, LoggingConfig, LoggingError};
+pub use rotation::Rotation;
+#[allow(clippy::module_inception)]
 pub mod logging;
+pub mod rotation;
@@ -0,0 +1,120 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+// Rotation rotates the log file once it would grow above max_bytes, the old
+// files are kept as debug.log.1, debug.log.2, ... up to max_files
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rotation {
+    pub max_bytes: u64,
+    pub max_files: usize,
+}
+
+// RotatingFile is used as writer of the file layer via Arc
+#[derive(Debug)]
+pub struct RotatingFile {
+    path: PathBuf,
+    rotation: Option<Rotation>,
+    state: Mutex<(File, u64)>,
+}
+
+impl RotatingFile {
+    pub fn open(path: impl AsRef<Path>, rotation: Option<Rotation>) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file = open_append(&path)?;
+        let size = file.metadata()?.len();
+
+        Ok(Self {
+            path,
+            rotation,
+            state: Mutex::new((file, size)),
+        })
+    }
+
+    fn rotate(&self, rotation: Rotation) -> io::Result<File> {
+        for i in (1..rotation.max_files).rev() {
+            let from = rotated_path(&self.path, i);
+            if from.exists() {
+                fs::rename(from, rotated_path(&self.path, i + 1))?;
+            }
+        }
+
+        if rotation.max_files > 0 {
+            fs::rename(&self.path, rotated_path(&self.path, 1))?;
+        } else {
+            fs::remove_file(&self.path)?;
+        }
+
+        open_append(&self.path)
+    }
+}
+
+impl Write for &RotatingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut state = self.state.lock().unwrap();
+
+        if let Some(rotation) = self.rotation {
+            if state.1 > 0 && state.1 + buf.len() as u64 > rotation.max_bytes {
+                *state = (self.rotate(rotation)?, 0);
+            }
+        }
+
+        let written = state.0.write(buf)?;
+        state.1 += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.state.lock().unwrap().0.flush()
+    }
+}
+
+fn open_append(path: &Path) -> io::Result<File> {
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+fn rotated_path(path: &Path, index: usize) -> PathBuf {
+    let mut path = path.as_os_str().to_owned();
+    path.push(format!(".{index}"));
+    path.into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rotation() {
+        let dir = std::env::temp_dir().join(format!("dnac-log-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("debug.log");
+
+        let file = RotatingFile::open(
+            &path,
+            Some(Rotation {
+                max_bytes: 10,
+                max_files: 2,
+            }),
+        )
+        .unwrap();
+        for line in ["first\n", "second\n", "third\n", "fourth\n"] {
+            (&file).write_all(line.as_bytes()).unwrap();
+        }
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "fourth\n");
+        assert_eq!(
+            fs::read_to_string(rotated_path(&path, 1)).unwrap(),
+            "third\n"
+        );
+        assert_eq!(
+            fs::read_to_string(rotated_path(&path, 2)).unwrap(),
+            "second\n"
+        );
+        assert!(!rotated_path(&path, 3).exists());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
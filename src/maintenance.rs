@@ -0,0 +1,167 @@
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::{event, Level};
+use uuid::Uuid;
+
+use crate::{
+    dnac::{ApiClient, Pagination, PaginationError, ResponseType},
+    ids::DeviceId,
+    query::Query,
+};
+
+// MaintenanceSchedule puts devices into maintenance, assurance doesn't raise
+// issues for devices while their maintenance window is active
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MaintenanceSchedule {
+    pub id: Uuid,
+    pub description: Option<String>,
+    pub maintenance_schedule: MaintenanceWindow,
+    #[serde(default)]
+    pub network_device_ids: Vec<DeviceId>,
+}
+
+// the times are epoch milliseconds
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MaintenanceWindow {
+    pub start_time: u64,
+    pub end_time: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recurrence: Option<Recurrence>,
+    #[serde(skip_serializing)]
+    pub status: Option<MaintenanceStatus>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Recurrence {
+    // days between two windows
+    pub interval: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recurrence_end_time: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum MaintenanceStatus {
+    Upcoming,
+    InProgress,
+    Completed,
+    Cancelled,
+    Failed,
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateMaintenanceSchedule {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub maintenance_schedule: MaintenanceWindow,
+    pub network_device_ids: Vec<DeviceId>,
+}
+
+#[derive(Debug, Error)]
+pub enum MaintenanceError {
+    #[error("General Maintenance Error")]
+    GeneralError,
+    #[error(transparent)]
+    PaginationOutOfRange(#[from] PaginationError),
+}
+
+impl MaintenanceSchedule {
+    // device_id limits the result to the schedules of that device
+    pub async fn list<C: ApiClient>(
+        dnac: &C,
+        device_id: Option<DeviceId>,
+        pagination: Option<Pagination>,
+    ) -> Result<Vec<MaintenanceSchedule>, MaintenanceError> {
+        let path = "/dna/intent/api/v1/networkDeviceMaintenanceSchedules";
+        let query = Query::new().eq_opt("networkDeviceIds", device_id);
+
+        let schedule_data = dnac
+            .get::<MaintenanceSchedule>(path, Some(query.as_slice()), pagination)
+            .await;
+
+        match schedule_data {
+            Ok(schedule_data) => match schedule_data.response {
+                ResponseType::Array(data) => Ok(data),
+                ResponseType::Item(data) => Ok(vec![data]),
+            },
+            Err(e) => match e.downcast::<PaginationError>() {
+                Ok(pagination_error) => Err(pagination_error.into()),
+                Err(e) => {
+                    event!(Level::ERROR, "{e}");
+                    Err(MaintenanceError::GeneralError)
+                }
+            },
+        }
+    }
+
+    pub async fn create<C: ApiClient>(
+        dnac: &C,
+        schedule: CreateMaintenanceSchedule,
+    ) -> anyhow::Result<()> {
+        let path = "/dna/intent/api/v1/networkDeviceMaintenanceSchedules";
+        dnac.post(path, schedule, true).await
+    }
+
+    pub async fn delete<C: ApiClient>(dnac: &C, schedule_id: Uuid) -> anyhow::Result<()> {
+        let path = format!("/dna/intent/api/v1/networkDeviceMaintenanceSchedules/{schedule_id}");
+        dnac.delete(path.as_str(), None, true).await
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.maintenance_schedule.status == Some(MaintenanceStatus::InProgress)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::MockDnac;
+
+    #[tokio::test]
+    async fn test_maintenance_schedules() {
+        let mock = MockDnac::start().await;
+        mock.mock_response(
+            "GET",
+            "/dna/intent/api/v1/networkDeviceMaintenanceSchedules",
+            200,
+            r#"
+              {"response":[{"id":"7e1c5293-b1d4-4f0e-8c1e-2b7a2d444f0e","description":"IOS-XE upgrade","maintenanceSchedule":{"startId":"a1","endId":"a2","startTime":1732811427209,"endTime":1732818627209,"recurrence":{"interval":7,"recurrenceEndTime":1735489827209},"status":"IN_PROGRESS"},"networkDeviceIds":["3f0ac5d4-6f3d-4b5e-9d2b-5b0f3a3c2f10"]}],"version":"1.0"}
+              "#,
+        )
+        .await;
+        mock.mock_task(
+            "POST",
+            "/dna/intent/api/v1/networkDeviceMaintenanceSchedules",
+            false,
+        )
+        .await;
+
+        let device_id: DeviceId = "3f0ac5d4-6f3d-4b5e-9d2b-5b0f3a3c2f10".parse().unwrap();
+        let schedules = MaintenanceSchedule::list(&mock.dnac, Some(device_id), None)
+            .await
+            .unwrap();
+        assert_eq!(schedules.len(), 1);
+        assert!(schedules[0].is_active());
+        assert_eq!(schedules[0].network_device_ids, vec![device_id]);
+
+        let schedule = CreateMaintenanceSchedule {
+            description: Some("IOS-XE upgrade".to_string()),
+            maintenance_schedule: MaintenanceWindow {
+                start_time: 1732811427209,
+                end_time: 1732818627209,
+                recurrence: None,
+                status: None,
+            },
+            network_device_ids: vec![device_id],
+        };
+        MaintenanceSchedule::create(&mock.dnac, schedule)
+            .await
+            .unwrap();
+    }
+}
@@ -0,0 +1,108 @@
+use anyhow::{anyhow, Result};
+use serde_json::Value;
+
+use crate::{dnac::ApiClientExt, file::File, ids::SiteId};
+
+// Maps wraps the map archive import and export, the archives contain the
+// floor plans, the access point placements and the RF model of the floors
+pub struct Maps;
+
+impl Maps {
+    // imports the Ekahau project (.esx) into the site, the import is
+    // started with an upload and performed once DNAC validated the archive
+    pub async fn import_ekahau<C: ApiClientExt>(
+        dnac: &C,
+        site_id: SiteId,
+        file: Vec<u8>,
+    ) -> Result<()> {
+        let path = format!("/dna/intent/api/v1/maps/import/start?siteHierarchyUuid={site_id}");
+        let response = dnac.upload(path.as_str(), "project.esx", file).await?;
+        let import_id = import_context(&response)
+            .ok_or_else(|| anyhow!("Map import didn't return an import context"))?;
+
+        let path = format!("/dna/intent/api/v1/maps/import/{import_id}/perform");
+        let task_info = dnac.post_task(path.as_str(), serde_json::json!({})).await?;
+        dnac.poll_task(task_info).await?;
+
+        Ok(())
+    }
+
+    // returns the map archive of the site and all sites below it as tar.gz
+    pub async fn export<C: ApiClientExt>(dnac: &C, site_id: SiteId) -> Result<Vec<u8>> {
+        let path = format!("/dna/intent/api/v1/maps/export/{site_id}");
+        let task_info = dnac.post_task(path.as_str(), serde_json::json!({})).await?;
+        let tasks = dnac.poll_task(task_info).await?;
+
+        Ok(File::from_tasks(dnac, &tasks).await?)
+    }
+}
+
+// the import context is returned as plain uuid, some versions wrap it in a
+// response field
+fn import_context(response: &Value) -> Option<&str> {
+    response
+        .as_str()
+        .or_else(|| response["response"].as_str())
+        .filter(|id| !id.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{fixtures, MockDnac};
+
+    #[tokio::test]
+    async fn test_import_ekahau() {
+        let mock = MockDnac::start().await;
+        let site_id: SiteId = "a4f3a3c2-f103-4f0a-8c5d-46f3d4b5e9d2".parse().unwrap();
+        mock.mock_response(
+            "POST",
+            "/dna/intent/api/v1/maps/import/start",
+            200,
+            r#""9d2b5b0f-3a3c-4f10-8f0a-c5d46f3d4b5e""#,
+        )
+        .await;
+        mock.mock_task(
+            "POST",
+            "/dna/intent/api/v1/maps/import/9d2b5b0f-3a3c-4f10-8f0a-c5d46f3d4b5e/perform",
+            false,
+        )
+        .await;
+
+        Maps::import_ekahau(&mock.dnac, site_id, b"esx".to_vec())
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_export() {
+        let mock = MockDnac::start().await;
+        let site_id: SiteId = "a4f3a3c2-f103-4f0a-8c5d-46f3d4b5e9d2".parse().unwrap();
+        mock.mock_response(
+            "POST",
+            &format!("/dna/intent/api/v1/maps/export/{site_id}"),
+            202,
+            fixtures::TASK_INFO,
+        )
+        .await;
+        mock.mock_response(
+            "GET",
+            &format!("/api/v1/task/{}/tree/", fixtures::TASK_ID),
+            200,
+            r#"
+              {"response":[{"id":"0193739c-0d88-78e4-ba0f-d82889fca555","progress":"Map export done","additionalStatusURL":"/file/2b7a2d44-4f0e-4a1e-80d6-c9b3e5a218c1","startTime":1732811427209,"endTime":1732811429512,"serviceType":"Maps Service","isError":false,"instanceTenantId":"6307971e4289f95403c86831","version":1732811427309}]}
+              "#,
+        )
+        .await;
+        mock.mock_response(
+            "GET",
+            "/dna/intent/api/v1/file/2b7a2d44-4f0e-4a1e-80d6-c9b3e5a218c1",
+            200,
+            r#""archive""#,
+        )
+        .await;
+
+        let archive = Maps::export(&mock.dnac, site_id).await.unwrap();
+        assert_eq!(archive, br#""archive""#);
+    }
+}
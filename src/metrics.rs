@@ -0,0 +1,226 @@
+use std::{
+    collections::HashMap,
+    net::IpAddr,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+    time::Duration,
+};
+
+use uuid::Uuid;
+
+// upper bounds of the latency histogram buckets in milliseconds, the last
+// bucket counts everything above
+pub const LATENCY_BUCKETS_MS: [u64; 9] = [10, 50, 100, 250, 500, 1000, 2500, 5000, 10000];
+
+// Metrics is recorded by DNAC for every request, use DNAC::metrics to get a snapshot
+#[derive(Debug, Default)]
+pub struct Metrics {
+    endpoints: Mutex<HashMap<String, EndpointMetrics>>,
+    active_task_polls: AtomicU64,
+    retries: AtomicU64,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct EndpointMetrics {
+    pub requests: u64,
+    pub errors: u64,
+    pub status_codes: HashMap<u16, u64>,
+    pub latency: LatencyHistogram,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct LatencyHistogram {
+    pub buckets: [u64; LATENCY_BUCKETS_MS.len() + 1],
+    pub sum_ms: u64,
+    pub count: u64,
+}
+
+pub struct TaskPollGuard<'a>(&'a AtomicU64);
+
+#[derive(Debug, Clone, Default)]
+pub struct MetricsSnapshot {
+    // keyed by method and path, ids, serials, MACs and IPs in the path are
+    // replaced with a placeholder like {id}
+    pub endpoints: HashMap<String, EndpointMetrics>,
+    pub active_task_polls: u64,
+    pub retries: u64,
+}
+
+impl Metrics {
+    // status is None if the request failed before a response was received,
+    // these and all non 2xx responses count as errors
+    pub fn record_request(&self, method: &str, path: &str, status: Option<u16>, latency: Duration) {
+        let key = format!("{method} {}", normalize_path(path));
+        let mut endpoints = self.endpoints.lock().unwrap();
+        let endpoint = endpoints.entry(key).or_default();
+
+        endpoint.requests += 1;
+        if let Some(status) = status {
+            *endpoint.status_codes.entry(status).or_default() += 1;
+        }
+        if !status.is_some_and(|status| (200..300).contains(&status)) {
+            endpoint.errors += 1;
+        }
+        endpoint.latency.record(latency);
+    }
+
+    pub fn record_retry(&self) {
+        self.retries.fetch_add(1, Ordering::Relaxed);
+    }
+
+    // the poll counts as active until the guard is dropped, so cancelled polls are handled too
+    pub fn task_poll_guard(&self) -> TaskPollGuard<'_> {
+        self.active_task_polls.fetch_add(1, Ordering::Relaxed);
+        TaskPollGuard(&self.active_task_polls)
+    }
+
+    pub fn snapshot(&self) -> MetricsSnapshot {
+        MetricsSnapshot {
+            endpoints: self.endpoints.lock().unwrap().clone(),
+            active_task_polls: self.active_task_polls.load(Ordering::Relaxed),
+            retries: self.retries.load(Ordering::Relaxed),
+        }
+    }
+}
+
+impl LatencyHistogram {
+    pub fn record(&mut self, latency: Duration) {
+        let ms = latency.as_millis() as u64;
+        let bucket = LATENCY_BUCKETS_MS
+            .iter()
+            .position(|upper| ms <= *upper)
+            .unwrap_or(LATENCY_BUCKETS_MS.len());
+
+        self.buckets[bucket] += 1;
+        self.sum_ms += ms;
+        self.count += 1;
+    }
+
+    pub fn mean(&self) -> Option<Duration> {
+        if self.count == 0 {
+            return None;
+        }
+
+        Some(Duration::from_millis(self.sum_ms / self.count))
+    }
+}
+
+impl Drop for TaskPollGuard<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+// ids, serials and addresses in paths would create an entry per device or
+// site, so we replace them
+fn normalize_path(path: &str) -> String {
+    let path = path.split('?').next().unwrap_or_default();
+    let mut previous = "";
+    path.split('/')
+        .map(|segment| {
+            let normalized = if Uuid::parse_str(segment).is_ok()
+                || (!segment.is_empty() && segment.chars().all(|c| c.is_ascii_digit()))
+            {
+                "{id}"
+            } else if segment.parse::<IpAddr>().is_ok() {
+                "{ip}"
+            } else if is_mac(segment) {
+                "{mac}"
+            } else if previous == "serial-number" && !segment.is_empty() {
+                "{serial}"
+            } else {
+                segment
+            };
+            previous = segment;
+            normalized
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+// accepts aa:bb:cc:dd:ee:ff, aa-bb-cc-dd-ee-ff and aabb.ccdd.eeff
+fn is_mac(segment: &str) -> bool {
+    let groups = |separator: char, count: usize, len: usize| {
+        let parts: Vec<_> = segment.split(separator).collect();
+        parts.len() == count
+            && parts
+                .iter()
+                .all(|p| p.len() == len && p.chars().all(|c| c.is_ascii_hexdigit()))
+    };
+    groups(':', 6, 2) || groups('-', 6, 2) || groups('.', 3, 4)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_request() {
+        let metrics = Metrics::default();
+        metrics.record_request(
+            "GET",
+            "/dna/intent/api/v1/network-device/3f0ac5d4-6f3d-4b5e-9d2b-5b0f3a3c2f10",
+            Some(200),
+            Duration::from_millis(120),
+        );
+        metrics.record_request(
+            "GET",
+            "/dna/intent/api/v1/network-device/8c1e2b7a-2d44-4f0e-a1e3-0d6c9b3e5a21",
+            None,
+            Duration::from_millis(20),
+        );
+
+        let snapshot = metrics.snapshot();
+        let endpoint = &snapshot.endpoints["GET /dna/intent/api/v1/network-device/{id}"];
+        assert_eq!(endpoint.requests, 2);
+        assert_eq!(endpoint.errors, 1);
+        assert_eq!(endpoint.status_codes[&200], 1);
+        assert_eq!(endpoint.latency.buckets[1], 1);
+        assert_eq!(endpoint.latency.buckets[3], 1);
+    }
+
+    #[test]
+    fn test_record_error_status() {
+        let metrics = Metrics::default();
+        let path = "/dna/intent/api/v1/site";
+        metrics.record_request("GET", path, Some(200), Duration::from_millis(10));
+        metrics.record_request("GET", path, Some(429), Duration::from_millis(10));
+        metrics.record_request("GET", path, Some(500), Duration::from_millis(10));
+
+        let snapshot = metrics.snapshot();
+        let endpoint = &snapshot.endpoints["GET /dna/intent/api/v1/site"];
+        assert_eq!(endpoint.requests, 3);
+        assert_eq!(endpoint.errors, 2);
+        assert_eq!(endpoint.status_codes[&429], 1);
+    }
+
+    #[test]
+    fn test_normalize_path() {
+        assert_eq!(
+            normalize_path("/dna/intent/api/v1/network-device/serial-number/FOC1234X0AB"),
+            "/dna/intent/api/v1/network-device/serial-number/{serial}"
+        );
+        assert_eq!(
+            normalize_path("/dna/intent/api/v1/interface/ip-address/10.10.1.11"),
+            "/dna/intent/api/v1/interface/ip-address/{ip}"
+        );
+        assert_eq!(
+            normalize_path("/dna/intent/api/v1/interface/ip-address/2001:db8::1"),
+            "/dna/intent/api/v1/interface/ip-address/{ip}"
+        );
+        assert_eq!(
+            normalize_path("/api/v1/host/mac/00:1b:54:c2:4e:01"),
+            "/api/v1/host/mac/{mac}"
+        );
+        assert_eq!(
+            normalize_path("/api/v1/host/mac/001b.54c2.4e01"),
+            "/api/v1/host/mac/{mac}"
+        );
+        assert_eq!(
+            normalize_path("/dna/intent/api/v1/site/count?siteId=1"),
+            "/dna/intent/api/v1/site/count"
+        );
+    }
+}
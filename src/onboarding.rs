@@ -0,0 +1,254 @@
+use std::{collections::HashMap, fs};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::{event, Level};
+
+use crate::{
+    devices::{
+        AddDevice, CliTransport, Device, DeviceType, SnmpAuthProtocol, SnmpMode, SnmpPrivProtocol,
+        SnmpVersion,
+    },
+    dnac::ApiClient,
+};
+
+// DeviceBatch is a prepared set of AddDevice payloads which can be written to
+// a file and executed later on another machine. Secrets are never part of the
+// batch, only references which are resolved when the batch is executed.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct DeviceBatch {
+    pub name: String,
+    pub created_at: i64,
+    pub devices: Vec<PreparedDevice>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PreparedDevice {
+    pub ip_address: Vec<String>,
+    pub device_type: DeviceType,
+    pub user_name: String,
+    pub password: Option<SecretRef>,
+    pub enable_password: Option<SecretRef>,
+    pub cli_transport: CliTransport,
+    pub snmp_version: SnmpVersion,
+    pub snmp_user_name: String,
+    pub snmp_mode: SnmpMode,
+    pub snmp_auth_passphrase: Option<SecretRef>,
+    pub snmp_priv_passphrase: Option<SecretRef>,
+    pub snmp_auth_protocol: SnmpAuthProtocol,
+    pub snmp_priv_protocol: SnmpPrivProtocol,
+    pub netconf_port: u16,
+}
+
+// SecretRef is the name under which a secret is looked up on execution
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct SecretRef(pub String);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SecretField {
+    Password,
+    EnablePassword,
+    SnmpAuthPassphrase,
+    SnmpPrivPassphrase,
+}
+
+// SecretResolver provides the secrets on the executing machine, e.g. from a
+// vault or environment variables
+pub trait SecretResolver {
+    fn resolve(&self, reference: &SecretRef) -> Option<String>;
+}
+
+// resolves references from environment variables of the same name
+pub struct EnvSecrets;
+
+#[derive(Debug, Error)]
+pub enum OnboardingError {
+    #[error("Secret {0} could not be resolved")]
+    UnresolvedSecret(String),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Serde(#[from] serde_json::Error),
+}
+
+impl DeviceBatch {
+    // externalizes the secrets of the devices, secret_ref names the reference
+    // for a field of a device. Devices sharing a credential should get the
+    // same reference. Returns the batch and the secrets by reference, which
+    // have to be transferred separately.
+    pub fn prepare<F>(
+        name: impl Into<String>,
+        devices: Vec<AddDevice>,
+        secret_ref: F,
+    ) -> (Self, HashMap<SecretRef, String>)
+    where
+        F: Fn(&AddDevice, SecretField) -> SecretRef,
+    {
+        let mut secrets = HashMap::new();
+        let mut externalize = |device: &AddDevice, field: SecretField, value: &str| {
+            if value.is_empty() {
+                return None;
+            }
+
+            let reference = secret_ref(device, field);
+            secrets.insert(reference.clone(), value.to_string());
+            Some(reference)
+        };
+
+        let devices = devices
+            .into_iter()
+            .map(|device| PreparedDevice {
+                password: externalize(&device, SecretField::Password, &device.password),
+                enable_password: externalize(
+                    &device,
+                    SecretField::EnablePassword,
+                    &device.enable_password,
+                ),
+                snmp_auth_passphrase: externalize(
+                    &device,
+                    SecretField::SnmpAuthPassphrase,
+                    &device.snmp_auth_passphrase,
+                ),
+                snmp_priv_passphrase: externalize(
+                    &device,
+                    SecretField::SnmpPrivPassphrase,
+                    &device.snmp_priv_passphrase,
+                ),
+                ip_address: device.ip_address,
+                device_type: device.device_type,
+                user_name: device.user_name,
+                cli_transport: device.cli_transport,
+                snmp_version: device.snmp_version,
+                snmp_user_name: device.snmp_user_name,
+                snmp_mode: device.snmp_mode,
+                snmp_auth_protocol: device.snmp_auth_protocol,
+                snmp_priv_protocol: device.snmp_priv_protocol,
+                netconf_port: device.netconf_port,
+            })
+            .collect();
+
+        let batch = DeviceBatch {
+            name: name.into(),
+            created_at: chrono::offset::Local::now().timestamp(),
+            devices,
+        };
+        (batch, secrets)
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), OnboardingError> {
+        let file = fs::File::create(path)?;
+        serde_json::to_writer_pretty(file, self)?;
+
+        Ok(())
+    }
+
+    pub fn load(path: &str) -> Result<Self, OnboardingError> {
+        let file = fs::File::open(path)?;
+        let batch = serde_json::from_reader(file)?;
+
+        Ok(batch)
+    }
+
+    // resolves all secrets, fails before anything is sent if one is missing
+    pub fn resolve<R: SecretResolver>(
+        &self,
+        resolver: &R,
+    ) -> Result<Vec<AddDevice>, OnboardingError> {
+        self.devices.iter().map(|d| d.resolve(resolver)).collect()
+    }
+
+    // adds all devices of the batch, returns the result per device in the
+    // order of the batch
+    pub async fn execute<C: ApiClient, R: SecretResolver>(
+        &self,
+        dnac: &C,
+        resolver: &R,
+    ) -> Result<Vec<anyhow::Result<()>>, OnboardingError> {
+        let devices = self.resolve(resolver)?;
+
+        let mut results = vec![];
+        for device in devices {
+            let result = Device::add_device(dnac, device).await;
+            if let Err(e) = &result {
+                event!(Level::ERROR, "{e}");
+            }
+            results.push(result);
+        }
+
+        Ok(results)
+    }
+}
+
+impl PreparedDevice {
+    pub fn resolve<R: SecretResolver>(&self, resolver: &R) -> Result<AddDevice, OnboardingError> {
+        let resolve = |reference: &Option<SecretRef>| match reference {
+            Some(reference) => resolver
+                .resolve(reference)
+                .ok_or_else(|| OnboardingError::UnresolvedSecret(reference.0.clone())),
+            None => Ok(String::new()),
+        };
+
+        Ok(AddDevice {
+            ip_address: self.ip_address.clone(),
+            device_type: self.device_type,
+            user_name: self.user_name.clone(),
+            password: resolve(&self.password)?,
+            enable_password: resolve(&self.enable_password)?,
+            cli_transport: self.cli_transport,
+            snmp_version: self.snmp_version,
+            snmp_user_name: self.snmp_user_name.clone(),
+            snmp_mode: self.snmp_mode,
+            snmp_auth_passphrase: resolve(&self.snmp_auth_passphrase)?,
+            snmp_priv_passphrase: resolve(&self.snmp_priv_passphrase)?,
+            snmp_auth_protocol: self.snmp_auth_protocol,
+            snmp_priv_protocol: self.snmp_priv_protocol,
+            netconf_port: self.netconf_port,
+        })
+    }
+}
+
+impl SecretResolver for HashMap<SecretRef, String> {
+    fn resolve(&self, reference: &SecretRef) -> Option<String> {
+        self.get(reference).cloned()
+    }
+}
+
+impl SecretResolver for EnvSecrets {
+    fn resolve(&self, reference: &SecretRef) -> Option<String> {
+        std::env::var(&reference.0).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prepare_and_resolve() {
+        let device = AddDevice {
+            ip_address: vec!["10.0.0.1".to_string()],
+            user_name: "admin".to_string(),
+            password: "secret".to_string(),
+            snmp_auth_passphrase: "auth".to_string(),
+            ..Default::default()
+        };
+
+        let (batch, secrets) = DeviceBatch::prepare("site-a", vec![device], |_, field| {
+            SecretRef(format!("SITE_A_{field:?}").to_uppercase())
+        });
+
+        let json = serde_json::to_string(&batch).unwrap();
+        assert!(!json.contains("secret"));
+        assert_eq!(batch.devices[0].enable_password, None);
+
+        let batch: DeviceBatch = serde_json::from_str(&json).unwrap();
+        let devices = batch.resolve(&secrets).unwrap();
+        assert_eq!(devices[0].password, "secret");
+        assert_eq!(devices[0].snmp_auth_passphrase, "auth");
+
+        let missing = batch.resolve(&HashMap::new());
+        assert!(
+            matches!(missing, Err(OnboardingError::UnresolvedSecret(s)) if s == "SITE_A_PASSWORD")
+        );
+    }
+}
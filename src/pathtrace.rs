@@ -0,0 +1,349 @@
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use thiserror::Error;
+use tracing::{event, Level};
+
+use crate::dnac::{ApiClient, ApiClientExt, ResponseType};
+
+// PathTrace is a flow analysis between two hosts, DNAC calculates the path
+// in the background and the trace has to be fetched until it's done
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PathTrace {
+    pub request: PathTraceRequest,
+    // the hops from source to destination
+    #[serde(default)]
+    pub network_elements_info: Vec<Hop>,
+    pub detailed_status: Option<DetailedStatus>,
+    pub last_update: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PathTraceRequest {
+    pub id: String,
+    #[serde(rename = "sourceIP")]
+    pub source_ip: String,
+    #[serde(rename = "destIP")]
+    pub dest_ip: String,
+    pub source_port: Option<String>,
+    pub dest_port: Option<String>,
+    pub protocol: Option<String>,
+    pub status: PathTraceStatus,
+    pub failure_reason: Option<String>,
+    pub create_time: Option<u64>,
+    pub last_update_time: Option<u64>,
+    #[serde(default)]
+    pub inclusions: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum PathTraceStatus {
+    Inprogress,
+    Completed,
+    Failed,
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Hop {
+    pub id: Option<String>,
+    pub name: Option<String>,
+    pub ip: Option<String>,
+    #[serde(rename = "type")]
+    pub device_type: Option<String>,
+    pub role: Option<String>,
+    // e.g. OSPF, STATIC or SWITCHED
+    pub link_information_source: Option<String>,
+    pub ingress_interface: Option<HopInterface>,
+    pub egress_interface: Option<HopInterface>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HopInterface {
+    pub physical_interface: Option<PathInterface>,
+    #[serde(default)]
+    pub virtual_interface: Vec<PathInterface>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PathInterface {
+    pub id: Option<String>,
+    pub name: Option<String>,
+    pub used_vlan: Option<String>,
+    pub vrf_name: Option<String>,
+    // only set with the InterfaceStats inclusion
+    pub interface_statistics: Option<InterfaceStatistics>,
+    // only set with the AclTrace inclusion
+    pub acl_analysis: Option<AclAnalysis>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InterfaceStatistics {
+    pub admin_status: Option<String>,
+    pub operational_status: Option<String>,
+    pub input_packets: Option<u64>,
+    pub output_packets: Option<u64>,
+    pub input_queue_drops: Option<u64>,
+    pub output_drops: Option<u64>,
+    pub input_ratebps: Option<u64>,
+    pub output_ratebps: Option<u64>,
+    pub refreshed_at: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AclAnalysis {
+    pub acl_name: Option<String>,
+    pub result: AclResult,
+    #[serde(default)]
+    pub matching_aces: Vec<MatchingAce>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MatchingAce {
+    pub ace: String,
+    pub result: AclResult,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum AclResult {
+    Permit,
+    Deny,
+    NoMatch,
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DetailedStatus {
+    pub acl_trace_calculation: Option<String>,
+    pub acl_trace_calculation_failure_reason: Option<String>,
+}
+
+// PathTraceOptions are the optional parts of the flow, the ports are only
+// used together with a protocol
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PathTraceOptions {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_port: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dest_port: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub protocol: Option<Protocol>,
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    pub inclusions: Vec<Inclusion>,
+    // traces the control plane path instead of the data plane path
+    pub control_path: bool,
+    // DNAC refreshes the trace every 30 seconds
+    pub periodic_refresh: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum Protocol {
+    Tcp,
+    Udp,
+}
+
+// the statistics collected for every hop in addition to the path
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "SCREAMING-KEBAB-CASE")]
+pub enum Inclusion {
+    InterfaceStats,
+    QosStats,
+    DeviceStats,
+    PerformanceStats,
+    AclTrace,
+}
+
+#[derive(Serialize)]
+struct CreatePathTrace<'a> {
+    #[serde(rename = "sourceIP")]
+    source_ip: &'a str,
+    #[serde(rename = "destIP")]
+    dest_ip: &'a str,
+    #[serde(flatten)]
+    options: &'a PathTraceOptions,
+}
+
+#[derive(Debug, Error)]
+pub enum PathTraceError {
+    #[error("General Path Trace Error")]
+    GeneralError,
+}
+
+impl PathTrace {
+    // starts the flow analysis and returns its id, the trace can be fetched
+    // with get once the status is no longer Inprogress
+    pub async fn create<C: ApiClientExt>(
+        dnac: &C,
+        src_ip: &str,
+        dst_ip: &str,
+        options: &PathTraceOptions,
+    ) -> Result<String> {
+        let path = "/dna/intent/api/v1/flow-analysis";
+        let request = CreatePathTrace {
+            source_ip: src_ip,
+            dest_ip: dst_ip,
+            options,
+        };
+
+        let response = dnac.post_raw(path, &serde_json::to_value(request)?).await?;
+        flow_analysis_id(&response)
+            .map(String::from)
+            .ok_or_else(|| anyhow!("Flow analysis didn't return a flow analysis id"))
+    }
+
+    pub async fn get<C: ApiClient>(dnac: &C, flow_id: &str) -> Result<PathTrace, PathTraceError> {
+        let path = format!("/dna/intent/api/v1/flow-analysis/{flow_id}");
+
+        let trace_data = dnac.get::<PathTrace>(path.as_str(), None, None).await;
+
+        match trace_data {
+            Ok(trace_data) => match trace_data.response {
+                ResponseType::Item(data) => Ok(data),
+                ResponseType::Array(data) => {
+                    data.into_iter().next().ok_or(PathTraceError::GeneralError)
+                }
+            },
+            Err(e) => {
+                event!(Level::ERROR, "{e}");
+                Err(PathTraceError::GeneralError)
+            }
+        }
+    }
+
+    pub async fn delete<C: ApiClient>(dnac: &C, flow_id: &str) -> Result<()> {
+        let path = format!("/dna/intent/api/v1/flow-analysis/{flow_id}");
+        dnac.delete(path.as_str(), None, true).await
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.request.status != PathTraceStatus::Inprogress
+    }
+
+    // the hops where an ingress or egress ACL denies the flow
+    pub fn denied_hops(&self) -> impl Iterator<Item = &Hop> {
+        self.network_elements_info.iter().filter(|hop| {
+            [&hop.ingress_interface, &hop.egress_interface]
+                .into_iter()
+                .flatten()
+                .flat_map(|i| i.physical_interface.iter().chain(&i.virtual_interface))
+                .filter_map(|i| i.acl_analysis.as_ref())
+                .any(|acl| acl.result == AclResult::Deny)
+        })
+    }
+}
+
+fn flow_analysis_id(response: &Value) -> Option<&str> {
+    response["response"]["flowAnalysisId"]
+        .as_str()
+        .filter(|id| !id.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::MockDnac;
+
+    #[tokio::test]
+    async fn test_create() {
+        let mock = MockDnac::start().await;
+        mock.mock_response(
+            "POST",
+            "/dna/intent/api/v1/flow-analysis",
+            202,
+            r#"
+              {"response":{"flowAnalysisId":"6a8d2c1e-5f0b-4d3a-9e7c-1b2f3a4c5d6e","taskId":"0193739c-0d88-78e4-ba0f-d82889fca555","url":"/api/v1/flow-analysis/6a8d2c1e-5f0b-4d3a-9e7c-1b2f3a4c5d6e"},"version":"1.0"}
+              "#,
+        )
+        .await;
+
+        let options = PathTraceOptions {
+            dest_port: Some(443),
+            protocol: Some(Protocol::Tcp),
+            inclusions: vec![Inclusion::InterfaceStats, Inclusion::AclTrace],
+            ..Default::default()
+        };
+        let flow_id = PathTrace::create(&mock.dnac, "10.10.20.15", "10.20.1.10", &options)
+            .await
+            .unwrap();
+        assert_eq!(flow_id, "6a8d2c1e-5f0b-4d3a-9e7c-1b2f3a4c5d6e");
+
+        let requests = mock.server.received_requests().await.unwrap();
+        let request = requests.iter().find(|r| r.method == "POST").unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&request.body).unwrap();
+        assert_eq!(body["sourceIP"], "10.10.20.15");
+        assert_eq!(body["destPort"], 443);
+        assert_eq!(body["protocol"], "TCP");
+        assert_eq!(
+            body["inclusions"],
+            serde_json::json!(["INTERFACE-STATS", "ACL-TRACE"])
+        );
+        assert!(body.get("sourcePort").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_get() {
+        let mock = MockDnac::start().await;
+        mock.mock_response(
+            "GET",
+            "/dna/intent/api/v1/flow-analysis/6a8d2c1e-5f0b-4d3a-9e7c-1b2f3a4c5d6e",
+            200,
+            r#"
+              {"response":{"request":{"id":"6a8d2c1e-5f0b-4d3a-9e7c-1b2f3a4c5d6e","sourceIP":"10.10.20.15","destIP":"10.20.1.10","destPort":"443","protocol":"TCP","status":"COMPLETED","createTime":1732811427209,"lastUpdateTime":1732811429512,"inclusions":["INTERFACE-STATS","ACL-TRACE"]},
+                "networkElementsInfo":[
+                  {"ip":"10.10.20.15","type":"wired","role":"HOST","linkInformationSource":"Switched"},
+                  {"id":"3f0ac5d4-6f3d-4b5e-9d2b-5b0f3a3c2f10","name":"zrh-acc-sw01","ip":"10.10.1.11","type":"Switches and Hubs","role":"ACCESS","linkInformationSource":"OSPF",
+                   "ingressInterface":{"physicalInterface":{"id":"5e9d2b5b-0f3a-4c2f-a103-f0ac5d46f3d4","name":"GigabitEthernet1/0/12","usedVlan":"20","interfaceStatistics":{"adminStatus":"UP","operationalStatus":"up","inputPackets":1024,"outputPackets":2048,"inputQueueDrops":0,"outputDrops":3,"inputRatebps":8000,"outputRatebps":16000,"refreshedAt":1732811429000}}},
+                   "egressInterface":{"physicalInterface":{"id":"9d2b5b0f-3a3c-4f10-8f0a-c5d46f3d4b5e","name":"GigabitEthernet1/0/48","aclAnalysis":{"aclName":"ACL_BLOCK_WEB","result":"DENY","matchingAces":[{"ace":"10 deny tcp any any eq 443","result":"DENY"}]}}}},
+                  {"ip":"10.20.1.10","type":"wired","role":"HOST"}],
+                "detailedStatus":{"aclTraceCalculation":"SUCCESS"},"lastUpdate":"Thu Nov 28 16:30:29 UTC 2024"},"version":"1.0"}
+              "#,
+        )
+        .await;
+
+        let trace = PathTrace::get(&mock.dnac, "6a8d2c1e-5f0b-4d3a-9e7c-1b2f3a4c5d6e")
+            .await
+            .unwrap();
+        assert!(trace.is_done());
+        assert_eq!(trace.network_elements_info.len(), 3);
+        let hop = &trace.network_elements_info[1];
+        let stats = hop
+            .ingress_interface
+            .as_ref()
+            .and_then(|i| i.physical_interface.as_ref())
+            .and_then(|i| i.interface_statistics.as_ref())
+            .unwrap();
+        assert_eq!(stats.output_drops, Some(3));
+
+        let denied: Vec<_> = trace.denied_hops().map(|h| h.name.as_deref()).collect();
+        assert_eq!(denied, vec![Some("zrh-acc-sw01")]);
+    }
+
+    #[tokio::test]
+    async fn test_delete() {
+        let mock = MockDnac::start().await;
+        mock.mock_task(
+            "DELETE",
+            "/dna/intent/api/v1/flow-analysis/6a8d2c1e-5f0b-4d3a-9e7c-1b2f3a4c5d6e",
+            false,
+        )
+        .await;
+
+        PathTrace::delete(&mock.dnac, "6a8d2c1e-5f0b-4d3a-9e7c-1b2f3a4c5d6e")
+            .await
+            .unwrap();
+    }
+}
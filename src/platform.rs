@@ -1,7 +1,7 @@
 use anyhow::{anyhow, Result};
 use serde::{Deserialize, Serialize};
 
-use crate::dnac::DNAC;
+use crate::dnac::ApiClient;
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct ReleaseSummary {
@@ -27,7 +27,7 @@ pub struct ReleaseSummary {
 }
 
 impl ReleaseSummary {
-    pub async fn get_release_summary(dnac: &DNAC) -> Result<Self> {
+    pub async fn get_release_summary<C: ApiClient>(dnac: &C) -> Result<Self> {
         let path = "/dna/intent/api/v1/dnac-release";
 
         let site_data = dnac.get(path, None, None).await;
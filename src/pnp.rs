@@ -0,0 +1,713 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use thiserror::Error;
+use tracing::{event, Level};
+
+use crate::{
+    dnac::{ApiClient, ApiClientExt},
+    ids::{ImageId, SiteId, TemplateId},
+};
+
+#[derive(Debug, Error)]
+pub enum PnpError {
+    #[error("General PnP Error")]
+    GeneralError,
+}
+
+// the pnp endpoints don't wrap their responses in a response field
+async fn get_pnp_data<C, T>(dnac: &C, path: &str) -> Result<T, PnpError>
+where
+    C: ApiClient,
+    T: serde::de::DeserializeOwned,
+{
+    match dnac.get_raw(path, None, None).await {
+        Ok(pnp_data) => serde_json::from_value(pnp_data).map_err(|e| {
+            event!(Level::ERROR, "{e}");
+            PnpError::GeneralError
+        }),
+        Err(e) => {
+            event!(Level::ERROR, "{e}");
+            Err(PnpError::GeneralError)
+        }
+    }
+}
+
+// PnpDevice is a device in the plug and play inventory, it becomes a network
+// device once it was claimed and provisioned
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PnpDevice {
+    pub id: String,
+    pub device_info: PnpDeviceInfo,
+}
+
+// PnpDeviceInfo identifies a device by its serial number and product id,
+// state and onb_state are set by DNAC
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PnpDeviceInfo {
+    pub serial_number: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pid: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hostname: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub stack: bool,
+    #[serde(default)]
+    pub sudi_required: bool,
+    // e.g. Unclaimed, Planned, Onboarding, Provisioned or Error
+    #[serde(skip_serializing)]
+    pub state: Option<String>,
+    #[serde(skip_serializing)]
+    pub onb_state: Option<String>,
+}
+
+// the result of PnpDevice::add, devices which already exist are failures
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PnpImport {
+    #[serde(default)]
+    pub success_list: Vec<PnpDevice>,
+    #[serde(default)]
+    pub failure_list: Vec<PnpImportFailure>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PnpImportFailure {
+    // the position of the device in the request
+    pub index: Option<usize>,
+    pub serial_num: Option<String>,
+    pub msg: Option<String>,
+}
+
+// PnpWorkflow is an onboarding sequence, e.g. an image upgrade followed by
+// the day 0 configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PnpWorkflow {
+    // empty for new workflows, it's set by DNAC on create
+    #[serde(default, alias = "_id", skip_serializing_if = "String::is_empty")]
+    pub id: String,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub tasks: Vec<WorkflowTask>,
+    #[serde(default)]
+    pub add_to_inventory: bool,
+}
+
+// the tasks run in the order of their task_seq_no
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkflowTask {
+    pub name: String,
+    pub task_seq_no: u32,
+    #[serde(flatten)]
+    pub task_type: WorkflowTaskType,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum WorkflowTaskType {
+    #[serde(rename_all = "camelCase")]
+    Image { image_id: ImageId },
+    #[serde(rename_all = "camelCase")]
+    Config { config_info: WorkflowConfig },
+    // task types which aren't supported yet, e.g. Reload or Cli
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkflowConfig {
+    pub config_id: TemplateId,
+    #[serde(default)]
+    pub config_parameters: Vec<WorkflowConfigParameter>,
+    #[serde(default)]
+    pub save_to_start_up: bool,
+    #[serde(default)]
+    pub conn_loss_roll_back: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkflowConfigParameter {
+    pub key: String,
+    pub value: String,
+}
+
+// PnpSettings are the global PnP settings, settings which aren't modeled
+// are kept in other, so an update doesn't reset them
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PnpSettings {
+    #[serde(default)]
+    pub sava_mapping_list: Vec<SavaMapping>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub task_time_outs: Option<TaskTimeouts>,
+    #[serde(default)]
+    pub accept_eula: bool,
+    #[serde(flatten)]
+    pub other: serde_json::Map<String, Value>,
+}
+
+// timeouts of the onboarding steps in minutes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskTimeouts {
+    pub image_download_time_out: u32,
+    pub image_install_time_out: u32,
+    pub config_time_out: u32,
+    pub general_time_out: u32,
+}
+
+// SavaMapping links a Smart Account virtual account to DNAC, the devices
+// registered in the virtual account are synced to the PnP inventory
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SavaMapping {
+    pub smart_account_id: String,
+    pub virtual_account_id: String,
+    pub profile: SavaProfile,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cco_user: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auto_sync_period: Option<u32>,
+    // set by DNAC, see PnpSettings::sync_result
+    #[serde(default, skip_serializing)]
+    pub sync_status: Option<SyncStatus>,
+    #[serde(default, skip_serializing)]
+    pub sync_result_str: Option<String>,
+    #[serde(default, skip_serializing)]
+    pub last_sync: Option<i64>,
+}
+
+// the controller profile which is registered in the virtual account, the
+// devices are redirected to this address
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SavaProfile {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address_fqdn: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address_ip_v4: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub port: Option<u16>,
+    #[serde(default)]
+    pub make_default: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum SyncStatus {
+    NotSynced,
+    Syncing,
+    Success,
+    Failure,
+    #[serde(other)]
+    Unknown,
+}
+
+// ClaimRequest claims the PnP device to a site, device_id is the id of the
+// device in the PnP inventory
+#[derive(Debug, Clone)]
+pub struct ClaimRequest {
+    pub device_id: String,
+    pub site_id: SiteId,
+    pub claim_type: ClaimType,
+    pub hostname: Option<String>,
+    // the image is skipped if None
+    pub image_id: Option<ImageId>,
+    pub config: Option<ClaimConfig>,
+    pub stack: Option<StackClaim>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum ClaimType {
+    Default,
+    StackSwitch,
+    AccessPoint,
+    Sensor,
+    #[serde(rename = "CatalystWLC")]
+    CatalystWlc,
+}
+
+// the onboarding template and the values of its parameters
+#[derive(Debug, Clone)]
+pub struct ClaimConfig {
+    pub template_id: TemplateId,
+    pub parameters: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct StackClaim {
+    pub top_of_stack_serial_number: String,
+    // 1A or 1B, see the stack cabling guide of the platform
+    pub cabling_scheme: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SiteClaim<'a> {
+    device_id: &'a str,
+    site_id: SiteId,
+    #[serde(rename = "type")]
+    claim_type: ClaimType,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    hostname: Option<&'a str>,
+    image_info: ImageInfo,
+    config_info: ConfigInfo,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_of_stack_serial_number: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cabling_scheme: Option<&'a str>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ImportDevice<'a> {
+    device_info: &'a PnpDeviceInfo,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Unclaim<'a> {
+    device_id_list: &'a [&'a str],
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Reset<'a> {
+    device_reset_list: Vec<ResetDevice<'a>>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ResetDevice<'a> {
+    device_id: &'a str,
+    config_list: Vec<ConfigInfo>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ImageInfo {
+    image_id: String,
+    skip: bool,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ConfigInfo {
+    config_id: String,
+    config_parameters: Vec<ConfigParameter>,
+}
+
+#[derive(Serialize)]
+struct ConfigParameter {
+    key: String,
+    value: String,
+}
+
+impl PnpDevice {
+    // the device gets the image and the configuration on its next contact
+    pub async fn claim_to_site<C: ApiClient>(
+        dnac: &C,
+        request: ClaimRequest,
+    ) -> anyhow::Result<()> {
+        let path = "/dna/intent/api/v1/onboarding/pnp-device/site-claim";
+        dnac.post(path, request.to_site_claim(), false).await
+    }
+
+    // pre-stages the devices, so they can be claimed once they contact DNAC
+    pub async fn add<C: ApiClientExt>(
+        dnac: &C,
+        devices: &[PnpDeviceInfo],
+    ) -> anyhow::Result<PnpImport> {
+        let path = "/dna/intent/api/v1/onboarding/pnp-device/import";
+        let request: Vec<ImportDevice> = devices
+            .iter()
+            .map(|device_info| ImportDevice { device_info })
+            .collect();
+
+        match dnac.post_raw(path, &serde_json::to_value(request)?).await? {
+            Value::Null => Ok(PnpImport::default()),
+            response => Ok(serde_json::from_value(response)?),
+        }
+    }
+
+    pub async fn delete<C: ApiClient>(dnac: &C, device_id: &str) -> anyhow::Result<()> {
+        let path = format!("/dna/intent/api/v1/onboarding/pnp-device/{device_id}");
+        dnac.delete(path.as_str(), None, false).await
+    }
+
+    // puts claimed devices back to unclaimed
+    pub async fn unclaim<C: ApiClient>(dnac: &C, device_ids: &[&str]) -> anyhow::Result<()> {
+        let path = "/dna/intent/api/v1/onboarding/pnp-device/unclaim";
+        let request = Unclaim {
+            device_id_list: device_ids,
+        };
+        dnac.post(path, request, false).await
+    }
+
+    // recovers devices in the error state, they restart the onboarding
+    pub async fn reset<C: ApiClient>(dnac: &C, device_ids: &[&str]) -> anyhow::Result<()> {
+        let path = "/dna/intent/api/v1/onboarding/pnp-device/reset";
+        let request = Reset {
+            device_reset_list: device_ids
+                .iter()
+                .map(|device_id| ResetDevice {
+                    device_id,
+                    config_list: vec![],
+                })
+                .collect(),
+        };
+        dnac.post(path, request, false).await
+    }
+}
+
+impl PnpWorkflow {
+    pub async fn list<C: ApiClient>(dnac: &C) -> Result<Vec<PnpWorkflow>, PnpError> {
+        let path = "/dna/intent/api/v1/onboarding/pnp-workflow";
+        get_pnp_data(dnac, path).await
+    }
+
+    // returns the created workflow, it has the id set
+    pub async fn create<C: ApiClientExt>(
+        dnac: &C,
+        workflow: &PnpWorkflow,
+    ) -> anyhow::Result<PnpWorkflow> {
+        let path = "/dna/intent/api/v1/onboarding/pnp-workflow";
+
+        // a dry run doesn't return anything, the workflow stays as is
+        match dnac
+            .post_raw(path, &serde_json::to_value(workflow)?)
+            .await?
+        {
+            Value::Null => Ok(workflow.clone()),
+            response => Ok(serde_json::from_value(response)?),
+        }
+    }
+
+    pub async fn update<C: ApiClient>(dnac: &C, workflow: &PnpWorkflow) -> anyhow::Result<()> {
+        let path = format!("/dna/intent/api/v1/onboarding/pnp-workflow/{}", workflow.id);
+        dnac.put(path.as_str(), workflow, false).await
+    }
+
+    pub async fn delete<C: ApiClient>(dnac: &C, workflow_id: &str) -> anyhow::Result<()> {
+        let path = format!("/dna/intent/api/v1/onboarding/pnp-workflow/{workflow_id}");
+        dnac.delete(path.as_str(), None, false).await
+    }
+}
+
+impl PnpSettings {
+    pub async fn get<C: ApiClient>(dnac: &C) -> Result<PnpSettings, PnpError> {
+        let path = "/dna/intent/api/v1/onboarding/pnp-settings";
+        get_pnp_data(dnac, path).await
+    }
+
+    pub async fn update<C: ApiClient>(dnac: &C, settings: &PnpSettings) -> anyhow::Result<()> {
+        let path = "/dna/intent/api/v1/onboarding/pnp-settings";
+        dnac.put(path, settings, false).await
+    }
+
+    // triggers the sync of the virtual account, it runs in the background,
+    // see sync_result
+    pub async fn sync_virtual_account<C: ApiClientExt>(
+        dnac: &C,
+        mapping: SavaMapping,
+    ) -> anyhow::Result<SavaMapping> {
+        let path = "/dna/intent/api/v1/onboarding/pnp-device/vacct-sync";
+
+        // a dry run doesn't return anything, the mapping stays as is
+        match dnac
+            .post_raw(path, &serde_json::to_value(&mapping)?)
+            .await?
+        {
+            Value::Null => Ok(mapping),
+            response => Ok(serde_json::from_value(response)?),
+        }
+    }
+
+    pub async fn sync_result<C: ApiClient>(
+        dnac: &C,
+        smart_account_id: &str,
+        virtual_account_id: &str,
+    ) -> Result<SavaMapping, PnpError> {
+        let path = format!(
+            "/dna/intent/api/v1/onboarding/pnp-settings/sacct/{smart_account_id}/vacct/{virtual_account_id}/sync-result"
+        );
+        get_pnp_data(dnac, path.as_str()).await
+    }
+}
+
+impl ClaimRequest {
+    fn to_site_claim(&self) -> SiteClaim<'_> {
+        SiteClaim {
+            device_id: &self.device_id,
+            site_id: self.site_id,
+            claim_type: self.claim_type,
+            hostname: self.hostname.as_deref(),
+            image_info: ImageInfo {
+                image_id: self.image_id.map(|id| id.to_string()).unwrap_or_default(),
+                skip: self.image_id.is_none(),
+            },
+            config_info: ConfigInfo {
+                config_id: self
+                    .config
+                    .as_ref()
+                    .map(|c| c.template_id.to_string())
+                    .unwrap_or_default(),
+                config_parameters: self
+                    .config
+                    .iter()
+                    .flat_map(|c| &c.parameters)
+                    .map(|(key, value)| ConfigParameter {
+                        key: key.clone(),
+                        value: value.clone(),
+                    })
+                    .collect(),
+            },
+            top_of_stack_serial_number: self
+                .stack
+                .as_ref()
+                .map(|s| s.top_of_stack_serial_number.as_str()),
+            cabling_scheme: self.stack.as_ref().map(|s| s.cabling_scheme.as_str()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::MockDnac;
+
+    #[tokio::test]
+    async fn test_claim_to_site() {
+        let mock = MockDnac::start().await;
+        mock.mock_response(
+            "POST",
+            "/dna/intent/api/v1/onboarding/pnp-device/site-claim",
+            200,
+            r#"{"response":"Device Claimed","version":"1.0"}"#,
+        )
+        .await;
+
+        let request = ClaimRequest {
+            device_id: "6745e7c5b1e03c2d1c3a8f21".to_string(),
+            site_id: "a4f3a3c2-f103-4f0a-8c5d-46f3d4b5e9d2".parse().unwrap(),
+            claim_type: ClaimType::StackSwitch,
+            hostname: Some("zrh-acc-sw03".to_string()),
+            image_id: None,
+            config: Some(ClaimConfig {
+                template_id: "3a3c2f10-3f0a-4c5d-86f3-d4b5e9d2b5b0".parse().unwrap(),
+                parameters: HashMap::from([("vlan".to_string(), "10".to_string())]),
+            }),
+            stack: Some(StackClaim {
+                top_of_stack_serial_number: "FOC2345X0AB".to_string(),
+                cabling_scheme: "1A".to_string(),
+            }),
+        };
+        PnpDevice::claim_to_site(&mock.dnac, request).await.unwrap();
+
+        let requests = mock.server.received_requests().await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+        assert_eq!(body["type"], "StackSwitch");
+        assert_eq!(body["imageInfo"]["skip"], true);
+        assert_eq!(body["configInfo"]["configParameters"][0]["key"], "vlan");
+        assert_eq!(body["topOfStackSerialNumber"], "FOC2345X0AB");
+    }
+
+    #[tokio::test]
+    async fn test_add_and_recover_devices() {
+        let mock = MockDnac::start().await;
+        mock.mock_response(
+            "POST",
+            "/dna/intent/api/v1/onboarding/pnp-device/import",
+            200,
+            r#"
+              {"successList":[{"id":"6745e7c5b1e03c2d1c3a8f21","deviceInfo":{"serialNumber":"FOC2345X0AB","pid":"C9300-48P","stack":false,"sudiRequired":false,"state":"Unclaimed","onbState":"Not Contacted"}}],
+               "failureList":[{"index":1,"serialNum":"FOC2345X0AC","id":"","msg":"Device with serial number FOC2345X0AC already exists"}]}
+              "#,
+        )
+        .await;
+        for path in ["unclaim", "reset"] {
+            mock.mock_response(
+                "POST",
+                &format!("/dna/intent/api/v1/onboarding/pnp-device/{path}"),
+                200,
+                r#"{"jsonArrayResponse":[],"jsonResponse":{},"message":"Success","statusCode":200}"#,
+            )
+            .await;
+        }
+        mock.mock_response(
+            "DELETE",
+            "/dna/intent/api/v1/onboarding/pnp-device/6745e7c5b1e03c2d1c3a8f21",
+            200,
+            r#"{"id":"6745e7c5b1e03c2d1c3a8f21","deviceInfo":{"serialNumber":"FOC2345X0AB"}}"#,
+        )
+        .await;
+
+        let devices = ["FOC2345X0AB", "FOC2345X0AC"].map(|serial_number| PnpDeviceInfo {
+            serial_number: serial_number.to_string(),
+            pid: Some("C9300-48P".to_string()),
+            ..Default::default()
+        });
+        let import = PnpDevice::add(&mock.dnac, &devices).await.unwrap();
+        assert_eq!(
+            import.success_list[0].device_info.state.as_deref(),
+            Some("Unclaimed")
+        );
+        assert_eq!(import.failure_list[0].index, Some(1));
+
+        let device_id = import.success_list[0].id.as_str();
+        PnpDevice::unclaim(&mock.dnac, &[device_id]).await.unwrap();
+        PnpDevice::reset(&mock.dnac, &[device_id]).await.unwrap();
+        PnpDevice::delete(&mock.dnac, device_id).await.unwrap();
+
+        let requests = mock.server.received_requests().await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+        assert_eq!(body[0]["deviceInfo"]["serialNumber"], "FOC2345X0AB");
+        assert!(body[0]["deviceInfo"].get("state").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_workflows() {
+        let mock = MockDnac::start().await;
+        let workflow = r#"
+          {"id":"674f0e3ac5d46f3d4b5e9d2b","name":"access-switch","description":"image and day 0","tasks":[
+            {"name":"Image Task","type":"Image","taskSeqNo":0,"imageId":"3f0ac5d4-6f3d-4b5e-9d2b-5b0f3a3c2f10","state":"Not Started"},
+            {"name":"Config Task","type":"Config","taskSeqNo":1,"configInfo":{"configId":"3a3c2f10-3f0a-4c5d-86f3-d4b5e9d2b5b0","configParameters":[{"key":"vlan","value":"10"}],"saveToStartUp":true,"connLossRollBack":true}},
+            {"name":"Reload Task","type":"Reload","taskSeqNo":2}],
+           "addToInventory":true,"tenantId":"6307971e4289f95403c86831","version":1}
+          "#;
+        mock.mock_response(
+            "GET",
+            "/dna/intent/api/v1/onboarding/pnp-workflow",
+            200,
+            &format!("[{workflow}]"),
+        )
+        .await;
+        mock.mock_response(
+            "POST",
+            "/dna/intent/api/v1/onboarding/pnp-workflow",
+            200,
+            workflow,
+        )
+        .await;
+        for method in ["PUT", "DELETE"] {
+            mock.mock_response(
+                method,
+                "/dna/intent/api/v1/onboarding/pnp-workflow/674f0e3ac5d46f3d4b5e9d2b",
+                200,
+                workflow,
+            )
+            .await;
+        }
+
+        let workflows = PnpWorkflow::list(&mock.dnac).await.unwrap();
+        assert_eq!(workflows.len(), 1);
+        assert!(matches!(
+            workflows[0].tasks[0].task_type,
+            WorkflowTaskType::Image { .. }
+        ));
+        assert!(matches!(
+            workflows[0].tasks[2].task_type,
+            WorkflowTaskType::Other
+        ));
+
+        let mut new_workflow = PnpWorkflow {
+            id: String::new(),
+            name: "access-switch".to_string(),
+            description: None,
+            tasks: workflows[0].tasks[..2].to_vec(),
+            add_to_inventory: true,
+        };
+        new_workflow = PnpWorkflow::create(&mock.dnac, &new_workflow)
+            .await
+            .unwrap();
+        assert_eq!(new_workflow.id, "674f0e3ac5d46f3d4b5e9d2b");
+
+        PnpWorkflow::update(&mock.dnac, &new_workflow)
+            .await
+            .unwrap();
+        PnpWorkflow::delete(&mock.dnac, &new_workflow.id)
+            .await
+            .unwrap();
+
+        let requests = mock.server.received_requests().await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&requests[1].body).unwrap();
+        assert!(body.get("id").is_none());
+        assert_eq!(body["tasks"][1]["type"], "Config");
+        assert_eq!(
+            body["tasks"][1]["configInfo"]["configParameters"][0]["key"],
+            "vlan"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_settings_and_sync() {
+        let mock = MockDnac::start().await;
+        let mapping = r#"{"smartAccountId":"example.com","virtualAccountId":"Campus","profile":{"name":"dnac-zrh","profileId":"674f0e3ac5d46f3d4b5e9d2c","addressIpV4":"10.0.0.10","port":443,"makeDefault":true},"ccoUser":"netops","syncStatus":"SYNCING","lastSync":1732811427209}"#;
+        mock.mock_response(
+            "GET",
+            "/dna/intent/api/v1/onboarding/pnp-settings",
+            200,
+            &format!(
+                r#"{{"savaMappingList":[{mapping}],"taskTimeOuts":{{"imageDownloadTimeOut":120,"imageInstallTimeOut":30,"configTimeOut":15,"generalTimeOut":10}},"acceptEula":true,"defaultProfile":{{"ip":["10.0.0.10"],"port":443}}}}"#
+            ),
+        )
+        .await;
+        mock.mock_response(
+            "PUT",
+            "/dna/intent/api/v1/onboarding/pnp-settings",
+            200,
+            "{}",
+        )
+        .await;
+        mock.mock_response(
+            "POST",
+            "/dna/intent/api/v1/onboarding/pnp-device/vacct-sync",
+            200,
+            mapping,
+        )
+        .await;
+        mock.mock_response(
+            "GET",
+            "/dna/intent/api/v1/onboarding/pnp-settings/sacct/example.com/vacct/Campus/sync-result",
+            200,
+            &mapping.replace("SYNCING", "SUCCESS"),
+        )
+        .await;
+
+        let mut settings = PnpSettings::get(&mock.dnac).await.unwrap();
+        assert_eq!(settings.sava_mapping_list[0].profile.port, Some(443));
+        settings.task_time_outs.as_mut().unwrap().config_time_out = 30;
+        PnpSettings::update(&mock.dnac, &settings).await.unwrap();
+
+        let requests = mock.server.received_requests().await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&requests[1].body).unwrap();
+        assert_eq!(body["taskTimeOuts"]["configTimeOut"], 30);
+        assert_eq!(body["defaultProfile"]["port"], 443);
+        assert!(body["savaMappingList"][0].get("syncStatus").is_none());
+
+        let mapping = settings.sava_mapping_list.remove(0);
+        let mapping = PnpSettings::sync_virtual_account(&mock.dnac, mapping)
+            .await
+            .unwrap();
+        assert_eq!(mapping.sync_status, Some(SyncStatus::Syncing));
+
+        let mapping = PnpSettings::sync_result(&mock.dnac, "example.com", "Campus")
+            .await
+            .unwrap();
+        assert_eq!(mapping.sync_status, Some(SyncStatus::Success));
+    }
+}
@@ -0,0 +1,101 @@
+use std::future::Future;
+
+use futures::future::join_all;
+
+#[cfg(feature = "devices")]
+use crate::devices::{Device, DeviceError, DeviceFamily};
+use crate::dnac::DNAC;
+
+// DnacPool holds sessions to multiple clusters, each identified by a name
+#[derive(Debug, Default)]
+pub struct DnacPool {
+    clusters: Vec<(String, DNAC)>,
+}
+
+// Tagged wraps a value with the name of the cluster it was fetched from
+#[derive(Debug)]
+pub struct Tagged<T> {
+    pub cluster: String,
+    pub item: T,
+}
+
+impl DnacPool {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // adding a cluster with an existing name replaces the previous session
+    pub fn add(&mut self, name: impl Into<String>, dnac: DNAC) {
+        let name = name.into();
+        if let Some(entry) = self.clusters.iter_mut().find(|(n, _)| *n == name) {
+            entry.1 = dnac;
+        } else {
+            self.clusters.push((name, dnac));
+        }
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<DNAC> {
+        let index = self.clusters.iter().position(|(n, _)| n == name)?;
+        Some(self.clusters.remove(index).1)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&DNAC> {
+        self.clusters
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, dnac)| dnac)
+    }
+
+    pub fn clusters(&self) -> impl Iterator<Item = (&str, &DNAC)> {
+        self.clusters.iter().map(|(n, dnac)| (n.as_str(), dnac))
+    }
+
+    pub fn len(&self) -> usize {
+        self.clusters.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.clusters.is_empty()
+    }
+
+    // runs f concurrently against every cluster and returns the results
+    // tagged with the cluster name, in the order the clusters were added
+    pub async fn for_each_cluster<'a, F, Fut, T>(&'a self, f: F) -> Vec<Tagged<T>>
+    where
+        F: Fn(&'a DNAC) -> Fut,
+        Fut: Future<Output = T>,
+    {
+        let results = join_all(self.clusters.iter().map(|(_, dnac)| f(dnac))).await;
+
+        self.clusters
+            .iter()
+            .zip(results)
+            .map(|((name, _), item)| Tagged {
+                cluster: name.clone(),
+                item,
+            })
+            .collect()
+    }
+
+    // fetches the devices of all clusters, fails if any of the clusters fails
+    #[cfg(feature = "devices")]
+    pub async fn get_all_devices(
+        &self,
+        device_family: Option<DeviceFamily>,
+    ) -> Result<Vec<Tagged<Device>>, DeviceError> {
+        let results = self
+            .for_each_cluster(|dnac| Device::get_all_devices(dnac, device_family.clone()))
+            .await;
+
+        let mut devices = vec![];
+        for result in results {
+            let cluster = result.cluster;
+            devices.extend(result.item?.into_iter().map(|item| Tagged {
+                cluster: cluster.clone(),
+                item,
+            }));
+        }
+
+        Ok(devices)
+    }
+}
@@ -0,0 +1,291 @@
+use std::fs;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use thiserror::Error;
+use tracing::{event, Level};
+
+use crate::{
+    dnac::{ApiClient, ResponseType},
+    sites::{SiteFilter, Sites},
+};
+
+// ClusterProfile is a serializable snapshot of the global configuration of a
+// cluster which can be applied to another cluster. Credentials are only
+// captured as references as the API never returns the secrets.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ClusterProfile {
+    pub name: String,
+    pub captured_at: i64,
+    pub network_settings: Vec<NetworkSetting>,
+    pub credentials: Vec<CredentialReference>,
+    pub ssids: Vec<Ssid>,
+    pub templates: Vec<String>,
+}
+
+// ProfileSelection controls which parts of the configuration get captured
+#[derive(Debug, Default)]
+pub struct ProfileSelection {
+    pub network_settings: bool,
+    pub credentials: bool,
+    pub ssids: bool,
+    pub templates: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NetworkSetting {
+    pub namespace: Option<String>,
+    #[serde(rename = "type")]
+    pub setting_type: Option<String>,
+    pub key: String,
+    #[serde(default)]
+    pub value: Vec<Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CredentialReference {
+    pub credential_type: String,
+    pub description: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Ssid {
+    pub name: String,
+    #[serde(rename = "securityLevel")]
+    pub security_level: Option<String>,
+    #[serde(rename = "enableFastLane", default)]
+    pub enable_fast_lane: bool,
+    #[serde(rename = "enableMACFiltering", default)]
+    pub enable_mac_filtering: bool,
+    #[serde(rename = "trafficType")]
+    pub traffic_type: Option<String>,
+    #[serde(rename = "radioPolicy")]
+    pub radio_policy: Option<String>,
+    #[serde(rename = "enableBroadcastSSID", default)]
+    pub enable_broadcast_ssid: bool,
+    #[serde(rename = "fastTransition")]
+    pub fast_transition: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct EnterpriseSsid {
+    #[serde(rename = "ssidDetails", default)]
+    ssid_details: Vec<Ssid>,
+}
+
+// ApplyReport lists what was applied to the target cluster and what needs
+// manual attention
+#[derive(Debug, Default)]
+pub struct ApplyReport {
+    pub applied: Vec<String>,
+    pub missing_credentials: Vec<CredentialReference>,
+    pub skipped: Vec<String>,
+    pub errors: Vec<String>,
+}
+
+#[derive(Debug, Error)]
+pub enum ProfileError {
+    #[error("General Profile Error")]
+    GeneralError,
+    #[error("Global site not found")]
+    GlobalSiteNotFound,
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Serde(#[from] serde_json::Error),
+}
+
+impl ClusterProfile {
+    pub async fn capture<C: ApiClient>(
+        dnac: &C,
+        name: impl Into<String>,
+        selection: ProfileSelection,
+    ) -> Result<Self, ProfileError> {
+        let mut profile = ClusterProfile {
+            name: name.into(),
+            captured_at: chrono::offset::Local::now().timestamp(),
+            templates: selection.templates,
+            ..Default::default()
+        };
+
+        if selection.network_settings {
+            profile.network_settings = get_network_settings(dnac).await?;
+        }
+
+        if selection.credentials {
+            profile.credentials = get_credential_references(dnac).await?;
+        }
+
+        if selection.ssids {
+            profile.ssids = get_ssids(dnac).await?;
+        }
+
+        Ok(profile)
+    }
+
+    // apply pushes the profile to the target cluster, failures of single
+    // items are collected in the report instead of aborting the run
+    pub async fn apply<C: ApiClient>(&self, dnac: &C) -> Result<ApplyReport, ProfileError> {
+        let mut report = ApplyReport::default();
+
+        if !self.network_settings.is_empty() {
+            let global = Sites::get_site(dnac, Some(SiteFilter::Name("Global".to_string())), None)
+                .await
+                .map_err(|_| ProfileError::GlobalSiteNotFound)?
+                .into_iter()
+                .next()
+                .ok_or(ProfileError::GlobalSiteNotFound)?;
+
+            let path = format!("/dna/intent/api/v1/network/{}", global.id);
+            let settings = json!({ "settings": self.network_settings_payload() });
+            match dnac.post(path.as_str(), settings, true).await {
+                Ok(_) => report.applied.push("network settings".to_string()),
+                Err(e) => report.errors.push(format!("network settings: {e}")),
+            }
+        }
+
+        if !self.credentials.is_empty() {
+            let existing = get_credential_references(dnac).await?;
+            report.missing_credentials = self
+                .credentials
+                .iter()
+                .filter(|c| !existing.contains(c))
+                .cloned()
+                .collect();
+        }
+
+        for ssid in &self.ssids {
+            // the passphrase is never returned by the API, PSK SSIDs have to be
+            // created manually
+            if ssid.security_level.as_deref() == Some("WPA2_PERSONAL") {
+                report.skipped.push(format!("ssid {}", ssid.name));
+                continue;
+            }
+
+            let path = "/dna/intent/api/v1/enterprise-ssid";
+            match dnac.post(path, ssid, true).await {
+                Ok(_) => report.applied.push(format!("ssid {}", ssid.name)),
+                Err(e) => report.errors.push(format!("ssid {}: {e}", ssid.name)),
+            }
+        }
+
+        // templates are only recorded by name, they have to be synced separately
+        for template in &self.templates {
+            report.skipped.push(format!("template {template}"));
+        }
+
+        Ok(report)
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), ProfileError> {
+        let file = fs::File::create(path)?;
+        serde_json::to_writer_pretty(file, self)?;
+
+        Ok(())
+    }
+
+    pub fn load(path: &str) -> Result<Self, ProfileError> {
+        let file = fs::File::open(path)?;
+        let profile = serde_json::from_reader(file)?;
+
+        Ok(profile)
+    }
+
+    // maps the captured key/value settings to the payload of the network update endpoint
+    fn network_settings_payload(&self) -> Map<String, Value> {
+        let mut settings = Map::new();
+        for setting in &self.network_settings {
+            let first = setting.value.first().cloned().unwrap_or(Value::Null);
+            match setting.key.as_str() {
+                "dhcp.server" => {
+                    settings.insert("dhcpServer".to_string(), Value::from(setting.value.clone()));
+                }
+                "ntp.server" => {
+                    settings.insert("ntpServer".to_string(), Value::from(setting.value.clone()));
+                }
+                "dns.server" => {
+                    settings.insert("dnsServer".to_string(), first);
+                }
+                "timezone.site" => {
+                    settings.insert("timezone".to_string(), first);
+                }
+                "device.banner" => {
+                    settings.insert("messageOfTheday".to_string(), first);
+                }
+                _ => event!(
+                    Level::DEBUG,
+                    "Network setting {} is not supported by profiles",
+                    setting.key
+                ),
+            }
+        }
+
+        settings
+    }
+}
+
+async fn get_network_settings<C: ApiClient>(dnac: &C) -> Result<Vec<NetworkSetting>, ProfileError> {
+    let path = "/dna/intent/api/v1/network";
+
+    match dnac.get::<NetworkSetting>(path, None, None).await {
+        Ok(data) => match data.response {
+            ResponseType::Array(data) => Ok(data),
+            ResponseType::Item(data) => Ok(vec![data]),
+        },
+        Err(e) => {
+            event!(Level::ERROR, "{e}");
+            Err(ProfileError::GeneralError)
+        }
+    }
+}
+
+async fn get_credential_references<C: ApiClient>(
+    dnac: &C,
+) -> Result<Vec<CredentialReference>, ProfileError> {
+    let path = "/dna/intent/api/v2/global-credential";
+
+    let credentials = match dnac.get::<Map<String, Value>>(path, None, None).await {
+        Ok(data) => match data.response {
+            ResponseType::Item(data) => data,
+            ResponseType::Array(_) => return Err(ProfileError::GeneralError),
+        },
+        Err(e) => {
+            event!(Level::ERROR, "{e}");
+            return Err(ProfileError::GeneralError);
+        }
+    };
+
+    // the response is grouped by credential type, e.g. cliCredential or snmpV3
+    Ok(credentials
+        .iter()
+        .filter_map(|(credential_type, entries)| {
+            entries.as_array().map(|entries| (credential_type, entries))
+        })
+        .flat_map(|(credential_type, entries)| {
+            entries.iter().map(|entry| CredentialReference {
+                credential_type: credential_type.clone(),
+                description: entry["description"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_string(),
+            })
+        })
+        .collect())
+}
+
+async fn get_ssids<C: ApiClient>(dnac: &C) -> Result<Vec<Ssid>, ProfileError> {
+    let path = "/dna/intent/api/v1/enterprise-ssid";
+
+    match dnac.get::<EnterpriseSsid>(path, None, None).await {
+        Ok(data) => match data.response {
+            ResponseType::Array(data) => {
+                Ok(data.into_iter().flat_map(|s| s.ssid_details).collect())
+            }
+            ResponseType::Item(data) => Ok(data.ssid_details),
+        },
+        Err(e) => {
+            event!(Level::ERROR, "{e}");
+            Err(ProfileError::GeneralError)
+        }
+    }
+}
@@ -0,0 +1,96 @@
+use std::fmt;
+
+// Query collects the query parameters of a request. Parameters are kept in
+// the order they are added and adding a key twice sends it twice, which DNAC
+// treats as an OR of the values (e.g. family=Switches&family=Routers).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Query<'a> {
+    params: Vec<(&'a str, String)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortOrder {
+    Asc,
+    Desc,
+}
+
+impl fmt::Display for SortOrder {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SortOrder::Asc => write!(f, "asc"),
+            SortOrder::Desc => write!(f, "desc"),
+        }
+    }
+}
+
+impl<'a> Query<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn eq(mut self, key: &'a str, value: impl ToString) -> Self {
+        self.params.push((key, value.to_string()));
+        self
+    }
+
+    // only adds the parameter if a value is given
+    pub fn eq_opt(self, key: &'a str, value: Option<impl ToString>) -> Self {
+        match value {
+            Some(value) => self.eq(key, value),
+            None => self,
+        }
+    }
+
+    // adds the key once per value
+    pub fn any_of<V: ToString>(
+        mut self,
+        key: &'a str,
+        values: impl IntoIterator<Item = V>,
+    ) -> Self {
+        self.params
+            .extend(values.into_iter().map(|v| (key, v.to_string())));
+        self
+    }
+
+    pub fn sort_by(self, field: &'a str, order: SortOrder) -> Self {
+        self.eq("sortBy", field).eq("order", order)
+    }
+
+    // limits the attributes returned, on endpoints which support it
+    pub fn fields(self, fields: &[&str]) -> Self {
+        self.eq("fields", fields.join(","))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.params.is_empty()
+    }
+
+    pub fn as_slice(&self) -> &[(&'a str, String)] {
+        &self.params
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_query() {
+        let query = Query::new()
+            .any_of("family", ["Switches and Hubs", "Routers"])
+            .eq_opt("hostname", None::<String>)
+            .sort_by("hostname", SortOrder::Desc)
+            .fields(&["id", "hostname"]);
+
+        assert_eq!(
+            query.as_slice(),
+            &[
+                ("family", "Switches and Hubs".to_string()),
+                ("family", "Routers".to_string()),
+                ("sortBy", "hostname".to_string()),
+                ("order", "desc".to_string()),
+                ("fields", "id,hostname".to_string()),
+            ]
+        );
+    }
+}
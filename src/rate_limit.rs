@@ -0,0 +1,256 @@
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use tracing::{event, Level};
+
+// EndpointGroup is the group an endpoint is rate limited by, DNAC applies
+// its limits per API family rather than per endpoint
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EndpointGroup {
+    Inventory,
+    CommandRunner,
+    Assurance,
+    Other,
+}
+
+// RateLimit allows requests per period, with bursts of up to requests
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    pub requests: u32,
+    pub period: Duration,
+}
+
+// RateLimiter is a token bucket per endpoint group, groups without a limit
+// are not throttled
+#[derive(Debug, Default)]
+pub struct RateLimiter {
+    buckets: HashMap<EndpointGroup, Mutex<Bucket>>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    limit: RateLimit,
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl EndpointGroup {
+    pub fn for_path(path: &str) -> Self {
+        let path = strip_api_prefix(path);
+
+        // the command runner lives below network-device, so it is matched first
+        if path.starts_with("network-device-poller/cli") {
+            EndpointGroup::CommandRunner
+        } else if path.starts_with("network-device") || path.starts_with("interface") {
+            EndpointGroup::Inventory
+        } else if [
+            "device-health",
+            "client-health",
+            "client-detail",
+            "network-health",
+            "site-health",
+            "issues",
+        ]
+        .iter()
+        .any(|p| path.starts_with(p))
+        {
+            EndpointGroup::Assurance
+        } else {
+            EndpointGroup::Other
+        }
+    }
+}
+
+// the groups don't depend on the API family or version, so everything up to
+// /dna/{intent,data,system}/api/v{n}/ is removed
+fn strip_api_prefix(path: &str) -> &str {
+    let stripped = path
+        .strip_prefix("/dna/")
+        .and_then(|p| {
+            ["intent/", "data/", "system/"]
+                .iter()
+                .find_map(|family| p.strip_prefix(family))
+        })
+        .and_then(|p| p.strip_prefix("api/v"))
+        .and_then(|p| p.split_once('/'))
+        .filter(|(version, _)| !version.is_empty() && version.chars().all(|c| c.is_ascii_digit()))
+        .map(|(_, rest)| rest);
+
+    stripped.unwrap_or(path)
+}
+
+impl RateLimit {
+    pub fn per_minute(requests: u32) -> Self {
+        Self {
+            requests,
+            period: Duration::from_secs(60),
+        }
+    }
+}
+
+impl RateLimiter {
+    // conservative limits which keep bulk jobs below the limits of the
+    // inventory, command runner and assurance APIs
+    pub fn with_default_limits() -> Self {
+        let mut limiter = Self::default();
+        limiter.set_limit(EndpointGroup::Inventory, Some(RateLimit::per_minute(100)));
+        limiter.set_limit(EndpointGroup::CommandRunner, Some(RateLimit::per_minute(5)));
+        limiter.set_limit(EndpointGroup::Assurance, Some(RateLimit::per_minute(100)));
+        limiter
+    }
+
+    // None removes the limit of the group, as does a limit without requests
+    // or period since no token could ever be refilled
+    pub fn set_limit(&mut self, group: EndpointGroup, limit: Option<RateLimit>) {
+        match limit {
+            Some(limit) if limit.requests == 0 || limit.period.is_zero() => {
+                event!(
+                    Level::WARN,
+                    "Ignoring rate limit {limit:?} of {group:?}, the group is not throttled"
+                );
+                self.buckets.remove(&group);
+            }
+            Some(limit) => {
+                self.buckets.insert(
+                    group,
+                    Mutex::new(Bucket {
+                        limit,
+                        tokens: limit.requests as f64,
+                        last_refill: Instant::now(),
+                    }),
+                );
+            }
+            None => {
+                self.buckets.remove(&group);
+            }
+        }
+    }
+
+    pub fn limit(&self, group: EndpointGroup) -> Option<RateLimit> {
+        self.buckets.get(&group).map(|b| b.lock().unwrap().limit)
+    }
+
+    // waits until the group of the path has a token left
+    pub async fn acquire(&self, path: &str) {
+        let group = EndpointGroup::for_path(path);
+        let Some(bucket) = self.buckets.get(&group) else {
+            return;
+        };
+
+        loop {
+            let wait = match bucket.lock().unwrap().try_take() {
+                None => return,
+                Some(wait) => wait,
+            };
+
+            event!(
+                Level::DEBUG,
+                "Rate limit of {group:?} reached, waiting {}ms",
+                wait.as_millis()
+            );
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+impl Bucket {
+    // takes a token, or returns how long it takes until one is available
+    fn try_take(&mut self) -> Option<Duration> {
+        let capacity = self.limit.requests as f64;
+        let per_sec = capacity / self.limit.period.as_secs_f64();
+
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * per_sec).min(capacity);
+        self.last_refill = now;
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            return None;
+        }
+
+        Some(Duration::from_secs_f64((1.0 - self.tokens) / per_sec))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_endpoint_group() {
+        assert_eq!(
+            EndpointGroup::for_path("/dna/intent/api/v1/network-device/module"),
+            EndpointGroup::Inventory
+        );
+        assert_eq!(
+            EndpointGroup::for_path("/dna/intent/api/v1/network-device-poller/cli/read-request"),
+            EndpointGroup::CommandRunner
+        );
+        assert_eq!(
+            EndpointGroup::for_path("/dna/intent/api/v1/site-health"),
+            EndpointGroup::Assurance
+        );
+        assert_eq!(
+            EndpointGroup::for_path("/dna/intent/api/v2/site"),
+            EndpointGroup::Other
+        );
+        assert_eq!(
+            EndpointGroup::for_path("/dna/intent/api/v2/network-device"),
+            EndpointGroup::Inventory
+        );
+        assert_eq!(
+            EndpointGroup::for_path("/dna/data/api/v1/clientHealth"),
+            EndpointGroup::Other
+        );
+        assert_eq!(
+            EndpointGroup::for_path("/dna/data/api/v1/issues"),
+            EndpointGroup::Assurance
+        );
+        assert_eq!(
+            EndpointGroup::for_path("/dna/system/api/v1/network-device"),
+            EndpointGroup::Inventory
+        );
+        assert_eq!(
+            EndpointGroup::for_path("/dna/intent/api/vx/network-device"),
+            EndpointGroup::Other
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rate_limiter() {
+        let mut limiter = RateLimiter::default();
+        limiter.set_limit(
+            EndpointGroup::Inventory,
+            Some(RateLimit {
+                requests: 2,
+                period: Duration::from_millis(200),
+            }),
+        );
+
+        let start = Instant::now();
+        for _ in 0..3 {
+            limiter.acquire("/dna/intent/api/v1/network-device").await;
+        }
+        // the burst of two passes, the third waits for a refill
+        assert!(start.elapsed() >= Duration::from_millis(90));
+
+        let start = Instant::now();
+        limiter.acquire("/dna/intent/api/v2/site").await;
+        assert!(start.elapsed() < Duration::from_millis(50));
+    }
+
+    #[tokio::test]
+    async fn test_zero_limit() {
+        let mut limiter = RateLimiter::with_default_limits();
+        limiter.set_limit(EndpointGroup::Inventory, Some(RateLimit::per_minute(0)));
+        assert_eq!(limiter.limit(EndpointGroup::Inventory), None);
+
+        let start = Instant::now();
+        limiter.acquire("/dna/intent/api/v1/network-device").await;
+        assert!(start.elapsed() < Duration::from_millis(50));
+    }
+}
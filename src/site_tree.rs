@@ -0,0 +1,212 @@
+use std::collections::HashMap;
+
+use crate::{
+    devices::DeviceFamily,
+    dnac::ApiClient,
+    ids::SiteId,
+    sites::{Site, SiteError, Sites},
+};
+
+// SiteTree indexes the flat site list by parent, the parent of a site is
+// the second to last id of its group_hierarchy
+#[derive(Debug)]
+pub struct SiteTree {
+    sites: Vec<Site>,
+    index: HashMap<SiteId, usize>,
+    by_hierarchy: HashMap<String, usize>,
+    children: HashMap<SiteId, Vec<usize>>,
+}
+
+#[derive(Debug)]
+pub struct BuildingSummary {
+    pub building_id: SiteId,
+    pub name: String,
+    pub group_name_hierarchy: String,
+    pub floor_count: usize,
+    pub ap_count: usize,
+    pub devices_by_family: HashMap<DeviceFamily, usize>,
+}
+
+impl SiteTree {
+    pub fn new(sites: Vec<Site>) -> Self {
+        let index = sites.iter().enumerate().map(|(i, s)| (s.id, i)).collect();
+        let by_hierarchy = sites
+            .iter()
+            .enumerate()
+            .map(|(i, s)| (s.group_name_hierarchy.clone(), i))
+            .collect();
+        let mut children: HashMap<SiteId, Vec<usize>> = HashMap::new();
+        for (i, site) in sites.iter().enumerate() {
+            if let Some(parent) = parent_id(site) {
+                children.entry(parent).or_default().push(i);
+            }
+        }
+
+        Self {
+            sites,
+            index,
+            by_hierarchy,
+            children,
+        }
+    }
+
+    pub async fn fetch<C: ApiClient>(dnac: &C) -> Result<Self, SiteError> {
+        Ok(Self::new(Sites::get_all_sites(dnac, None).await?))
+    }
+
+    pub fn get(&self, site_id: SiteId) -> Option<&Site> {
+        self.index.get(&site_id).map(|i| &self.sites[*i])
+    }
+
+    pub fn children(&self, site_id: SiteId) -> impl Iterator<Item = &Site> {
+        self.children
+            .get(&site_id)
+            .into_iter()
+            .flatten()
+            .map(|i| &self.sites[*i])
+    }
+
+    pub fn parent(&self, site_id: SiteId) -> Option<&Site> {
+        self.get(site_id)
+            .and_then(parent_id)
+            .and_then(|parent| self.get(parent))
+    }
+
+    // sites without a parent in the tree, usually only Global
+    pub fn roots(&self) -> impl Iterator<Item = &Site> {
+        self.sites
+            .iter()
+            .filter(|s| parent_id(s).is_none_or(|parent| !self.index.contains_key(&parent)))
+    }
+
+    // hierarchy is the full name of the site, e.g. "Global/EMEA/Zurich"
+    pub fn find_by_hierarchy(&self, hierarchy: &str) -> Option<&Site> {
+        self.by_hierarchy
+            .get(hierarchy.trim_end_matches('/'))
+            .map(|i| &self.sites[*i])
+    }
+
+    // the site and all its descendants, parents come before their children
+    pub fn subtree(&self, site_id: SiteId) -> impl Iterator<Item = &Site> {
+        let mut stack: Vec<usize> = self.index.get(&site_id).copied().into_iter().collect();
+        std::iter::from_fn(move || {
+            let i = stack.pop()?;
+            let site = &self.sites[i];
+            if let Some(children) = self.children.get(&site.id) {
+                stack.extend(children.iter().rev());
+            }
+            Some(site)
+        })
+    }
+
+    // Global has depth 0, its direct children 1 and so on
+    pub fn depth(&self, site_id: SiteId) -> Option<usize> {
+        self.get(site_id).map(depth)
+    }
+
+    pub fn at_depth(&self, level: usize) -> impl Iterator<Item = &Site> {
+        self.sites.iter().filter(move |s| depth(s) == level)
+    }
+
+    pub fn buildings(&self) -> impl Iterator<Item = &Site> {
+        self.sites
+            .iter()
+            .filter(|s| s.get_location_type() == "building")
+    }
+
+    // computes a summary per building, the devices are counted from the
+    // membership of the building and all its floors
+    pub async fn building_summaries<C: ApiClient>(
+        &self,
+        dnac: &C,
+    ) -> Result<Vec<BuildingSummary>, SiteError> {
+        let mut summaries = vec![];
+
+        for building in self.buildings() {
+            let floors: Vec<&Site> = self
+                .children(building.id)
+                .filter(|s| s.get_location_type() == "floor")
+                .collect();
+
+            let mut devices_by_family: HashMap<DeviceFamily, usize> = HashMap::new();
+            for site_id in std::iter::once(building.id).chain(floors.iter().map(|f| f.id)) {
+                for device in Sites::get_site_membership(dnac, site_id, None).await? {
+                    if let Some(family) = device.family {
+                        *devices_by_family.entry(family).or_default() += 1;
+                    }
+                }
+            }
+
+            summaries.push(BuildingSummary {
+                building_id: building.id,
+                name: building.name.clone(),
+                group_name_hierarchy: building.group_name_hierarchy.clone(),
+                floor_count: floors.len(),
+                ap_count: devices_by_family
+                    .get(&DeviceFamily::UnifiedAp)
+                    .copied()
+                    .unwrap_or_default(),
+                devices_by_family,
+            });
+        }
+
+        Ok(summaries)
+    }
+}
+
+fn depth(site: &Site) -> usize {
+    site.group_hierarchy.split('/').count().saturating_sub(1)
+}
+
+fn parent_id(site: &Site) -> Option<SiteId> {
+    let mut ids = site.group_hierarchy.rsplit('/');
+    ids.next();
+    ids.next().and_then(|id| id.parse().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dnac::{Response, ResponseType};
+    use crate::test_util::fixtures;
+
+    #[test]
+    fn test_site_tree() {
+        let sites: Response<Site> = serde_json::from_str(fixtures::SITES).unwrap();
+        let ResponseType::Array(sites) = sites.response else {
+            panic!("expected a list of sites");
+        };
+        let sites = sites.into_iter().map(|s| s.parse().unwrap()).collect();
+
+        let tree = SiteTree::new(sites);
+        let buildings: Vec<&Site> = tree.buildings().collect();
+        assert_eq!(buildings.len(), 1);
+        assert_eq!(buildings[0].name, "Zurich");
+        let (latitude, _) = buildings[0].coordinates().unwrap();
+        assert_eq!(latitude, 47.3686);
+    }
+
+    #[test]
+    fn test_navigation() {
+        let sites = r#"
+          [{"id":"00000000-0000-4000-8000-000000000001","name":"Global","groupNameHierarchy":"Global","groupHierarchy":"00000000-0000-4000-8000-000000000001"},
+           {"id":"00000000-0000-4000-8000-000000000002","name":"EMEA","groupNameHierarchy":"Global/EMEA","groupHierarchy":"00000000-0000-4000-8000-000000000001/00000000-0000-4000-8000-000000000002"},
+           {"id":"00000000-0000-4000-8000-000000000003","name":"Zurich","groupNameHierarchy":"Global/EMEA/Zurich","groupHierarchy":"00000000-0000-4000-8000-000000000001/00000000-0000-4000-8000-000000000002/00000000-0000-4000-8000-000000000003"},
+           {"id":"00000000-0000-4000-8000-000000000004","name":"Geneva","groupNameHierarchy":"Global/EMEA/Geneva","groupHierarchy":"00000000-0000-4000-8000-000000000001/00000000-0000-4000-8000-000000000002/00000000-0000-4000-8000-000000000004"}]
+          "#;
+        let sites: Vec<Site> = serde_json::from_str(sites).unwrap();
+        let tree = SiteTree::new(sites);
+
+        let zurich = tree.find_by_hierarchy("Global/EMEA/Zurich/").unwrap();
+        assert_eq!(tree.depth(zurich.id), Some(2));
+        assert_eq!(tree.parent(zurich.id).unwrap().name, "EMEA");
+
+        let roots: Vec<&str> = tree.roots().map(|s| s.name.as_str()).collect();
+        assert_eq!(roots, vec!["Global"]);
+
+        let emea = tree.find_by_hierarchy("Global/EMEA").unwrap();
+        let subtree: Vec<&str> = tree.subtree(emea.id).map(|s| s.name.as_str()).collect();
+        assert_eq!(subtree, vec!["EMEA", "Zurich", "Geneva"]);
+        assert_eq!(tree.at_depth(2).count(), 2);
+    }
+}
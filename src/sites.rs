@@ -1,27 +1,47 @@
 use core::fmt;
 use std::error::Error;
+use std::str::FromStr;
 
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
+use tokio_util::sync::CancellationToken;
 use tracing::{event, Level};
-use uuid::Uuid;
 
 use crate::{
     devices::MembershipDevice,
-    dnac::{ApiError, Pagination, DNAC},
+    dnac::{
+        with_cancel, ApiClient, ApiClientExt, ApiError, Cancelled, Pagination, PaginationError,
+        Response, ResponseType,
+    },
+    ids::SiteId,
+    query::Query,
 };
 
 pub struct Sites;
 
+// SitesV2 wraps the sites API of Catalyst Center 2.3.7.6, which returns the
+// coordinates and the parent of a site directly
+pub struct SitesV2;
+
+// versions which provide the sites API
+const SITES_V2_VERSIONS: [&str; 1] = ["2.3.7.6"];
+
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct Site {
-    pub id: Uuid,
+    pub id: SiteId,
     pub group_name_hierarchy: String,
     pub group_hierarchy: String,
     pub name: String,
     pub location: Option<Location>,
     pub additional_info: Option<Vec<serde_json::Value>>,
+    // the fields below are filled by parse from the additional_info
+    #[serde(skip)]
+    pub site_type: Option<SiteType>,
+    #[serde(skip)]
+    pub floor: Option<FloorGeometry>,
+    #[serde(skip)]
+    pub building: Option<BuildingAttributes>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -36,30 +56,383 @@ pub struct Location {
     longitude: Option<String>,
 }
 
+// FloorGeometry combines the mapGeometry and mapsSummary namespaces of a
+// floor, the dimensions are in feet
+#[derive(Debug, Clone, PartialEq)]
+pub struct FloorGeometry {
+    pub width: f64,
+    pub length: f64,
+    pub height: f64,
+    pub offset_x: f64,
+    pub offset_y: f64,
+    pub rf_model: Option<RfModel>,
+    pub floor_index: Option<i32>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BuildingAttributes {
+    pub address: Option<String>,
+    pub country: Option<String>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+}
+
+// the legacy API returns all attributes as strings
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MapGeometry {
+    #[serde(deserialize_with = "lossy_f64")]
+    width: f64,
+    #[serde(deserialize_with = "lossy_f64")]
+    length: f64,
+    #[serde(deserialize_with = "lossy_f64")]
+    height: f64,
+    #[serde(default, deserialize_with = "lossy_f64")]
+    offset_x: f64,
+    #[serde(default, deserialize_with = "lossy_f64")]
+    offset_y: f64,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MapsSummary {
+    rf_model: Option<String>,
+    floor_index: Option<String>,
+}
+
 // name: siteNameHierarchy (ex: global/groupName)
 // id: Site id to which site details to retrieve.
 // type (ex: area, building, floor)
 pub enum SiteFilter {
     Name(String),
-    SiteID(Uuid),
+    SiteID(SiteId),
     Type(SiteType),
 }
 
+// SiteMembership holds the site and its child sites together with the
+// devices assigned to each of them
+#[derive(Deserialize, Debug)]
+pub struct SiteMembership {
+    #[serde(rename = "site", deserialize_with = "membership_sites")]
+    pub sites: Vec<Site>,
+    #[serde(rename = "device", default)]
+    pub devices: Vec<SiteDevices>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct SiteDevices {
+    #[serde(rename = "siteId")]
+    pub site_id: SiteId,
+    #[serde(rename = "response", default)]
+    pub devices: Vec<MembershipDevice>,
+}
+
+// CreateSite describes a new site, parent_name is the hierarchy of the
+// parent site, e.g. Global/CH/ZRH
+#[derive(Debug, Clone)]
+pub enum CreateSite {
+    Area(NewArea),
+    Building(NewBuilding),
+    Floor(NewFloor),
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct NewArea {
+    pub name: String,
+    pub parent_name: String,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct NewBuilding {
+    pub name: String,
+    pub parent_name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub country: Option<String>,
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+// the dimensions are in feet
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct NewFloor {
+    pub name: String,
+    pub parent_name: String,
+    pub rf_model: RfModel,
+    pub width: f64,
+    pub length: f64,
+    pub height: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub floor_number: Option<i32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RfModel {
+    #[serde(rename = "Cubes And Walled Offices")]
+    CubesAndWalledOffices,
+    #[serde(rename = "Drywall Office Only")]
+    DrywallOfficeOnly,
+    #[serde(rename = "Indoor High Ceiling")]
+    IndoorHighCeiling,
+    #[serde(rename = "Outdoor Open Space")]
+    OutdoorOpenSpace,
+}
+
+// UpdateSite changes the given fields of a site, the type of a site can't
+// be changed
+#[derive(Debug, Clone)]
+pub enum UpdateSite {
+    Area(UpdateArea),
+    Building(UpdateBuilding),
+    Floor(UpdateFloor),
+}
+
+#[derive(Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateArea {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent_name: Option<String>,
+}
+
+#[derive(Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateBuilding {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub country: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latitude: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub longitude: Option<f64>,
+}
+
+#[derive(Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateFloor {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rf_model: Option<RfModel>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub width: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub length: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub height: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub floor_number: Option<i32>,
+}
+
+impl Serialize for CreateSite {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            CreateSite::Area(area) => serialize_site(serializer, SiteType::Area, area),
+            CreateSite::Building(building) => {
+                serialize_site(serializer, SiteType::Building, building)
+            }
+            CreateSite::Floor(floor) => serialize_site(serializer, SiteType::Floor, floor),
+        }
+    }
+}
+
+impl Serialize for UpdateSite {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            UpdateSite::Area(area) => serialize_site(serializer, SiteType::Area, area),
+            UpdateSite::Building(building) => {
+                serialize_site(serializer, SiteType::Building, building)
+            }
+            UpdateSite::Floor(floor) => serialize_site(serializer, SiteType::Floor, floor),
+        }
+    }
+}
+
+// the API nests the site below its type, e.g. {"type": "area", "site": {"area": {..}}}
+fn serialize_site<S, T>(serializer: S, site_type: SiteType, site: &T) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+    T: Serialize,
+{
+    use serde::ser::SerializeMap;
+
+    let site_type = site_type.to_string();
+    let site = serde_json::to_value(site).map_err(serde::ser::Error::custom)?;
+
+    let mut map = serializer.serialize_map(Some(2))?;
+    map.serialize_entry("type", &site_type)?;
+    map.serialize_entry("site", &serde_json::json!({ site_type.as_str(): site }))?;
+    map.end()
+}
+
+#[derive(Serialize, Debug)]
+struct AssignDevices<'a> {
+    device: Vec<DeviceIp<'a>>,
+}
+
+#[derive(Serialize, Debug)]
+struct DeviceIp<'a> {
+    ip: &'a str,
+}
+
+// CredentialAssignment holds the ids of the global credentials which are
+// used for the devices of the site, credentials which are None aren't changed
+#[derive(Debug, Default, Clone)]
+pub struct CredentialAssignment {
+    pub cli_id: Option<String>,
+    pub snmp_v2_read_id: Option<String>,
+    pub snmp_v2_write_id: Option<String>,
+    pub snmp_v3_id: Option<String>,
+    pub http_read_id: Option<String>,
+    pub http_write_id: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct CredentialToSite<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cli_id: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    snmp_v2_read_id: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    snmp_v2_write_id: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    snmp_v3_id: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    http_read: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    http_write: Option<&'a str>,
+}
+
+// the device credential settings of the new sites API wrap every id
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct DeviceCredentialSettings<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cli_credentials_id: Option<CredentialsId<'a>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    snmpv2c_read_credentials_id: Option<CredentialsId<'a>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    snmpv2c_write_credentials_id: Option<CredentialsId<'a>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    snmpv3_credentials_id: Option<CredentialsId<'a>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    http_read_credentials_id: Option<CredentialsId<'a>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    http_write_credentials_id: Option<CredentialsId<'a>>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct CredentialsId<'a> {
+    credentials_id: &'a str,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SiteV2 {
+    pub id: SiteId,
+    pub parent_id: Option<SiteId>,
+    pub name: String,
+    pub name_hierarchy: String,
+    // global, area, building or floor
+    #[serde(rename = "type")]
+    pub site_type: String,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub address: Option<String>,
+    pub country: Option<String>,
+    pub floor_number: Option<i32>,
+    pub rf_model: Option<RfModel>,
+    pub width: Option<f64>,
+    pub length: Option<f64>,
+    pub height: Option<f64>,
+    // feet or meters
+    pub units_of_measure: Option<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SiteV2Filter {
+    pub name: Option<String>,
+    pub name_hierarchy: Option<String>,
+    pub site_type: Option<SiteType>,
+}
+
+// CreateSiteV2 is a site for SitesV2::create, only the fields of its type are used
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateSiteV2 {
+    #[serde(rename = "type", serialize_with = "serialize_display")]
+    pub site_type: SiteType,
+    pub name: String,
+    pub parent_name_hierarchy: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub country: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latitude: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub longitude: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub floor_number: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rf_model: Option<RfModel>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub width: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub length: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub height: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub units_of_measure: Option<String>,
+}
+
 pub enum SiteMembershipFilter {
     MemberType(String),
 }
 
-#[derive(Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SiteType {
     Area,
     Building,
     Floor,
 }
 
+// Strict fails the whole request on the first site which can't be parsed,
+// Lossy skips it and reports it in LossySites::skipped
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum ParseMode {
+    #[default]
+    Strict,
+    Lossy,
+}
+
+// the sites which could be parsed and a MalformedSite error per skipped site
+#[derive(Debug, Default)]
+pub struct LossySites {
+    pub sites: Vec<Site>,
+    pub skipped: Vec<SiteError>,
+}
+
 #[derive(Debug)]
 pub enum SiteError {
     GeneralError,
     InvalidSite,
+    // the additional_info of the site couldn't be parsed
+    MalformedSite(SiteId, String),
+    PaginationOutOfRange(PaginationError),
+    Cancelled,
 }
 
 impl fmt::Display for SiteError {
@@ -67,40 +440,76 @@ impl fmt::Display for SiteError {
         match self {
             SiteError::GeneralError => write!(f, "General Site Error"),
             SiteError::InvalidSite => write!(f, "Invalid Site"),
+            SiteError::MalformedSite(id, reason) => write!(f, "Malformed Site {id}: {reason}"),
+            SiteError::PaginationOutOfRange(e) => write!(f, "{e}"),
+            SiteError::Cancelled => write!(f, "{Cancelled}"),
         }
     }
 }
 impl Error for SiteError {}
 
-impl ToString for SiteType {
-    fn to_string(&self) -> String {
+impl From<Cancelled> for SiteError {
+    fn from(_: Cancelled) -> Self {
+        SiteError::Cancelled
+    }
+}
+
+impl FromStr for SiteType {
+    type Err = SiteError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "area" => Ok(Self::Area),
+            "building" => Ok(Self::Building),
+            "floor" => Ok(Self::Floor),
+            _ => Err(SiteError::InvalidSite),
+        }
+    }
+}
+
+impl fmt::Display for SiteType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Self::Area => "area".to_string(),
-            Self::Building => "building".to_string(),
-            Self::Floor => "floor".to_string(),
+            Self::Area => write!(f, "area"),
+            Self::Building => write!(f, "building"),
+            Self::Floor => write!(f, "floor"),
         }
     }
 }
 
 impl Sites {
-    pub async fn get_site(
-        dnac: &DNAC,
+    // fails with MalformedSite if any of the sites can't be parsed
+    pub async fn get_site<C: ApiClient>(
+        dnac: &C,
         filter: Option<SiteFilter>,
         pagination: Option<Pagination>,
     ) -> Result<Vec<Site>, SiteError> {
-        let path = "/dna/intent/api/v2/site";
-        let query = {
-            let mut query = vec![];
-
-            if let Some(filter) = filter {
-                match filter {
-                    SiteFilter::Name(name) => query.push(("name", name)),
-                    SiteFilter::SiteID(id) => query.push(("siteId", id.to_string())),
-                    SiteFilter::Type(site_type) => query.push(("type", site_type.to_string())),
-                }
-            };
+        Sites::fetch_site(dnac, filter, pagination, ParseMode::Strict)
+            .await
+            .map(|sites| sites.sites)
+    }
 
-            query
+    // like get_site, but skips the sites which can't be parsed
+    pub async fn get_site_lossy<C: ApiClient>(
+        dnac: &C,
+        filter: Option<SiteFilter>,
+        pagination: Option<Pagination>,
+    ) -> Result<LossySites, SiteError> {
+        Sites::fetch_site(dnac, filter, pagination, ParseMode::Lossy).await
+    }
+
+    async fn fetch_site<C: ApiClient>(
+        dnac: &C,
+        filter: Option<SiteFilter>,
+        pagination: Option<Pagination>,
+        mode: ParseMode,
+    ) -> Result<LossySites, SiteError> {
+        let path = "/dna/intent/api/v2/site";
+        let query = match filter {
+            Some(SiteFilter::Name(name)) => Query::new().eq("name", name),
+            Some(SiteFilter::SiteID(id)) => Query::new().eq("siteId", id),
+            Some(SiteFilter::Type(site_type)) => Query::new().eq("type", site_type),
+            None => Query::new(),
         };
 
         let site_data = dnac
@@ -109,10 +518,8 @@ impl Sites {
 
         match site_data {
             Ok(site_data) => match site_data.response {
-                super::dnac::ResponseType::Array(data) => {
-                    Ok(data.into_iter().map(|s| s.parse()).collect())
-                }
-                super::dnac::ResponseType::Item(data) => Ok(vec![data.parse()]),
+                super::dnac::ResponseType::Array(data) => parse_sites(data, mode),
+                super::dnac::ResponseType::Item(data) => parse_sites(vec![data], mode),
             },
             Err(e) => {
                 if let Some(api_error) = e.downcast_ref::<ApiError>() {
@@ -123,21 +530,70 @@ impl Sites {
                             return Err(SiteError::GeneralError);
                         }
                     }
-                } else {
-                    event!(Level::ERROR, "{}", e);
                 }
-                Err(SiteError::GeneralError)
+
+                match e.downcast::<PaginationError>() {
+                    Ok(pagination_error) => Err(SiteError::PaginationOutOfRange(pagination_error)),
+                    Err(e) => {
+                        event!(Level::ERROR, "{}", e);
+                        Err(SiteError::GeneralError)
+                    }
+                }
             }
         }
     }
 
-    pub async fn get_all_sites(
-        dnac: &DNAC,
+    pub async fn get_all_sites<C: ApiClient>(
+        dnac: &C,
+        site_type: Option<SiteType>,
+    ) -> Result<Vec<Site>, SiteError> {
+        Sites::get_all_sites_with_cancel(dnac, site_type, None).await
+    }
+
+    // stops between or during page requests once cancel is triggered. On
+    // versions with the new sites API the sites are fetched through SitesV2.
+    pub async fn get_all_sites_with_cancel<C: ApiClient>(
+        dnac: &C,
         site_type: Option<SiteType>,
+        cancel: Option<&CancellationToken>,
     ) -> Result<Vec<Site>, SiteError> {
+        Sites::fetch_all_sites(dnac, site_type, cancel, ParseMode::Strict)
+            .await
+            .map(|sites| sites.sites)
+    }
+
+    // like get_all_sites_with_cancel, but skips the sites which can't be parsed
+    pub async fn get_all_sites_lossy<C: ApiClient>(
+        dnac: &C,
+        site_type: Option<SiteType>,
+        cancel: Option<&CancellationToken>,
+    ) -> Result<LossySites, SiteError> {
+        Sites::fetch_all_sites(dnac, site_type, cancel, ParseMode::Lossy).await
+    }
+
+    async fn fetch_all_sites<C: ApiClient>(
+        dnac: &C,
+        site_type: Option<SiteType>,
+        cancel: Option<&CancellationToken>,
+        mode: ParseMode,
+    ) -> Result<LossySites, SiteError> {
+        if SitesV2::is_supported(dnac.version()) {
+            // all sites are needed to rebuild the hierarchy, so the type is
+            // filtered afterwards
+            let sites = SitesV2::list_all(dnac, None, cancel).await?;
+            return Ok(LossySites {
+                sites: SitesV2::to_legacy(&sites)
+                    .into_iter()
+                    .filter(|s| site_type.is_none_or(|t| s.get_location_type() == t.to_string()))
+                    .collect(),
+                skipped: vec![],
+            });
+        }
+
         let mut offset = 1;
         let limit = 500;
         let mut sites: Vec<Site> = vec![];
+        let mut skipped: Vec<SiteError> = vec![];
         loop {
             event!(
                 Level::DEBUG,
@@ -148,16 +604,20 @@ impl Sites {
                 .with_limit(limit)
                 .build();
 
-            let filter = match site_type {
-                Some(site_type) => Some(SiteFilter::Type(site_type)),
-                None => None,
-            };
-            let current_sites = Sites::get_site(dnac, filter, Some(pagination)).await?;
-            if current_sites.len() <= 1 {
-                if current_sites.len() == 1 {
-                    if let None = sites.iter().find(|s| s.id == current_sites[0].id) {
-                        sites.extend(current_sites);
-                    }
+            let filter = site_type.map(SiteFilter::Type);
+            let page = with_cancel(
+                cancel,
+                Sites::fetch_site(dnac, filter, Some(pagination), mode),
+            )
+            .await??;
+            // the page size is judged by what the API returned, skipped
+            // sites included
+            let returned = page.sites.len() + page.skipped.len();
+            skipped.extend(page.skipped);
+            let current_sites = page.sites;
+            if returned <= 1 {
+                if current_sites.len() == 1 && !sites.iter().any(|s| s.id == current_sites[0].id) {
+                    sites.extend(current_sites);
                 }
                 break;
             }
@@ -166,16 +626,16 @@ impl Sites {
             offset += limit;
         }
 
-        Ok(sites)
+        Ok(LossySites { sites, skipped })
     }
 
-    pub async fn get_site_membership(
-        dnac: &DNAC,
-        site_id: Uuid,
+    pub async fn get_site_membership<C: ApiClient>(
+        dnac: &C,
+        site_id: SiteId,
         pagination: Option<Pagination>,
     ) -> Result<Vec<MembershipDevice>, SiteError> {
         let path = format!("/dna/intent/api/v1/site-member/{}/member", site_id);
-        let query = vec![("memberType", "networkdevice".to_string())];
+        let query = Query::new().eq("memberType", "networkdevice");
 
         let device_data = dnac
             .get::<MembershipDevice>(path.as_str(), Some(query.as_slice()), pagination)
@@ -183,7 +643,7 @@ impl Sites {
 
         match device_data {
             Ok(device_data) => match device_data.response {
-                super::dnac::ResponseType::Array(data) => Ok(data.into_iter().map(|d| d).collect()),
+                super::dnac::ResponseType::Array(data) => Ok(data),
                 super::dnac::ResponseType::Item(data) => Ok(vec![data]),
             },
             Err(e) => {
@@ -192,26 +652,420 @@ impl Sites {
             }
         }
     }
+
+    // creates the site and waits for the execution, parents have to be
+    // created before their children
+    pub async fn create<C: ApiClientExt>(dnac: &C, site: CreateSite) -> Result<()> {
+        let path = "/dna/intent/api/v1/site";
+
+        let execution_info = dnac.post_execution(path, site).await?;
+        dnac.poll_execution(execution_info).await?;
+        Ok(())
+    }
+
+    pub async fn update<C: ApiClientExt>(
+        dnac: &C,
+        site_id: SiteId,
+        site: UpdateSite,
+    ) -> Result<()> {
+        let path = format!("/dna/intent/api/v1/site/{site_id}");
+
+        let execution_info = dnac.put_execution(path.as_str(), site).await?;
+        dnac.poll_execution(execution_info).await?;
+        Ok(())
+    }
+
+    // sites with child sites or assigned devices can't be deleted
+    pub async fn delete<C: ApiClientExt>(dnac: &C, site_id: SiteId) -> Result<()> {
+        let path = format!("/dna/intent/api/v1/site/{site_id}");
+
+        let execution_info = dnac.delete_execution(path.as_str()).await?;
+        dnac.poll_execution(execution_info).await?;
+        Ok(())
+    }
+
+    // assigns the devices by their management IP address, the devices must
+    // already be in the inventory, see Device::add_device
+    pub async fn assign_devices<C: ApiClientExt>(
+        dnac: &C,
+        site_id: SiteId,
+        ip_addresses: &[&str],
+    ) -> Result<()> {
+        let path = format!("/dna/intent/api/v1/assign-device-to-site/{site_id}/device");
+        let request = AssignDevices {
+            device: ip_addresses.iter().map(|ip| DeviceIp { ip }).collect(),
+        };
+
+        let execution_info = dnac.post_execution(path.as_str(), request).await?;
+        dnac.poll_execution(execution_info).await?;
+        Ok(())
+    }
+
+    // assigns the global credentials to the site, they are inherited by its
+    // child sites. On versions with the new sites API the device credential
+    // settings are used.
+    pub async fn assign_credentials<C: ApiClientExt>(
+        dnac: &C,
+        site_id: SiteId,
+        credentials: CredentialAssignment,
+    ) -> Result<()> {
+        if SitesV2::is_supported(dnac.version()) {
+            let path = format!("/dna/intent/api/v1/sites/{site_id}/deviceCredentials");
+            let task_info = dnac
+                .put_task(path.as_str(), None, credentials.to_settings())
+                .await?;
+            dnac.poll_task(task_info).await?;
+            return Ok(());
+        }
+
+        let path = format!("/dna/intent/api/v1/credential-to-site/{site_id}");
+        let execution_info = dnac
+            .post_execution(path.as_str(), credentials.to_legacy())
+            .await?;
+        dnac.poll_execution(execution_info).await?;
+        Ok(())
+    }
+
+    // the membership includes the child sites, e.g. the floors of a building
+    pub async fn get_membership<C: ApiClient>(
+        dnac: &C,
+        site_id: SiteId,
+    ) -> Result<SiteMembership, SiteError> {
+        let path = format!("/dna/intent/api/v1/membership/{site_id}");
+
+        let membership_data = dnac.get_raw(path.as_str(), None, None).await;
+
+        match membership_data {
+            Ok(membership_data) => serde_json::from_value(membership_data).map_err(|e| {
+                event!(Level::ERROR, "{e}");
+                SiteError::GeneralError
+            }),
+            Err(e) => {
+                event!(Level::ERROR, "{e}");
+                Err(SiteError::GeneralError)
+            }
+        }
+    }
+}
+
+fn credentials_id(id: &Option<String>) -> Option<CredentialsId<'_>> {
+    id.as_deref()
+        .map(|credentials_id| CredentialsId { credentials_id })
+}
+
+impl CredentialAssignment {
+    fn to_legacy(&self) -> CredentialToSite<'_> {
+        CredentialToSite {
+            cli_id: self.cli_id.as_deref(),
+            snmp_v2_read_id: self.snmp_v2_read_id.as_deref(),
+            snmp_v2_write_id: self.snmp_v2_write_id.as_deref(),
+            snmp_v3_id: self.snmp_v3_id.as_deref(),
+            http_read: self.http_read_id.as_deref(),
+            http_write: self.http_write_id.as_deref(),
+        }
+    }
+
+    fn to_settings(&self) -> DeviceCredentialSettings<'_> {
+        DeviceCredentialSettings {
+            cli_credentials_id: credentials_id(&self.cli_id),
+            snmpv2c_read_credentials_id: credentials_id(&self.snmp_v2_read_id),
+            snmpv2c_write_credentials_id: credentials_id(&self.snmp_v2_write_id),
+            snmpv3_credentials_id: credentials_id(&self.snmp_v3_id),
+            http_read_credentials_id: credentials_id(&self.http_read_id),
+            http_write_credentials_id: credentials_id(&self.http_write_id),
+        }
+    }
+}
+
+impl SitesV2 {
+    pub fn is_supported(version: Option<&str>) -> bool {
+        version.is_some_and(|version| SITES_V2_VERSIONS.iter().any(|v| version.contains(v)))
+    }
+
+    pub async fn list<C: ApiClient>(
+        dnac: &C,
+        filter: Option<&SiteV2Filter>,
+        pagination: Option<Pagination>,
+    ) -> Result<Vec<SiteV2>, SiteError> {
+        let path = "/dna/intent/api/v1/sites";
+        let query = match filter {
+            Some(filter) => Query::new()
+                .eq_opt("name", filter.name.as_ref())
+                .eq_opt("nameHierarchy", filter.name_hierarchy.as_ref())
+                .eq_opt("type", filter.site_type),
+            None => Query::new(),
+        };
+
+        let site_data = dnac
+            .get::<SiteV2>(path, Some(query.as_slice()), pagination)
+            .await;
+
+        match site_data {
+            Ok(site_data) => match site_data.response {
+                ResponseType::Array(data) => Ok(data),
+                ResponseType::Item(data) => Ok(vec![data]),
+            },
+            Err(e) => match e.downcast::<PaginationError>() {
+                Ok(pagination_error) => Err(SiteError::PaginationOutOfRange(pagination_error)),
+                Err(e) => {
+                    event!(Level::ERROR, "{e}");
+                    Err(SiteError::GeneralError)
+                }
+            },
+        }
+    }
+
+    pub async fn list_all<C: ApiClient>(
+        dnac: &C,
+        filter: Option<&SiteV2Filter>,
+        cancel: Option<&CancellationToken>,
+    ) -> Result<Vec<SiteV2>, SiteError> {
+        let mut offset = 1;
+        let limit = 500;
+        let mut sites = vec![];
+        loop {
+            let pagination = Pagination::builder()
+                .with_offset(offset)
+                .with_limit(limit)
+                .build();
+
+            let current_sites =
+                with_cancel(cancel, SitesV2::list(dnac, filter, Some(pagination))).await??;
+            let done = (current_sites.len() as u64) < limit;
+            sites.extend(current_sites);
+            if done {
+                break;
+            }
+
+            offset += limit;
+        }
+
+        Ok(sites)
+    }
+
+    // name_hierarchy is the full name of the site, e.g. Global/CH/ZRH
+    pub async fn get<C: ApiClient>(dnac: &C, name_hierarchy: &str) -> Result<SiteV2, SiteError> {
+        let filter = SiteV2Filter {
+            name_hierarchy: Some(name_hierarchy.to_string()),
+            ..Default::default()
+        };
+
+        SitesV2::list(dnac, Some(&filter), None)
+            .await?
+            .into_iter()
+            .find(|s| s.name_hierarchy == name_hierarchy)
+            .ok_or(SiteError::InvalidSite)
+    }
+
+    // creates the sites in one task, parents have to come before their children
+    pub async fn create<C: ApiClient>(dnac: &C, sites: &[CreateSiteV2]) -> Result<()> {
+        let path = "/dna/intent/api/v1/sites/bulk";
+        dnac.post(path, sites, true).await
+    }
+
+    // converts the sites to the schema of the v2/site endpoint, the group
+    // hierarchy is rebuilt from the parents, so all ancestors must be included
+    pub fn to_legacy(sites: &[SiteV2]) -> Vec<Site> {
+        let by_id: std::collections::HashMap<SiteId, &SiteV2> =
+            sites.iter().map(|s| (s.id, s)).collect();
+
+        sites
+            .iter()
+            .map(|site| {
+                let mut ids = vec![site.id.to_string()];
+                let mut parent = site.parent_id;
+                while let Some(parent_site) = parent.and_then(|id| by_id.get(&id)) {
+                    ids.push(parent_site.id.to_string());
+                    parent = parent_site.parent_id;
+                }
+                ids.reverse();
+
+                Site {
+                    id: site.id,
+                    group_name_hierarchy: site.name_hierarchy.clone(),
+                    group_hierarchy: ids.join("/"),
+                    name: site.name.clone(),
+                    location: Some(Location {
+                        country: site.country.clone(),
+                        address: site.address.clone(),
+                        latitude: site.latitude.map(|l| l.to_string()),
+                        longitude: site.longitude.map(|l| l.to_string()),
+                        // the v2 API doesn't report where the address comes from
+                        address_inherited_from: String::new(),
+                        location_type: site.site_type.clone(),
+                    }),
+                    additional_info: None,
+                    site_type: site.site_type.parse().ok(),
+                    floor: site.floor_geometry(),
+                    building: (site.site_type == "building").then(|| BuildingAttributes {
+                        address: site.address.clone(),
+                        country: site.country.clone(),
+                        latitude: site.latitude,
+                        longitude: site.longitude,
+                    }),
+                }
+            })
+            .collect()
+    }
+}
+
+impl SiteV2 {
+    fn floor_geometry(&self) -> Option<FloorGeometry> {
+        Some(FloorGeometry {
+            width: self.width?,
+            length: self.length?,
+            height: self.height?,
+            offset_x: 0.0,
+            offset_y: 0.0,
+            rf_model: self.rf_model,
+            floor_index: self.floor_number,
+        })
+    }
+}
+
+fn serialize_display<S, T>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+    T: fmt::Display,
+{
+    serializer.collect_str(value)
+}
+
+impl SiteMembership {
+    // the devices of all sites in the membership
+    pub fn all_devices(&self) -> impl Iterator<Item = &MembershipDevice> {
+        self.devices.iter().flat_map(|s| s.devices.iter())
+    }
+}
+
+fn valid_coordinates(latitude: f64, longitude: f64) -> bool {
+    (-90.0..=90.0).contains(&latitude) && (-180.0..=180.0).contains(&longitude)
+}
+
+fn lossy_f64<'de, D>(deserializer: D) -> Result<f64, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Number {
+        Float(f64),
+        String(String),
+    }
+
+    match Number::deserialize(deserializer)? {
+        Number::Float(value) => Ok(value),
+        Number::String(value) => value.trim().parse().map_err(serde::de::Error::custom),
+    }
+}
+
+fn parse_sites(data: Vec<Site>, mode: ParseMode) -> Result<LossySites, SiteError> {
+    let mut parsed = LossySites::default();
+    for site in data {
+        match (site.parse(), mode) {
+            (Ok(site), _) => parsed.sites.push(site),
+            (Err(e), ParseMode::Strict) => return Err(e),
+            (Err(e), ParseMode::Lossy) => {
+                event!(Level::WARN, "Skipping site: {e}");
+                parsed.skipped.push(e);
+            }
+        }
+    }
+    Ok(parsed)
+}
+
+// the sites are wrapped in a response field, like in the other endpoints
+fn membership_sites<'de, D>(deserializer: D) -> Result<Vec<Site>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let sites = match Response::<Site>::deserialize(deserializer)?.response {
+        ResponseType::Array(data) => data,
+        ResponseType::Item(data) => vec![data],
+    };
+    parse_sites(sites, ParseMode::Strict)
+        .map(|sites| sites.sites)
+        .map_err(serde::de::Error::custom)
 }
 
 impl Site {
-    pub fn parse(mut self) -> Self {
+    // fails if the Location namespace is missing its name or is malformed,
+    // the map namespaces are optional and skipped when they can't be parsed
+    pub fn parse(mut self) -> Result<Self, SiteError> {
+        let mut geometry: Option<MapGeometry> = None;
+        let mut summary: Option<MapsSummary> = None;
+
         if let Some(data) = &self.additional_info {
             for entry in data {
-                let name_space = entry["nameSpace"].as_str().unwrap();
+                let Some(name_space) = entry["nameSpace"].as_str() else {
+                    return Err(SiteError::MalformedSite(
+                        self.id,
+                        "additionalInfo entry without nameSpace".to_string(),
+                    ));
+                };
+                let attributes = entry["attributes"].clone();
                 match name_space {
                     "Location" => {
-                        let location: Location =
-                            serde_json::from_value(entry["attributes"].clone()).unwrap();
+                        let location: Location = serde_json::from_value(attributes)
+                            .map_err(|e| SiteError::MalformedSite(self.id, e.to_string()))?;
                         self.location = Some(location);
                     }
-
-                    _ => (),
+                    "mapGeometry" => geometry = serde_json::from_value(attributes).ok(),
+                    "mapsSummary" => summary = serde_json::from_value(attributes).ok(),
+                    _ => {}
                 }
             }
         }
 
-        self
+        if let Some(location) = &self.location {
+            self.site_type = location.location_type.parse().ok();
+            if self.site_type == Some(SiteType::Building) {
+                self.building = Some(BuildingAttributes {
+                    address: location.address.clone(),
+                    country: location.country.clone(),
+                    latitude: location.latitude.as_deref().and_then(|l| l.parse().ok()),
+                    longitude: location.longitude.as_deref().and_then(|l| l.parse().ok()),
+                });
+            }
+        }
+
+        self.floor = geometry.map(|geometry| FloorGeometry {
+            width: geometry.width,
+            length: geometry.length,
+            height: geometry.height,
+            offset_x: geometry.offset_x,
+            offset_y: geometry.offset_y,
+            rf_model: summary
+                .as_ref()
+                .and_then(|s| s.rf_model.clone())
+                .and_then(|m| serde_json::from_value(serde_json::Value::String(m)).ok()),
+            floor_index: summary
+                .as_ref()
+                .and_then(|s| s.floor_index.as_deref())
+                .and_then(|i| i.parse().ok()),
+        });
+
+        Ok(self)
+    }
+
+    // latitude and longitude in degrees, None if the site has no valid coordinates
+    pub fn coordinates(&self) -> Option<(f64, f64)> {
+        let (latitude, longitude) = match &self.building {
+            Some(BuildingAttributes {
+                latitude: Some(latitude),
+                longitude: Some(longitude),
+                ..
+            }) => (*latitude, *longitude),
+            _ => {
+                let location = self.location.as_ref()?;
+                (
+                    location.latitude.as_deref()?.trim().parse().ok()?,
+                    location.longitude.as_deref()?.trim().parse().ok()?,
+                )
+            }
+        };
+
+        valid_coordinates(latitude, longitude).then_some((latitude, longitude))
     }
 
     // we provide various getters which return a location value or an empty string
@@ -262,3 +1116,274 @@ impl Site {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::MockDnac;
+
+    #[tokio::test]
+    async fn test_assign_credentials() {
+        let mut mock = MockDnac::start().await;
+        let site_id: SiteId = "a4f3a3c2-f103-4f0a-8c5d-46f3d4b5e9d2".parse().unwrap();
+        mock.mock_task(
+            "PUT",
+            &format!("/dna/intent/api/v1/sites/{site_id}/deviceCredentials"),
+            false,
+        )
+        .await;
+        mock.mock_execution(
+            "POST",
+            &format!("/dna/intent/api/v1/credential-to-site/{site_id}"),
+            false,
+        )
+        .await;
+
+        let credentials = CredentialAssignment {
+            cli_id: Some("2b7a2d44-4f0e-4a1e-80d6-c9b3e5a218c1".to_string()),
+            snmp_v3_id: Some("4f0e2b7a-2d44-4a1e-80d6-c9b3e5a218c1".to_string()),
+            ..Default::default()
+        };
+        Sites::assign_credentials(&mock.dnac, site_id, credentials.clone())
+            .await
+            .unwrap();
+
+        // older versions only know the credential-to-site API
+        mock.dnac.version = Some("2.3.5.5".to_string());
+        Sites::assign_credentials(&mock.dnac, site_id, credentials)
+            .await
+            .unwrap();
+
+        let requests = mock.server.received_requests().await.unwrap();
+        let body = |method: &str| -> serde_json::Value {
+            let request = requests
+                .iter()
+                .find(|r| r.method.as_str() == method)
+                .unwrap();
+            serde_json::from_slice(&request.body).unwrap()
+        };
+        let settings = body("PUT");
+        assert_eq!(
+            settings["cliCredentialsId"]["credentialsId"],
+            "2b7a2d44-4f0e-4a1e-80d6-c9b3e5a218c1"
+        );
+        assert!(settings.get("httpReadCredentialsId").is_none());
+        let legacy = body("POST");
+        assert_eq!(legacy["snmpV3Id"], "4f0e2b7a-2d44-4a1e-80d6-c9b3e5a218c1");
+    }
+
+    #[tokio::test]
+    async fn test_get_membership() {
+        let mock = MockDnac::start().await;
+        let site_id: SiteId = "a4f3a3c2-f103-4f0a-8c5d-46f3d4b5e9d2".parse().unwrap();
+        mock.mock_response(
+            "GET",
+            &format!("/dna/intent/api/v1/membership/{site_id}"),
+            200,
+            r#"
+              {"site":{"response":[{"id":"a4f3a3c2-f103-4f0a-8c5d-46f3d4b5e9d2","name":"HQ","groupNameHierarchy":"Global/CH/ZRH/HQ","groupHierarchy":"1/2/3/a4f3a3c2-f103-4f0a-8c5d-46f3d4b5e9d2"}],"version":"1.0"},
+               "device":[{"response":[{"instanceUuid":"3f0ac5d4-6f3d-4b5e-9d2b-5b0f3a3c2f10","collectionStatus":"Managed","managementIpAddress":"10.10.1.11","hostname":"zrh-acc-sw01","family":"Switches and Hubs"}],"version":"1.0","siteId":"a4f3a3c2-f103-4f0a-8c5d-46f3d4b5e9d2"}]}
+              "#,
+        )
+        .await;
+
+        let membership = Sites::get_membership(&mock.dnac, site_id).await.unwrap();
+        assert_eq!(membership.sites[0].name, "HQ");
+        assert_eq!(membership.devices[0].site_id, site_id);
+        assert_eq!(
+            membership.all_devices().next().unwrap().hostname.as_deref(),
+            Some("zrh-acc-sw01")
+        );
+    }
+
+    #[test]
+    fn test_serialize_create_site() {
+        let floor = CreateSite::Floor(NewFloor {
+            name: "Floor 2".to_string(),
+            parent_name: "Global/CH/ZRH/HQ".to_string(),
+            rf_model: RfModel::CubesAndWalledOffices,
+            width: 100.0,
+            length: 50.0,
+            height: 10.0,
+            floor_number: Some(2),
+        });
+
+        assert_eq!(
+            serde_json::to_value(&floor).unwrap(),
+            serde_json::json!({
+                "type": "floor",
+                "site": {"floor": {
+                    "name": "Floor 2",
+                    "parentName": "Global/CH/ZRH/HQ",
+                    "rfModel": "Cubes And Walled Offices",
+                    "width": 100.0,
+                    "length": 50.0,
+                    "height": 10.0,
+                    "floorNumber": 2
+                }}
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_create_site() {
+        let mock = MockDnac::start().await;
+        mock.mock_execution("POST", "/dna/intent/api/v1/site", false)
+            .await;
+
+        let area = CreateSite::Area(NewArea {
+            name: "ZRH".to_string(),
+            parent_name: "Global/CH".to_string(),
+        });
+        Sites::create(&mock.dnac, area).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_update_and_delete_site() {
+        let mock = MockDnac::start().await;
+        let site_id: SiteId = "a4f3a3c2-f103-4f0a-8c5d-46f3d4b5e9d2".parse().unwrap();
+        let path = format!("/dna/intent/api/v1/site/{site_id}");
+        mock.mock_execution("PUT", &path, false).await;
+        mock.mock_execution("DELETE", &path, false).await;
+
+        let building = UpdateSite::Building(UpdateBuilding {
+            address: Some("Bahnhofstrasse 1, 8001 Zurich".to_string()),
+            ..Default::default()
+        });
+        assert_eq!(
+            serde_json::to_value(&building).unwrap(),
+            serde_json::json!({
+                "type": "building",
+                "site": {"building": {"address": "Bahnhofstrasse 1, 8001 Zurich"}}
+            })
+        );
+        Sites::update(&mock.dnac, site_id, building).await.unwrap();
+        Sites::delete(&mock.dnac, site_id).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_sites_v2() {
+        let mock = MockDnac::start().await;
+        mock.mock_response(
+            "GET",
+            "/dna/intent/api/v1/sites",
+            200,
+            r#"
+              {"response":[{"id":"1f0ac5d4-6f3d-4b5e-9d2b-5b0f3a3c2f10","name":"Global","nameHierarchy":"Global","type":"global"},
+                           {"id":"a4f3a3c2-f103-4f0a-8c5d-46f3d4b5e9d2","parentId":"1f0ac5d4-6f3d-4b5e-9d2b-5b0f3a3c2f10","name":"HQ","nameHierarchy":"Global/HQ","type":"building","latitude":47.37,"longitude":8.54,"address":"Bahnhofstrasse 1, 8001 Zurich","country":"Switzerland"},
+                           {"id":"b5e9d2a4-f3a3-4c2f-a103-8c5d46f3d4f0","parentId":"a4f3a3c2-f103-4f0a-8c5d-46f3d4b5e9d2","name":"Floor 1","nameHierarchy":"Global/HQ/Floor 1","type":"floor","floorNumber":1,"rfModel":"Cubes And Walled Offices","width":100.0,"length":50.0,"height":10.0,"unitsOfMeasure":"feet"}],"version":"1.0"}
+              "#,
+        )
+        .await;
+
+        let sites = Sites::get_all_sites(&mock.dnac, None).await.unwrap();
+        assert_eq!(sites.len(), 3);
+        assert_eq!(sites[2].get_location_type(), "floor");
+        assert_eq!(
+            sites[2].group_hierarchy,
+            "1f0ac5d4-6f3d-4b5e-9d2b-5b0f3a3c2f10/a4f3a3c2-f103-4f0a-8c5d-46f3d4b5e9d2/b5e9d2a4-f3a3-4c2f-a103-8c5d46f3d4f0"
+        );
+        assert_eq!(sites[1].get_latitude(), "47.37");
+
+        let floor = SitesV2::get(&mock.dnac, "Global/HQ/Floor 1").await.unwrap();
+        assert_eq!(floor.rf_model, Some(RfModel::CubesAndWalledOffices));
+        assert_eq!(sites[2].site_type, Some(SiteType::Floor));
+        assert_eq!(sites[2].floor.as_ref().unwrap().floor_index, Some(1));
+        assert_eq!(sites[1].building.as_ref().unwrap().longitude, Some(8.54));
+        assert_eq!(sites[1].coordinates(), Some((47.37, 8.54)));
+        assert_eq!(sites[0].coordinates(), None);
+    }
+
+    #[test]
+    fn test_parse_floor() {
+        let floor = r#"
+          {"id":"b5e9d2a4-f3a3-4c2f-a103-8c5d46f3d4f0","name":"Floor 1","groupNameHierarchy":"Global/HQ/Floor 1","groupHierarchy":"1/2/b5e9d2a4-f3a3-4c2f-a103-8c5d46f3d4f0",
+           "additionalInfo":[{"nameSpace":"Location","attributes":{"addressInheritedFrom":"a4f3a3c2-f103-4f0a-8c5d-46f3d4b5e9d2","type":"floor"}},
+                             {"nameSpace":"mapGeometry","attributes":{"offsetX":"0.0","offsetY":"0.0","length":"50.0","width":"100.0","height":"10.0"}},
+                             {"nameSpace":"mapsSummary","attributes":{"rfModel":"Cubes And Walled Offices","floorIndex":"1","isCadFileExists":"false"}}]}
+          "#;
+        let floor: Site = serde_json::from_str(floor).unwrap();
+        let floor = floor.parse().unwrap();
+
+        assert_eq!(floor.site_type, Some(SiteType::Floor));
+        assert_eq!(floor.building, None);
+        assert_eq!(
+            floor.floor,
+            Some(FloorGeometry {
+                width: 100.0,
+                length: 50.0,
+                height: 10.0,
+                offset_x: 0.0,
+                offset_y: 0.0,
+                rf_model: Some(RfModel::CubesAndWalledOffices),
+                floor_index: Some(1),
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_malformed_site() {
+        let site = r#"
+          {"id":"b5e9d2a4-f3a3-4c2f-a103-8c5d46f3d4f0","name":"Floor 1","groupNameHierarchy":"Global/HQ/Floor 1","groupHierarchy":"1/2/b5e9d2a4-f3a3-4c2f-a103-8c5d46f3d4f0",
+           "additionalInfo":[{"attributes":{"type":"floor"}}]}
+          "#;
+        let site: Site = serde_json::from_str(site).unwrap();
+        assert!(matches!(site.parse(), Err(SiteError::MalformedSite(..))));
+
+        let site = r#"
+          {"id":"b5e9d2a4-f3a3-4c2f-a103-8c5d46f3d4f0","name":"Floor 1","groupNameHierarchy":"Global/HQ/Floor 1","groupHierarchy":"1/2/b5e9d2a4-f3a3-4c2f-a103-8c5d46f3d4f0",
+           "additionalInfo":[{"nameSpace":"Location","attributes":{"country":"Switzerland"}}]}
+          "#;
+        let site: Site = serde_json::from_str(site).unwrap();
+        assert!(matches!(site.parse(), Err(SiteError::MalformedSite(..))));
+    }
+
+    #[tokio::test]
+    async fn test_get_site_malformed() {
+        let mock = MockDnac::start().await;
+        mock.mock_response(
+            "GET",
+            "/dna/intent/api/v2/site",
+            200,
+            r#"
+              {"response":[{"id":"a4f3a3c2-f103-4f0a-8c5d-46f3d4b5e9d2","name":"HQ","groupNameHierarchy":"Global/HQ","groupHierarchy":"1/a4f3a3c2-f103-4f0a-8c5d-46f3d4b5e9d2",
+                            "additionalInfo":[{"nameSpace":"Location","attributes":{"type":"building","addressInheritedFrom":"a4f3a3c2-f103-4f0a-8c5d-46f3d4b5e9d2"}}]},
+                           {"id":"b5e9d2a4-f3a3-4c2f-a103-8c5d46f3d4f0","name":"Floor 1","groupNameHierarchy":"Global/HQ/Floor 1","groupHierarchy":"1/2/b5e9d2a4-f3a3-4c2f-a103-8c5d46f3d4f0",
+                            "additionalInfo":[{"nameSpace":"Location","attributes":{"country":"Switzerland"}}]}],"version":"1.0"}
+              "#,
+        )
+        .await;
+
+        let result = Sites::get_site(&mock.dnac, None, None).await;
+        assert!(matches!(result, Err(SiteError::MalformedSite(..))));
+
+        let sites = Sites::get_site_lossy(&mock.dnac, None, None).await.unwrap();
+        assert_eq!(sites.sites.len(), 1);
+        assert_eq!(sites.sites[0].name, "HQ");
+        assert!(matches!(
+            sites.skipped[..],
+            [SiteError::MalformedSite(id, _)] if id.to_string() == "b5e9d2a4-f3a3-4c2f-a103-8c5d46f3d4f0"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_assign_devices() {
+        let mock = MockDnac::start().await;
+        let site_id: SiteId = "a4f3a3c2-f103-4f0a-8c5d-46f3d4b5e9d2".parse().unwrap();
+        let path = format!("/dna/intent/api/v1/assign-device-to-site/{site_id}/device");
+        mock.mock_execution("POST", &path, false).await;
+
+        Sites::assign_devices(&mock.dnac, site_id, &["10.10.1.11"])
+            .await
+            .unwrap();
+
+        let mock = MockDnac::start().await;
+        mock.mock_execution("POST", &path, true).await;
+
+        let result = Sites::assign_devices(&mock.dnac, site_id, &["10.10.1.99"]).await;
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("not found in inventory"));
+    }
+}
@@ -0,0 +1,736 @@
+use std::{fmt, path::Path};
+
+use anyhow::anyhow;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::{event, Level};
+
+use crate::{
+    compliance::{Compliance, ComplianceCategory, ComplianceState},
+    devices::{Device, DeviceRole, DeviceStatus},
+    dnac::{
+        ApiClient, ApiClientExt, Pagination, PaginationError, Response, ResponseType, TaskInfo,
+    },
+    ids::{DeviceId, ImageId, SiteId},
+    query::Query,
+};
+
+// SoftwareImage is an image in the image repository of the software image
+// management (SWIM)
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SoftwareImage {
+    #[serde(rename = "imageUuid")]
+    pub id: ImageId,
+    pub name: String,
+    // e.g. CAT9K
+    pub family: Option<String>,
+    pub version: Option<String>,
+    pub display_version: Option<String>,
+    #[serde(default)]
+    pub is_tagged_golden: bool,
+    pub image_integrity_status: Option<ImageIntegrityStatus>,
+    pub image_type: Option<String>,
+    pub file_size: Option<String>,
+    pub import_source_type: Option<String>,
+    #[serde(default)]
+    pub image_series: Vec<String>,
+    #[serde(default)]
+    pub applicable_devices_for_image: Vec<ApplicableDevice>,
+    pub md5_checksum: Option<String>,
+    #[serde(rename = "shaCheckSum")]
+    pub sha_checksum: Option<String>,
+    #[serde(default, rename = "isCCORecommended")]
+    pub is_cco_recommended: bool,
+    #[serde(default, rename = "isCCOLatest")]
+    pub is_cco_latest: bool,
+}
+
+// the product the image can be installed on, identified by its mdf id
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApplicableDevice {
+    pub mdf_id: String,
+    pub product_name: Option<String>,
+    #[serde(default)]
+    pub product_id: Vec<String>,
+}
+
+// the result of the checksum verification against cisco.com
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ImageIntegrityStatus {
+    Verified,
+    Failed,
+    Unknown,
+    #[serde(other)]
+    Other,
+}
+
+impl fmt::Display for ImageIntegrityStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self {
+            Self::Verified => write!(f, "VERIFIED"),
+            Self::Failed => write!(f, "FAILED"),
+            Self::Unknown => write!(f, "UNKNOWN"),
+            Self::Other => write!(f, "OTHER"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ImageFilter {
+    pub name: Option<String>,
+    pub family: Option<String>,
+    pub version: Option<String>,
+    pub is_tagged_golden: Option<bool>,
+    pub image_integrity_status: Option<ImageIntegrityStatus>,
+}
+
+// UrlImport imports an image from a http or ftp server, images of other
+// vendors need to be flagged as third party
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UrlImport {
+    #[serde(rename = "sourceURL")]
+    pub source_url: String,
+    pub third_party: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vendor: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image_family: Option<String>,
+}
+
+// GoldenTag tells if an image is golden for a site, device family and
+// role, the tag can be inherited from a parent site
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GoldenTag {
+    pub site_id: Option<String>,
+    pub device_family_identifier: Option<String>,
+    pub device_role: Option<String>,
+    #[serde(default)]
+    pub tagged_golden: bool,
+    pub inherited_site_id: Option<String>,
+    pub inherited_site_name: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TagGolden {
+    image_id: ImageId,
+    site_id: String,
+    device_family_identifier: String,
+    device_role: String,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct DeviceImagePair {
+    #[serde(rename = "deviceUuid")]
+    pub device_id: DeviceId,
+    #[serde(rename = "imageUuid")]
+    pub image_id: ImageId,
+}
+
+// ActivationRequest activates the images on the device, the device reloads
+// once they are installed
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActivationRequest {
+    #[serde(rename = "deviceUuid")]
+    pub device_id: DeviceId,
+    #[serde(rename = "imageUuidList")]
+    pub image_ids: Vec<ImageId>,
+    // allows a downgrade
+    pub activate_lower_image_version: bool,
+    // distributes the images first, if they aren't on the device yet
+    pub distribute_if_needed: bool,
+}
+
+// ImageTask is the result of a distribution or activation on one device
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImageTask {
+    pub task_uuid: String,
+    pub device_id: Option<DeviceId>,
+    pub device_ip_address: Option<String>,
+    // e.g. distribute or activate
+    pub task_type: Option<String>,
+    pub task_status: ImageTaskStatus,
+    pub start_time: Option<u64>,
+    pub completion_time: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageTaskStatus {
+    Success,
+    Failure,
+    Pending,
+    #[serde(other)]
+    Unknown,
+}
+
+// InventoryReadiness collects the checks the inventory and the image
+// compliance allow, needs_upgrade is set if the running image isn't the
+// golden one. Flash space and image compatibility aren't part of it, those
+// are only checked by the distribution precheck on DNAC.
+#[derive(Debug)]
+pub struct InventoryReadiness {
+    pub device_id: DeviceId,
+    pub running_version: Option<String>,
+    pub needs_upgrade: bool,
+    pub checks: Vec<ReadinessCheck>,
+}
+
+#[derive(Debug)]
+pub struct ReadinessCheck {
+    pub check: ReadinessCheckType,
+    pub passed: bool,
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReadinessCheckType {
+    // DNAC has to reach the device to distribute the image
+    Reachability,
+    // the inventory collection has to be done, otherwise the data is stale
+    Managed,
+    // a golden image has to exist for the device and be comparable
+    GoldenImage,
+}
+
+#[derive(Debug, Error)]
+pub enum SwimError {
+    #[error("General SWIM Error")]
+    GeneralError,
+    #[error(transparent)]
+    PaginationOutOfRange(#[from] PaginationError),
+}
+
+impl SoftwareImage {
+    pub async fn list<C: ApiClient>(
+        dnac: &C,
+        filter: Option<&ImageFilter>,
+        pagination: Option<Pagination>,
+    ) -> Result<Vec<SoftwareImage>, SwimError> {
+        let path = "/dna/intent/api/v1/image/importation";
+        let query = filter.map(ImageFilter::to_query).unwrap_or_default();
+
+        let image_data = dnac
+            .get::<SoftwareImage>(path, Some(query.as_slice()), pagination)
+            .await;
+
+        match image_data {
+            Ok(image_data) => match image_data.response {
+                ResponseType::Array(data) => Ok(data),
+                ResponseType::Item(data) => Ok(vec![data]),
+            },
+            Err(e) => match e.downcast::<PaginationError>() {
+                Ok(pagination_error) => Err(pagination_error.into()),
+                Err(e) => {
+                    event!(Level::ERROR, "{e}");
+                    Err(SwimError::GeneralError)
+                }
+            },
+        }
+    }
+
+    pub async fn import_from_url<C: ApiClient>(dnac: &C, import: UrlImport) -> anyhow::Result<()> {
+        let path = "/dna/intent/api/v1/image/importation/source/url";
+        dnac.post(path, [import], true).await
+    }
+
+    // streams the image to DNAC, the file name is used as image name
+    pub async fn import_local<C: ApiClientExt>(dnac: &C, file: &Path) -> anyhow::Result<()> {
+        let path = "/dna/intent/api/v1/image/importation/source/file";
+        let response = dnac.upload_file(path, file).await?;
+
+        let task_info = match serde_json::from_value::<Response<TaskInfo>>(response)?.response {
+            ResponseType::Item(task_info) => task_info,
+            ResponseType::Array(_) => return Err(anyhow!("Unexpected response")),
+        };
+        dnac.poll_task(task_info).await?;
+
+        Ok(())
+    }
+
+    // device_family is the mdf id of the family, see applicable_devices_for_image.
+    // Without a site the tag applies to Global, without a role to all roles.
+    pub async fn tag_golden<C: ApiClient>(
+        dnac: &C,
+        image_id: ImageId,
+        site_id: Option<SiteId>,
+        device_family: &str,
+        role: Option<DeviceRole>,
+    ) -> anyhow::Result<()> {
+        let path = "/dna/intent/api/v1/image/importation/golden";
+        let request = TagGolden {
+            image_id,
+            site_id: golden_site(site_id),
+            device_family_identifier: device_family.to_string(),
+            device_role: golden_role(role),
+        };
+        dnac.post(path, request, true).await
+    }
+
+    pub async fn untag_golden<C: ApiClient>(
+        dnac: &C,
+        image_id: ImageId,
+        site_id: Option<SiteId>,
+        device_family: &str,
+        role: Option<DeviceRole>,
+    ) -> anyhow::Result<()> {
+        let path = golden_path(image_id, site_id, device_family, role);
+        dnac.delete(path.as_str(), None, true).await
+    }
+
+    pub async fn golden_tag<C: ApiClient>(
+        dnac: &C,
+        image_id: ImageId,
+        site_id: Option<SiteId>,
+        device_family: &str,
+        role: Option<DeviceRole>,
+    ) -> Result<GoldenTag, SwimError> {
+        let path = golden_path(image_id, site_id, device_family, role);
+
+        let tag_data = dnac.get::<GoldenTag>(path.as_str(), None, None).await;
+
+        match tag_data {
+            Ok(tag_data) => match tag_data.response {
+                ResponseType::Item(data) => Ok(data),
+                ResponseType::Array(mut data) => data.pop().ok_or(SwimError::GeneralError),
+            },
+            Err(e) => {
+                event!(Level::ERROR, "{e}");
+                Err(SwimError::GeneralError)
+            }
+        }
+    }
+
+    // the image which is golden for all roles of the family at the site,
+    // tags inherited from a parent site are included
+    pub async fn golden_for<C: ApiClient>(
+        dnac: &C,
+        site_id: Option<SiteId>,
+        device_family: &str,
+    ) -> Result<Option<SoftwareImage>, SwimError> {
+        let filter = ImageFilter {
+            is_tagged_golden: Some(true),
+            ..Default::default()
+        };
+        let images = SoftwareImage::list(dnac, Some(&filter), None).await?;
+
+        for image in images {
+            if !image
+                .applicable_devices_for_image
+                .iter()
+                .any(|d| d.mdf_id == device_family)
+            {
+                continue;
+            }
+
+            let tag =
+                SoftwareImage::golden_tag(dnac, image.id, site_id, device_family, None).await?;
+            if tag.tagged_golden {
+                return Ok(Some(image));
+            }
+        }
+
+        Ok(None)
+    }
+
+    // copies the images to the devices, the result contains one entry per
+    // device, also if the distribution failed on some of them
+    pub async fn distribute<C: ApiClient>(
+        dnac: &C,
+        pairs: &[DeviceImagePair],
+    ) -> anyhow::Result<Vec<ImageTask>> {
+        let path = "/dna/intent/api/v1/image/distribution";
+        let task_info = dnac.post_task(path, pairs).await?;
+        image_task_results(dnac, task_info).await
+    }
+
+    pub async fn activate<C: ApiClient>(
+        dnac: &C,
+        requests: &[ActivationRequest],
+    ) -> anyhow::Result<Vec<ImageTask>> {
+        let path = "/dna/intent/api/v1/image/activation/device";
+        let task_info = dnac.post_task(path, requests).await?;
+        image_task_results(dnac, task_info).await
+    }
+
+    // checks reachability, the collection status and the image compliance of
+    // the device, the compliance tells if the running image differs from the
+    // golden image. A device passing them can still fail the distribution if
+    // the flash is full or the image isn't compatible.
+    pub async fn inventory_readiness<C: ApiClient>(
+        dnac: &C,
+        device_id: DeviceId,
+    ) -> Result<InventoryReadiness, SwimError> {
+        let device = Device::get_by_id(dnac, device_id).await.map_err(|e| {
+            event!(Level::ERROR, "{e}");
+            SwimError::GeneralError
+        })?;
+
+        let compliance = Compliance::detail(dnac, device_id, Some(ComplianceCategory::Image))
+            .await
+            .map_err(|_| SwimError::GeneralError)?
+            .pop();
+
+        let reachable = device
+            .reachability_status
+            .as_deref()
+            .is_some_and(|s| s.eq_ignore_ascii_case("reachable"));
+        let managed = device.collection_status == DeviceStatus::Managed;
+        let golden_status = compliance.as_ref().map(|c| c.status);
+
+        let checks = vec![
+            ReadinessCheck {
+                check: ReadinessCheckType::Reachability,
+                passed: reachable,
+                message: device.reachability_failure_reason.clone(),
+            },
+            ReadinessCheck {
+                check: ReadinessCheckType::Managed,
+                passed: managed,
+                message: (!managed).then(|| device.collection_status.to_string()),
+            },
+            ReadinessCheck {
+                check: ReadinessCheckType::GoldenImage,
+                passed: matches!(
+                    golden_status,
+                    Some(ComplianceState::Compliant | ComplianceState::NonCompliant)
+                ),
+                message: compliance.and_then(|c| c.state),
+            },
+        ];
+
+        Ok(InventoryReadiness {
+            device_id,
+            running_version: device.software_version,
+            needs_upgrade: golden_status == Some(ComplianceState::NonCompliant),
+            checks,
+        })
+    }
+
+    pub fn is_verified(&self) -> bool {
+        self.image_integrity_status == Some(ImageIntegrityStatus::Verified)
+    }
+}
+
+impl InventoryReadiness {
+    pub fn is_ready(&self) -> bool {
+        self.checks.iter().all(|c| c.passed)
+    }
+
+    pub fn failed_checks(&self) -> impl Iterator<Item = &ReadinessCheck> {
+        self.checks.iter().filter(|c| !c.passed)
+    }
+}
+
+// waits for the task and returns the per device results, a failed task is
+// only an error if it didn't produce any
+async fn image_task_results<C: ApiClient>(
+    dnac: &C,
+    task_info: TaskInfo,
+) -> anyhow::Result<Vec<ImageTask>> {
+    let task_id = task_info.task_id.clone();
+    let polled = dnac.poll_task(task_info).await;
+
+    let path = "/dna/intent/api/v1/image/task";
+    let query = Query::new().eq("taskUuid", task_id);
+    let results = match dnac
+        .get::<ImageTask>(path, Some(query.as_slice()), None)
+        .await?
+        .response
+    {
+        ResponseType::Array(data) => data,
+        ResponseType::Item(data) => vec![data],
+    };
+
+    match polled {
+        Err(e) if results.is_empty() => Err(e),
+        _ => Ok(results),
+    }
+}
+
+// Global is addressed as -1 by the golden image endpoints
+fn golden_site(site_id: Option<SiteId>) -> String {
+    site_id.map_or("-1".to_string(), |id| id.to_string())
+}
+
+fn golden_role(role: Option<DeviceRole>) -> String {
+    role.map_or("ALL".to_string(), |role| role.to_string())
+}
+
+fn golden_path(
+    image_id: ImageId,
+    site_id: Option<SiteId>,
+    device_family: &str,
+    role: Option<DeviceRole>,
+) -> String {
+    format!(
+        "/dna/intent/api/v1/image/importation/golden/site/{}/family/{device_family}/role/{}/image/{image_id}",
+        golden_site(site_id),
+        golden_role(role)
+    )
+}
+
+impl UrlImport {
+    pub fn new(source_url: impl Into<String>) -> Self {
+        Self {
+            source_url: source_url.into(),
+            third_party: false,
+            vendor: None,
+            image_family: None,
+        }
+    }
+
+    pub fn third_party(
+        mut self,
+        vendor: impl Into<String>,
+        image_family: impl Into<String>,
+    ) -> Self {
+        self.third_party = true;
+        self.vendor = Some(vendor.into());
+        self.image_family = Some(image_family.into());
+        self
+    }
+}
+
+impl ImageFilter {
+    pub fn to_query(&self) -> Query<'static> {
+        Query::new()
+            .eq_opt("name", self.name.as_ref())
+            .eq_opt("family", self.family.as_ref())
+            .eq_opt("version", self.version.as_ref())
+            .eq_opt("isTaggedGolden", self.is_tagged_golden)
+            .eq_opt("imageIntegrityStatus", self.image_integrity_status)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{fixtures, MockDnac};
+
+    #[tokio::test]
+    async fn test_list_images() {
+        let mock = MockDnac::start().await;
+        mock.mock_response(
+            "GET",
+            "/dna/intent/api/v1/image/importation",
+            200,
+            r#"
+              {"response":[{"imageUuid":"4b5e9d2b-5b0f-4a3c-9f10-3f0ac5d46f3d","name":"cat9k_iosxe.17.09.04a.SPA.bin","family":"CAT9K","version":"17.09.04a","displayVersion":"17.9.4a","isTaggedGolden":true,"imageIntegrityStatus":"VERIFIED","imageType":"SYSTEM_SW","fileSize":"1234567890 bytes","importSourceType":"CCO","imageSeries":["Switches and Hubs/Cisco Catalyst 9300 Series Switches"],
+                            "applicableDevicesForImage":[{"mdfId":"286315874","productName":"Cisco Catalyst 9300 Switch","productId":["C9300-48P"]}]},
+                           {"imageUuid":"9f103f0a-c5d4-46f3-84b5-e9d2b5b0f3a3","name":"cat9k_iosxe.17.12.03.SPA.bin","family":"CAT9K","version":"17.12.03","isTaggedGolden":false,"imageIntegrityStatus":"UNKNOWN"}],"version":"1.0"}
+              "#,
+        )
+        .await;
+
+        let filter = ImageFilter {
+            family: Some("CAT9K".to_string()),
+            ..Default::default()
+        };
+        let images = SoftwareImage::list(&mock.dnac, Some(&filter), None)
+            .await
+            .unwrap();
+        assert_eq!(images.len(), 2);
+        assert!(images[0].is_tagged_golden);
+        assert!(images[0].is_verified());
+        assert_eq!(
+            images[0].applicable_devices_for_image[0].mdf_id,
+            "286315874"
+        );
+        assert!(!images[1].is_verified());
+    }
+
+    #[tokio::test]
+    async fn test_import_images() {
+        let mock = MockDnac::start().await;
+        mock.mock_task(
+            "POST",
+            "/dna/intent/api/v1/image/importation/source/url",
+            false,
+        )
+        .await;
+        mock.mock_task(
+            "POST",
+            "/dna/intent/api/v1/image/importation/source/file",
+            false,
+        )
+        .await;
+
+        let import = UrlImport::new("https://images.example.com/cat9k_iosxe.17.12.03.SPA.bin");
+        SoftwareImage::import_from_url(&mock.dnac, import)
+            .await
+            .unwrap();
+
+        let file = std::env::temp_dir().join("dnac-swim-test.bin");
+        std::fs::write(&file, b"image").unwrap();
+        SoftwareImage::import_local(&mock.dnac, &file)
+            .await
+            .unwrap();
+        std::fs::remove_file(&file).unwrap();
+
+        let requests = mock.server.received_requests().await.unwrap();
+        let url_import: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+        assert_eq!(
+            url_import[0]["sourceURL"],
+            "https://images.example.com/cat9k_iosxe.17.12.03.SPA.bin"
+        );
+        let upload = requests
+            .iter()
+            .find(|r| r.url.path().ends_with("source/file"))
+            .unwrap();
+        assert!(String::from_utf8_lossy(&upload.body).contains("dnac-swim-test.bin"));
+    }
+
+    #[tokio::test]
+    async fn test_golden_images() {
+        let mock = MockDnac::start().await;
+        let site_id: SiteId = "a4f3a3c2-f103-4f0a-8c5d-46f3d4b5e9d2".parse().unwrap();
+        let image_id: ImageId = "4b5e9d2b-5b0f-4a3c-9f10-3f0ac5d46f3d".parse().unwrap();
+        mock.mock_response(
+            "GET",
+            "/dna/intent/api/v1/image/importation",
+            200,
+            r#"
+              {"response":[{"imageUuid":"4b5e9d2b-5b0f-4a3c-9f10-3f0ac5d46f3d","name":"cat9k_iosxe.17.09.04a.SPA.bin","family":"CAT9K","isTaggedGolden":true,
+                            "applicableDevicesForImage":[{"mdfId":"286315874","productName":"Cisco Catalyst 9300 Switch"}]}],"version":"1.0"}
+              "#,
+        )
+        .await;
+        mock.mock_response(
+            "GET",
+            &format!("/dna/intent/api/v1/image/importation/golden/site/{site_id}/family/286315874/role/ALL/image/{image_id}"),
+            200,
+            r#"{"response":{"siteId":"a4f3a3c2-f103-4f0a-8c5d-46f3d4b5e9d2","deviceFamilyIdentifier":"286315874","deviceRole":"ALL","taggedGolden":true,"inheritedSiteId":"","inheritedSiteName":""},"version":"1.0"}"#,
+        )
+        .await;
+        mock.mock_task("POST", "/dna/intent/api/v1/image/importation/golden", false)
+            .await;
+        mock.mock_task(
+            "DELETE",
+            &format!("/dna/intent/api/v1/image/importation/golden/site/-1/family/286315874/role/BORDER%20ROUTER/image/{image_id}"),
+            false,
+        )
+        .await;
+
+        let golden = SoftwareImage::golden_for(&mock.dnac, Some(site_id), "286315874")
+            .await
+            .unwrap();
+        assert_eq!(golden.unwrap().id, image_id);
+        let golden = SoftwareImage::golden_for(&mock.dnac, Some(site_id), "286315875")
+            .await
+            .unwrap();
+        assert!(golden.is_none());
+
+        SoftwareImage::tag_golden(
+            &mock.dnac,
+            image_id,
+            Some(site_id),
+            "286315874",
+            Some(DeviceRole::Access),
+        )
+        .await
+        .unwrap();
+        SoftwareImage::untag_golden(
+            &mock.dnac,
+            image_id,
+            None,
+            "286315874",
+            Some(DeviceRole::BorderRouter),
+        )
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_distribute_and_activate() {
+        let mock = MockDnac::start().await;
+        mock.mock_task("POST", "/dna/intent/api/v1/image/distribution", true)
+            .await;
+        mock.mock_response(
+            "POST",
+            "/dna/intent/api/v1/image/activation/device",
+            202,
+            fixtures::TASK_INFO,
+        )
+        .await;
+        mock.mock_response(
+            "GET",
+            "/dna/intent/api/v1/image/task",
+            200,
+            r#"
+              {"response":[{"taskUuid":"0193739c-0d88-78e4-ba0f-d82889fca555","deviceId":"3f0ac5d4-6f3d-4b5e-9d2b-5b0f3a3c2f10","deviceIpAddress":"10.10.1.11","taskType":"distribute","taskStatus":"success","startTime":1732811427209,"completionTime":1732811429512},
+                           {"taskUuid":"0193739c-0d88-78e4-ba0f-d82889fca555","deviceId":"8c1e2b7a-2d44-4f0e-a1e3-0d6c9b3e5a21","deviceIpAddress":"10.10.1.12","taskType":"distribute","taskStatus":"failure","startTime":1732811427209}],"version":"1.0"}
+              "#,
+        )
+        .await;
+
+        let device_id: DeviceId = "3f0ac5d4-6f3d-4b5e-9d2b-5b0f3a3c2f10".parse().unwrap();
+        let image_id: ImageId = "4b5e9d2b-5b0f-4a3c-9f10-3f0ac5d46f3d".parse().unwrap();
+
+        // the task failed for one device, the results are returned anyway
+        let results = SoftwareImage::distribute(
+            &mock.dnac,
+            &[DeviceImagePair {
+                device_id,
+                image_id,
+            }],
+        )
+        .await
+        .unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].device_id, Some(device_id));
+        assert_eq!(results[0].task_status, ImageTaskStatus::Success);
+        assert_eq!(results[1].task_status, ImageTaskStatus::Failure);
+
+        let request = ActivationRequest {
+            device_id,
+            image_ids: vec![image_id],
+            activate_lower_image_version: false,
+            distribute_if_needed: true,
+        };
+        let results = SoftwareImage::activate(&mock.dnac, &[request])
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_inventory_readiness() {
+        let mock = MockDnac::start().await;
+        let device_id: DeviceId = "3f0ac5d4-6f3d-4b5e-9d2b-5b0f3a3c2f10".parse().unwrap();
+        mock.mock_response(
+            "GET",
+            &format!("/dna/intent/api/v1/network-device/{device_id}"),
+            200,
+            r#"
+              {"response":{"id":"3f0ac5d4-6f3d-4b5e-9d2b-5b0f3a3c2f10","collectionStatus":"Managed","managementIpAddress":"10.10.1.11","hostname":"zrh-acc-sw01","softwareVersion":"17.9.4a","reachabilityStatus":"Reachable","reachabilityFailureReason":""},"version":"1.0"}
+              "#,
+        )
+        .await;
+        mock.mock_response(
+            "GET",
+            &format!("/dna/intent/api/v1/compliance/{device_id}/detail"),
+            200,
+            r#"
+              {"response":[{"deviceUuid":"3f0ac5d4-6f3d-4b5e-9d2b-5b0f3a3c2f10","complianceType":"IMAGE","status":"NON_COMPLIANT","state":"SUCCESS","lastSyncTime":1732811427209}],"version":"1.0"}
+              "#,
+        )
+        .await;
+
+        let readiness = SoftwareImage::inventory_readiness(&mock.dnac, device_id)
+            .await
+            .unwrap();
+        assert!(readiness.is_ready());
+        assert!(readiness.needs_upgrade);
+        assert_eq!(readiness.running_version.as_deref(), Some("17.9.4a"));
+        assert_eq!(readiness.failed_checks().count(), 0);
+    }
+}
@@ -0,0 +1,255 @@
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use futures::{stream, StreamExt};
+use tracing::{event, Level};
+
+use crate::dnac::{check_task_tree, ApiClient, ResponseType, StatusError, Task, TaskInfo};
+
+// transient errors in a row after which a task is reported as failed
+const MAX_TRANSIENT_ERRORS: u32 = 5;
+// the interval is doubled after a round with transient errors, up to this factor
+const MAX_BACKOFF_FACTOR: u32 = 8;
+
+// TaskPoller drives many pending tasks at once. Instead of every task
+// polling on its own schedule, all pending tasks are checked together once
+// per interval with a bounded number of concurrent requests, any configured
+// rate limit of the client applies on top.
+pub struct TaskPoller<'a, C> {
+    dnac: &'a C,
+    interval: Duration,
+    max_concurrency: usize,
+    tasks: Vec<TaskInfo>,
+}
+
+// TaskHandle identifies a task added to a TaskPoller
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TaskHandle(usize);
+
+// the state of a task after one check
+enum Check {
+    Finished(Result<Vec<Task>>),
+    Running,
+    // the status couldn't be read, e.g. because of a 429 or a connection
+    // error, the task is checked again in the next round
+    Transient(anyhow::Error),
+}
+
+#[derive(Debug)]
+pub struct TaskOutcome {
+    pub handle: TaskHandle,
+    pub task_info: TaskInfo,
+    // the finished task tree or the reason the task failed
+    pub result: Result<Vec<Task>>,
+}
+
+impl<'a, C: ApiClient> TaskPoller<'a, C> {
+    pub fn new(dnac: &'a C) -> Self {
+        Self {
+            dnac,
+            interval: Duration::from_secs(5),
+            max_concurrency: 10,
+            tasks: vec![],
+        }
+    }
+
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    pub fn with_max_concurrency(mut self, max_concurrency: usize) -> Self {
+        self.max_concurrency = max_concurrency.max(1);
+        self
+    }
+
+    pub fn add(&mut self, task_info: TaskInfo) -> TaskHandle {
+        self.tasks.push(task_info);
+        TaskHandle(self.tasks.len() - 1)
+    }
+
+    pub fn len(&self) -> usize {
+        self.tasks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tasks.is_empty()
+    }
+
+    // polls until every task finished, the outcomes are in the order the
+    // tasks were added
+    pub async fn run(self) -> Vec<TaskOutcome> {
+        let mut outcomes: Vec<Option<Result<Vec<Task>>>> =
+            self.tasks.iter().map(|_| None).collect();
+        let mut transient_errors = vec![0; self.tasks.len()];
+        let mut backoff = 1;
+
+        loop {
+            let pending: Vec<usize> = (0..self.tasks.len())
+                .filter(|i| outcomes[*i].is_none())
+                .collect();
+            if pending.is_empty() {
+                break;
+            }
+
+            event!(Level::DEBUG, "Polling {} pending tasks", pending.len());
+            let poller = &self;
+            let results: Vec<(usize, Check)> = stream::iter(pending)
+                .map(|i| async move { (i, poller.check(&poller.tasks[i]).await) })
+                .buffer_unordered(self.max_concurrency)
+                .collect()
+                .await;
+
+            let mut finished = true;
+            let mut throttled = false;
+            for (i, check) in results {
+                match check {
+                    Check::Finished(result) => outcomes[i] = Some(result),
+                    Check::Running => {
+                        transient_errors[i] = 0;
+                        finished = false;
+                    }
+                    Check::Transient(e) => {
+                        transient_errors[i] += 1;
+                        if transient_errors[i] >= MAX_TRANSIENT_ERRORS {
+                            outcomes[i] = Some(Err(e));
+                        } else {
+                            event!(Level::WARN, "Checking task failed, retrying: {e}");
+                            throttled = true;
+                            finished = false;
+                        }
+                    }
+                }
+            }
+
+            backoff = if throttled {
+                (backoff * 2).min(MAX_BACKOFF_FACTOR)
+            } else {
+                1
+            };
+            if !finished {
+                tokio::time::sleep(self.interval * backoff).await;
+            }
+        }
+
+        self.tasks
+            .into_iter()
+            .zip(outcomes)
+            .enumerate()
+            .map(|(i, (task_info, result))| TaskOutcome {
+                handle: TaskHandle(i),
+                task_info,
+                result: result.expect("all tasks finished"),
+            })
+            .collect()
+    }
+
+    async fn check(&self, task_info: &TaskInfo) -> Check {
+        let url = format!("{}/tree/", task_info.url);
+        let tasks = match self.dnac.get::<Task>(&url, None, None).await {
+            Ok(response) => match response.response {
+                ResponseType::Array(tasks) => tasks,
+                _ => return Check::Finished(Err(anyhow!("Unexpected response"))),
+            },
+            Err(e) if is_transient(&e) => return Check::Transient(e),
+            Err(e) => return Check::Finished(Err(e)),
+        };
+
+        match check_task_tree(&tasks) {
+            Ok(true) => Check::Finished(Ok(tasks)),
+            Ok(false) => Check::Running,
+            Err(e) => Check::Finished(Err(e)),
+        }
+    }
+}
+
+// rate limited, unavailable and connection errors say nothing about the
+// task, anything else means the status can't be read at all
+fn is_transient(e: &anyhow::Error) -> bool {
+    if let Some(e) = e.downcast_ref::<StatusError>() {
+        return matches!(e.status.as_u16(), 429 | 502 | 503 | 504);
+    }
+    e.downcast_ref::<reqwest::Error>()
+        .is_some_and(|e| e.is_connect() || e.is_timeout() || e.is_request())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{fixtures, MockDnac};
+
+    #[tokio::test]
+    async fn test_task_poller() {
+        let mock = MockDnac::start().await;
+        mock.mock_task("POST", "/dna/intent/api/v1/network-device", false)
+            .await;
+        mock.mock_response(
+            "GET",
+            "/api/v1/task/failed-task/tree/",
+            200,
+            fixtures::TASK_TREE_ERROR,
+        )
+        .await;
+
+        let mut poller = TaskPoller::new(&mock.dnac).with_interval(Duration::from_millis(10));
+        let ok = poller.add(TaskInfo {
+            task_id: fixtures::TASK_ID.to_string(),
+            url: format!("/api/v1/task/{}", fixtures::TASK_ID),
+        });
+        let failed = poller.add(TaskInfo {
+            task_id: "failed-task".to_string(),
+            url: "/api/v1/task/failed-task".to_string(),
+        });
+
+        let outcomes = poller.run().await;
+        assert_eq!(outcomes[0].handle, ok);
+        assert!(outcomes[0].result.is_ok());
+        assert_eq!(outcomes[1].handle, failed);
+        assert!(outcomes[1].result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_task_poller_rate_limited() {
+        use wiremock::{
+            matchers::{method, path},
+            Mock, ResponseTemplate,
+        };
+
+        let mock = MockDnac::start().await;
+        let tree = format!("/api/v1/task/{}/tree/", fixtures::TASK_ID);
+        // the limited responses take precedence until they are used up
+        Mock::given(method("GET"))
+            .and(path(tree.as_str()))
+            .respond_with(ResponseTemplate::new(429))
+            .up_to_n_times(2)
+            .with_priority(1)
+            .mount(&mock.server)
+            .await;
+        mock.mock_response("GET", &tree, 200, fixtures::TASK_TREE)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/task/throttled-task/tree/"))
+            .respond_with(ResponseTemplate::new(429))
+            .mount(&mock.server)
+            .await;
+
+        let mut poller = TaskPoller::new(&mock.dnac).with_interval(Duration::from_millis(1));
+        poller.add(TaskInfo {
+            task_id: fixtures::TASK_ID.to_string(),
+            url: format!("/api/v1/task/{}", fixtures::TASK_ID),
+        });
+        poller.add(TaskInfo {
+            task_id: "throttled-task".to_string(),
+            url: "/api/v1/task/throttled-task".to_string(),
+        });
+
+        let outcomes = poller.run().await;
+        assert!(outcomes[0].result.is_ok());
+        // a task which stays throttled fails once the retries are used up
+        let e = outcomes[1].result.as_ref().unwrap_err();
+        assert_eq!(
+            e.downcast_ref::<StatusError>().unwrap().status.as_u16(),
+            429
+        );
+    }
+}
@@ -0,0 +1,181 @@
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::{event, Level};
+
+use crate::{
+    dnac::{ApiClient, ResponseType},
+    ids::SiteId,
+    query::Query,
+    sites::Sites,
+};
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TelemetrySettings {
+    pub wired_data_collection: Option<WiredDataCollection>,
+    pub wireless_telemetry: Option<WirelessTelemetry>,
+    pub snmp_traps: Option<SnmpTraps>,
+    pub syslogs: Option<Syslogs>,
+    pub application_visibility: Option<ApplicationVisibility>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WiredDataCollection {
+    pub enable_wired_data_collection: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WirelessTelemetry {
+    pub enable_wireless_telemetry: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnmpTraps {
+    pub use_builtin_trap_server: bool,
+    #[serde(default)]
+    pub external_trap_servers: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Syslogs {
+    pub use_builtin_syslog_server: bool,
+    #[serde(default)]
+    pub external_syslog_servers: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApplicationVisibility {
+    pub collector: Option<NetflowCollector>,
+    pub enable_on_wired_access_devices: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetflowCollector {
+    pub collector_type: String,
+    pub address: Option<String>,
+    pub port: Option<u16>,
+}
+
+// Collectors is a flat view of where a site sends its telemetry to
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Collectors {
+    pub syslog: Vec<String>,
+    pub snmp_trap: Vec<String>,
+    pub netflow: Vec<String>,
+}
+
+#[derive(Debug, Error)]
+pub enum TelemetryError {
+    #[error("General Telemetry Error")]
+    GeneralError,
+}
+
+impl TelemetrySettings {
+    // inherited returns the effective settings including the ones inherited
+    // from parent sites, otherwise only the settings defined on the site itself
+    pub async fn get<C: ApiClient>(
+        dnac: &C,
+        site_id: SiteId,
+        inherited: bool,
+    ) -> Result<Self, TelemetryError> {
+        let path = format!("/dna/intent/api/v1/sites/{site_id}/telemetrySettings");
+        let query = Query::new().eq("_inherited", inherited);
+
+        let settings_data = dnac
+            .get::<TelemetrySettings>(path.as_str(), Some(query.as_slice()), None)
+            .await;
+
+        match settings_data {
+            Ok(settings_data) => match settings_data.response {
+                ResponseType::Item(data) => Ok(data),
+                ResponseType::Array(_) => Err(TelemetryError::GeneralError),
+            },
+            Err(e) => {
+                event!(Level::ERROR, "{e}");
+                Err(TelemetryError::GeneralError)
+            }
+        }
+    }
+
+    // fetches the effective settings of all sites, devices use the settings
+    // of the site they are assigned to
+    pub async fn get_all<C: ApiClient>(
+        dnac: &C,
+    ) -> Result<Vec<(SiteId, TelemetrySettings)>, TelemetryError> {
+        let sites = Sites::get_all_sites(dnac, None).await.map_err(|e| {
+            event!(Level::ERROR, "{e}");
+            TelemetryError::GeneralError
+        })?;
+
+        let mut settings = vec![];
+        for site in sites {
+            settings.push((site.id, TelemetrySettings::get(dnac, site.id, true).await?));
+        }
+
+        Ok(settings)
+    }
+
+    pub fn collectors(&self) -> Collectors {
+        let mut collectors = Collectors::default();
+
+        if let Some(syslogs) = &self.syslogs {
+            if syslogs.use_builtin_syslog_server {
+                collectors.syslog.push("builtin".to_string());
+            }
+            collectors
+                .syslog
+                .extend(syslogs.external_syslog_servers.iter().cloned());
+        }
+
+        if let Some(snmp_traps) = &self.snmp_traps {
+            if snmp_traps.use_builtin_trap_server {
+                collectors.snmp_trap.push("builtin".to_string());
+            }
+            collectors
+                .snmp_trap
+                .extend(snmp_traps.external_trap_servers.iter().cloned());
+        }
+
+        if let Some(collector) = self
+            .application_visibility
+            .as_ref()
+            .and_then(|a| a.collector.as_ref())
+        {
+            match (&collector.address, collector.port) {
+                (Some(address), Some(port)) => collectors.netflow.push(format!("{address}:{port}")),
+                (Some(address), None) => collectors.netflow.push(address.clone()),
+                _ => collectors.netflow.push(collector.collector_type.clone()),
+            }
+        }
+
+        collectors
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collectors() {
+        let settings = r#"
+          {"snmpTraps":{"useBuiltinTrapServer":true,"externalTrapServers":["10.1.1.1"]},"syslogs":{"useBuiltinSyslogServer":false,"externalSyslogServers":["10.1.1.2"]},"applicationVisibility":{"collector":{"collectorType":"TelemetryBrokerOrUDPDirector","address":"10.1.1.3","port":2055},"enableOnWiredAccessDevices":true}}
+          "#;
+
+        let settings: TelemetrySettings = serde_json::from_str(settings).unwrap();
+        assert_eq!(
+            settings.collectors(),
+            Collectors {
+                syslog: vec!["10.1.1.2".to_string()],
+                snmp_trap: vec!["builtin".to_string(), "10.1.1.1".to_string()],
+                netflow: vec!["10.1.1.3:2055".to_string()],
+            }
+        );
+    }
+}
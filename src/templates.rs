@@ -0,0 +1,844 @@
+use std::collections::HashMap;
+
+use anyhow::anyhow;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use thiserror::Error;
+use tracing::{event, Level};
+use uuid::Uuid;
+
+use crate::{
+    dnac::{ApiClient, ApiClientExt, Task},
+    ids::{DeviceId, ProjectId, TemplateId},
+    query::Query,
+};
+
+// Project groups the configuration templates of the template programmer
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Project {
+    pub id: ProjectId,
+    pub name: String,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub templates: Vec<ProjectTemplate>,
+    #[serde(default)]
+    pub is_deletable: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectTemplate {
+    pub id: TemplateId,
+    pub name: String,
+    #[serde(default)]
+    pub composite: bool,
+}
+
+// TemplateInfo is the summary returned when listing templates
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplateInfo {
+    #[serde(rename = "templateId")]
+    pub id: TemplateId,
+    pub name: String,
+    pub project_id: ProjectId,
+    pub project_name: String,
+    #[serde(default)]
+    pub composite: bool,
+    #[serde(default)]
+    pub versions_info: Vec<TemplateVersion>,
+}
+
+// a committed version of a template, version_time is in epoch milliseconds
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplateVersion {
+    pub id: TemplateId,
+    pub version: Option<String>,
+    pub version_comment: Option<String>,
+    pub version_time: Option<u64>,
+    pub author: Option<String>,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Template {
+    pub id: TemplateId,
+    pub name: String,
+    pub project_id: ProjectId,
+    pub project_name: String,
+    pub description: Option<String>,
+    // e.g. IOS-XE or NX-OS
+    pub software_type: String,
+    pub software_variant: Option<String>,
+    pub software_version: Option<String>,
+    #[serde(default)]
+    pub device_types: Vec<TemplateDeviceType>,
+    pub template_content: Option<String>,
+    #[serde(default)]
+    pub template_params: Vec<TemplateParam>,
+    pub language: TemplateLanguage,
+    // the latest committed version, None if the template was never committed
+    pub version: Option<String>,
+    #[serde(default)]
+    pub composite: bool,
+    pub last_update_time: Option<u64>,
+}
+
+// the devices a template can be deployed to, only the family is required
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplateDeviceType {
+    pub product_family: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub product_series: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub product_type: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplateParam {
+    pub parameter_name: String,
+    pub data_type: Option<TemplateParamType>,
+    pub default_value: Option<String>,
+    pub description: Option<String>,
+    pub display_name: Option<String>,
+    #[serde(default)]
+    pub required: bool,
+    // variables of the template which aren't parameters, e.g. loop variables
+    #[serde(default)]
+    pub not_param: bool,
+    pub order: Option<u32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum TemplateParamType {
+    String,
+    Integer,
+    Ipaddress,
+    Macaddress,
+    Sectiondivider,
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum TemplateLanguage {
+    Velocity,
+    Jinja,
+}
+
+// NewTemplate is the content of a template for create and update, the
+// template has to be committed with Template::version before it can be deployed
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewTemplate {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub software_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub software_variant: Option<String>,
+    pub device_types: Vec<TemplateDeviceType>,
+    pub template_content: String,
+    pub template_params: Vec<TemplateParam>,
+    pub language: TemplateLanguage,
+}
+
+// the update replaces the whole template, so the id is sent with the content
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct UpdateTemplate<'a> {
+    id: TemplateId,
+    project_id: ProjectId,
+    #[serde(flatten)]
+    template: &'a NewTemplate,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CommitTemplate<'a> {
+    template_id: TemplateId,
+    comments: &'a str,
+}
+
+// DeployRequest deploys the committed version of a template, every target
+// gets its own parameter values
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeployRequest {
+    pub template_id: TemplateId,
+    // pushes the configuration even if it was already deployed to the device
+    pub force_push_template: bool,
+    pub is_composite: bool,
+    pub target_info: Vec<DeployTarget>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeployTarget {
+    // the device id, ip or hostname depending on the type
+    pub id: String,
+    #[serde(rename = "type")]
+    pub target_type: DeployTargetType,
+    pub params: HashMap<String, Value>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum DeployTargetType {
+    ManagedDeviceUuid,
+    ManagedDeviceIp,
+    ManagedDeviceHostname,
+    PreProvision,
+}
+
+// DeploymentStatus is the progress of a deployment and of each of its devices
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeploymentStatus {
+    pub deployment_id: Uuid,
+    pub status: DeploymentState,
+    pub status_message: Option<String>,
+    pub template_name: Option<String>,
+    pub template_version: Option<String>,
+    #[serde(default)]
+    pub devices: Vec<DeviceDeployment>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceDeployment {
+    pub device_id: Option<DeviceId>,
+    pub name: Option<String>,
+    pub ip_address: Option<String>,
+    pub status: DeploymentState,
+    pub detailed_status_message: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum DeploymentState {
+    Init,
+    InProgress,
+    Success,
+    Failure,
+    #[serde(other)]
+    Unknown,
+}
+
+// TemplatePreview is the configuration a deploy would push to the device
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplatePreview {
+    pub cli_preview: Option<String>,
+    pub device_id: Option<DeviceId>,
+    pub template_id: Option<TemplateId>,
+    pub validation_errors: Option<Value>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PreviewRequest<'a> {
+    template_id: TemplateId,
+    device_id: DeviceId,
+    params: &'a HashMap<String, Value>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TemplateFilter {
+    pub software_type: Option<String>,
+    pub product_family: Option<String>,
+    pub product_series: Option<String>,
+    pub product_type: Option<String>,
+    // only templates which have changes that weren't committed yet
+    pub un_committed: Option<bool>,
+}
+
+#[derive(Debug, Error)]
+pub enum TemplateError {
+    #[error("General Template Error")]
+    GeneralError,
+}
+
+// the template programmer doesn't wrap its responses in a response field
+async fn get_template_data<C, T>(
+    dnac: &C,
+    path: &str,
+    query: Option<&[(&str, String)]>,
+) -> Result<T, TemplateError>
+where
+    C: ApiClient,
+    T: serde::de::DeserializeOwned,
+{
+    let template_data = dnac.get_raw(path, query, None).await;
+
+    match template_data {
+        Ok(template_data) => serde_json::from_value(template_data).map_err(|e| {
+            event!(Level::ERROR, "{e}");
+            TemplateError::GeneralError
+        }),
+        Err(e) => {
+            event!(Level::ERROR, "{e}");
+            Err(TemplateError::GeneralError)
+        }
+    }
+}
+
+impl Project {
+    pub async fn list<C: ApiClient>(dnac: &C) -> Result<Vec<Project>, TemplateError> {
+        let path = "/dna/intent/api/v1/template-programmer/project";
+        get_template_data(dnac, path, None).await
+    }
+
+    // exports the projects together with their templates, the result can
+    // be passed to import on the same or another cluster
+    pub async fn export<C: ApiClient>(
+        dnac: &C,
+        project_names: &[&str],
+    ) -> anyhow::Result<Vec<Value>> {
+        let path = "/dna/intent/api/v1/template-programmer/project/name/exportprojects";
+        let task_info = dnac.post_task(path, project_names).await?;
+        let tasks = dnac.poll_task(task_info).await?;
+
+        exported_data(&tasks)
+    }
+
+    // imports projects as returned by export, do_version commits the
+    // imported templates right away
+    pub async fn import<C: ApiClient>(
+        dnac: &C,
+        payload: &[Value],
+        do_version: bool,
+    ) -> anyhow::Result<()> {
+        let path = format!(
+            "/dna/intent/api/v1/template-programmer/project/importprojects?doVersion={do_version}"
+        );
+        dnac.post(path.as_str(), payload, true).await
+    }
+}
+
+// the export tasks return the exported json in their data
+fn exported_data(tasks: &[Task]) -> anyhow::Result<Vec<Value>> {
+    tasks
+        .iter()
+        .find_map(|t| serde_json::from_str(t.data.as_deref()?).ok())
+        .ok_or_else(|| anyhow!("Export task didn't return any data"))
+}
+
+impl Template {
+    // project_id limits the result to the templates of that project
+    pub async fn list<C: ApiClient>(
+        dnac: &C,
+        project_id: Option<ProjectId>,
+        filter: Option<&TemplateFilter>,
+    ) -> Result<Vec<TemplateInfo>, TemplateError> {
+        let path = "/dna/intent/api/v1/template-programmer/template";
+        let query = filter
+            .map(TemplateFilter::to_query)
+            .unwrap_or_default()
+            .eq_opt("projectId", project_id);
+
+        get_template_data(dnac, path, Some(query.as_slice())).await
+    }
+
+    pub async fn get<C: ApiClient>(
+        dnac: &C,
+        template_id: TemplateId,
+    ) -> Result<Template, TemplateError> {
+        let path = format!("/dna/intent/api/v1/template-programmer/template/{template_id}");
+        get_template_data(dnac, path.as_str(), None).await
+    }
+
+    // creates the template in the project and returns its id
+    pub async fn create<C: ApiClient>(
+        dnac: &C,
+        project_id: ProjectId,
+        template: &NewTemplate,
+    ) -> anyhow::Result<TemplateId> {
+        let path = format!("/dna/intent/api/v1/template-programmer/project/{project_id}/template");
+        let task_info = dnac.post_task(path.as_str(), template).await?;
+        let tasks = dnac.poll_task(task_info).await?;
+
+        // the task reports the id of the new template in its data
+        tasks
+            .iter()
+            .find_map(|t| t.data.as_deref().and_then(|d| d.parse().ok()))
+            .ok_or_else(|| anyhow!("Template task didn't return the template id"))
+    }
+
+    pub async fn update<C: ApiClient>(
+        dnac: &C,
+        project_id: ProjectId,
+        template_id: TemplateId,
+        template: &NewTemplate,
+    ) -> anyhow::Result<()> {
+        let path = "/dna/intent/api/v1/template-programmer/template";
+        let request = UpdateTemplate {
+            id: template_id,
+            project_id,
+            template,
+        };
+        dnac.put(path, request, true).await
+    }
+
+    // commits the current content as new version
+    pub async fn version<C: ApiClient>(
+        dnac: &C,
+        template_id: TemplateId,
+        commit_comment: &str,
+    ) -> anyhow::Result<()> {
+        let path = "/dna/intent/api/v1/template-programmer/template/version";
+        let request = CommitTemplate {
+            template_id,
+            comments: commit_comment,
+        };
+        dnac.post(path, request, true).await
+    }
+
+    // exports the templates as json, e.g. as backup or to import them on
+    // another cluster
+    pub async fn export<C: ApiClient>(
+        dnac: &C,
+        template_ids: &[TemplateId],
+    ) -> anyhow::Result<Vec<Value>> {
+        let path = "/dna/intent/api/v1/template-programmer/template/name/exportTemplates";
+        let task_info = dnac.post_task(path, template_ids).await?;
+        let tasks = dnac.poll_task(task_info).await?;
+
+        exported_data(&tasks)
+    }
+
+    // deploys the template and returns the deployment id, the devices are
+    // configured in the background, see deployment_status
+    pub async fn deploy<C: ApiClient>(dnac: &C, request: DeployRequest) -> anyhow::Result<Uuid> {
+        let path = "/dna/intent/api/v2/template-programmer/template/deploy";
+        let task_info = dnac.post_task(path, request).await?;
+        let tasks = dnac.poll_task(task_info).await?;
+
+        // the progress ends with the id, e.g. "Template Deployemnt Id: <id>"
+        tasks
+            .iter()
+            .find_map(|t| t.progress.split_whitespace().last()?.parse().ok())
+            .ok_or_else(|| anyhow!("Deploy task didn't return the deployment id"))
+    }
+
+    pub async fn deployment_status<C: ApiClient>(
+        dnac: &C,
+        deployment_id: Uuid,
+    ) -> Result<DeploymentStatus, TemplateError> {
+        let path = format!(
+            "/dna/intent/api/v1/template-programmer/template/deploy/status/{deployment_id}"
+        );
+        get_template_data(dnac, path.as_str(), None).await
+    }
+
+    // renders the template for the device without deploying it
+    pub async fn preview<C: ApiClientExt>(
+        dnac: &C,
+        template_id: TemplateId,
+        params: &HashMap<String, Value>,
+        device_id: DeviceId,
+    ) -> Result<TemplatePreview, TemplateError> {
+        let path = "/dna/intent/api/v1/template-programmer/template/preview";
+        let request = PreviewRequest {
+            template_id,
+            device_id,
+            params,
+        };
+
+        match dnac.put_query(path, request).await {
+            Ok(preview_data) => serde_json::from_value(preview_data).map_err(|e| {
+                event!(Level::ERROR, "{e}");
+                TemplateError::GeneralError
+            }),
+            Err(e) => {
+                event!(Level::ERROR, "{e}");
+                Err(TemplateError::GeneralError)
+            }
+        }
+    }
+
+    // the parameters which have to be provided on deploy
+    pub fn required_params(&self) -> impl Iterator<Item = &TemplateParam> {
+        self.template_params
+            .iter()
+            .filter(|p| p.required && !p.not_param && p.default_value.is_none())
+    }
+}
+
+impl From<Template> for NewTemplate {
+    fn from(template: Template) -> Self {
+        Self {
+            name: template.name,
+            description: template.description,
+            software_type: template.software_type,
+            software_variant: template.software_variant,
+            device_types: template.device_types,
+            template_content: template.template_content.unwrap_or_default(),
+            template_params: template.template_params,
+            language: template.language,
+        }
+    }
+}
+
+impl DeployTarget {
+    pub fn device(device_id: DeviceId, params: HashMap<String, Value>) -> Self {
+        Self {
+            id: device_id.to_string(),
+            target_type: DeployTargetType::ManagedDeviceUuid,
+            params,
+        }
+    }
+}
+
+impl DeploymentStatus {
+    pub fn is_finished(&self) -> bool {
+        matches!(
+            self.status,
+            DeploymentState::Success | DeploymentState::Failure
+        )
+    }
+
+    pub fn succeeded(&self) -> impl Iterator<Item = &DeviceDeployment> {
+        self.devices
+            .iter()
+            .filter(|d| d.status == DeploymentState::Success)
+    }
+
+    pub fn failed(&self) -> impl Iterator<Item = &DeviceDeployment> {
+        self.devices
+            .iter()
+            .filter(|d| d.status == DeploymentState::Failure)
+    }
+}
+
+impl TemplatePreview {
+    pub fn has_errors(&self) -> bool {
+        match &self.validation_errors {
+            Some(Value::Array(errors)) => !errors.is_empty(),
+            Some(Value::Object(errors)) => !errors.is_empty(),
+            Some(Value::Null) | None => false,
+            Some(_) => true,
+        }
+    }
+
+    // the rendered lines which aren't in the config yet, e.g. the running
+    // config of the device, whitespace at the line ends is ignored
+    pub fn new_lines<'a>(&'a self, config: &str) -> Vec<&'a str> {
+        let existing: std::collections::HashSet<&str> = config.lines().map(str::trim_end).collect();
+        self.cli_preview
+            .as_deref()
+            .unwrap_or_default()
+            .lines()
+            .map(str::trim_end)
+            .filter(|l| !l.is_empty() && !existing.contains(l))
+            .collect()
+    }
+}
+
+impl TemplateFilter {
+    pub fn to_query(&self) -> Query<'static> {
+        Query::new()
+            .eq_opt("softwareType", self.software_type.as_ref())
+            .eq_opt("productFamily", self.product_family.as_ref())
+            .eq_opt("productSeries", self.product_series.as_ref())
+            .eq_opt("productType", self.product_type.as_ref())
+            .eq_opt("unCommitted", self.un_committed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{fixtures, MockDnac};
+
+    #[tokio::test]
+    async fn test_list_templates() {
+        let mock = MockDnac::start().await;
+        mock.mock_response(
+            "GET",
+            "/dna/intent/api/v1/template-programmer/project",
+            200,
+            r#"
+              [{"name":"Onboarding Configuration","id":"6f3d4b5e-9d2b-4b0f-8a3c-2f103f0ac5d4","description":"Day-0 templates","isDeletable":false,
+                "templates":[{"name":"access-switch-day0","composite":false,"id":"3a3c2f10-3f0a-4c5d-86f3-d4b5e9d2b5b0"}]}]
+              "#,
+        )
+        .await;
+        mock.mock_response(
+            "GET",
+            "/dna/intent/api/v1/template-programmer/template",
+            200,
+            r#"
+              [{"name":"access-switch-day0","projectName":"Onboarding Configuration","projectId":"6f3d4b5e-9d2b-4b0f-8a3c-2f103f0ac5d4","templateId":"3a3c2f10-3f0a-4c5d-86f3-d4b5e9d2b5b0","composite":false,
+                "versionsInfo":[{"id":"c5d46f3d-4b5e-4d2b-9b0f-3a3c2f103f0a","author":"admin","version":"1","versionComment":"initial","versionTime":1732811427209}]}]
+              "#,
+        )
+        .await;
+
+        let projects = Project::list(&mock.dnac).await.unwrap();
+        assert_eq!(projects.len(), 1);
+        assert_eq!(projects[0].templates[0].name, "access-switch-day0");
+
+        let filter = TemplateFilter {
+            software_type: Some("IOS-XE".to_string()),
+            ..Default::default()
+        };
+        let templates = Template::list(&mock.dnac, Some(projects[0].id), Some(&filter))
+            .await
+            .unwrap();
+        assert_eq!(templates[0].id, projects[0].templates[0].id);
+        assert_eq!(templates[0].versions_info[0].version.as_deref(), Some("1"));
+    }
+
+    #[tokio::test]
+    async fn test_get_template() {
+        let mock = MockDnac::start().await;
+        let template_id: TemplateId = "3a3c2f10-3f0a-4c5d-86f3-d4b5e9d2b5b0".parse().unwrap();
+        mock.mock_response(
+            "GET",
+            &format!("/dna/intent/api/v1/template-programmer/template/{template_id}"),
+            200,
+            r#"
+              {"name":"access-switch-day0","id":"3a3c2f10-3f0a-4c5d-86f3-d4b5e9d2b5b0","projectName":"Onboarding Configuration","projectId":"6f3d4b5e-9d2b-4b0f-8a3c-2f103f0ac5d4",
+               "softwareType":"IOS-XE","softwareVariant":"XE","deviceTypes":[{"productFamily":"Switches and Hubs","productSeries":"Cisco Catalyst 9300 Series Switches"}],
+               "templateContent":"hostname $hostname\nvlan $vlan","language":"VELOCITY","composite":false,"version":"1","lastUpdateTime":1732811427209,
+               "templateParams":[{"parameterName":"hostname","dataType":"STRING","required":true,"notParam":false,"order":1},
+                                 {"parameterName":"vlan","dataType":"INTEGER","defaultValue":"10","required":true,"notParam":false,"order":2}]}
+              "#,
+        )
+        .await;
+
+        let template = Template::get(&mock.dnac, template_id).await.unwrap();
+        assert_eq!(template.language, TemplateLanguage::Velocity);
+        assert_eq!(template.device_types[0].product_family, "Switches and Hubs");
+        assert_eq!(
+            template.template_params[1].data_type,
+            Some(TemplateParamType::Integer)
+        );
+        let required: Vec<&str> = template
+            .required_params()
+            .map(|p| p.parameter_name.as_str())
+            .collect();
+        assert_eq!(required, vec!["hostname"]);
+    }
+
+    #[tokio::test]
+    async fn test_create_and_commit_template() {
+        let mock = MockDnac::start().await;
+        let project_id: ProjectId = "6f3d4b5e-9d2b-4b0f-8a3c-2f103f0ac5d4".parse().unwrap();
+        mock.mock_response(
+            "POST",
+            &format!("/dna/intent/api/v1/template-programmer/project/{project_id}/template"),
+            202,
+            fixtures::TASK_INFO,
+        )
+        .await;
+        mock.mock_response(
+            "GET",
+            &format!("/api/v1/task/{}/tree/", fixtures::TASK_ID),
+            200,
+            r#"
+              {"response":[{"id":"0193739c-0d88-78e4-ba0f-d82889fca555","progress":"Successfully created template with name access-switch-day0","data":"3a3c2f10-3f0a-4c5d-86f3-d4b5e9d2b5b0","startTime":1732811427209,"endTime":1732811429512,"serviceType":"NCTP","isError":false,"instanceTenantId":"6307971e4289f95403c86831","version":1732811427309}]}
+              "#,
+        )
+        .await;
+        mock.mock_response(
+            "PUT",
+            "/dna/intent/api/v1/template-programmer/template",
+            202,
+            fixtures::TASK_INFO,
+        )
+        .await;
+        mock.mock_response(
+            "POST",
+            "/dna/intent/api/v1/template-programmer/template/version",
+            202,
+            fixtures::TASK_INFO,
+        )
+        .await;
+
+        let mut template = NewTemplate {
+            name: "access-switch-day0".to_string(),
+            description: None,
+            software_type: "IOS-XE".to_string(),
+            software_variant: None,
+            device_types: vec![TemplateDeviceType {
+                product_family: "Switches and Hubs".to_string(),
+                product_series: None,
+                product_type: None,
+            }],
+            template_content: "hostname $hostname".to_string(),
+            template_params: vec![],
+            language: TemplateLanguage::Velocity,
+        };
+        let template_id = Template::create(&mock.dnac, project_id, &template)
+            .await
+            .unwrap();
+        assert_eq!(
+            template_id,
+            "3a3c2f10-3f0a-4c5d-86f3-d4b5e9d2b5b0".parse().unwrap()
+        );
+
+        template.template_content = "hostname $hostname\nvlan 10".to_string();
+        Template::update(&mock.dnac, project_id, template_id, &template)
+            .await
+            .unwrap();
+        Template::version(&mock.dnac, template_id, "add vlan 10")
+            .await
+            .unwrap();
+
+        let requests = mock.server.received_requests().await.unwrap();
+        let update = requests
+            .iter()
+            .find(|r| r.method.as_str() == "PUT")
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&update.body).unwrap();
+        assert_eq!(body["id"], template_id.to_string());
+        assert_eq!(body["language"], "VELOCITY");
+    }
+
+    #[tokio::test]
+    async fn test_deploy_template() {
+        let mock = MockDnac::start().await;
+        mock.mock_response(
+            "POST",
+            "/dna/intent/api/v2/template-programmer/template/deploy",
+            202,
+            fixtures::TASK_INFO,
+        )
+        .await;
+        mock.mock_response(
+            "GET",
+            &format!("/api/v1/task/{}/tree/", fixtures::TASK_ID),
+            200,
+            r#"
+              {"response":[{"id":"0193739c-0d88-78e4-ba0f-d82889fca555","progress":"Template Deployemnt Id: 2d444f0e-a1e3-4d6c-9b3e-5a218c1e2b7a","startTime":1732811427209,"endTime":1732811429512,"serviceType":"NCTP","isError":false,"instanceTenantId":"6307971e4289f95403c86831","version":1732811427309}]}
+              "#,
+        )
+        .await;
+        mock.mock_response(
+            "GET",
+            "/dna/intent/api/v1/template-programmer/template/deploy/status/2d444f0e-a1e3-4d6c-9b3e-5a218c1e2b7a",
+            200,
+            r#"
+              {"deploymentId":"2d444f0e-a1e3-4d6c-9b3e-5a218c1e2b7a","deploymentName":"access-switch-day0","status":"FAILURE","statusMessage":"Deployment failed on 1 device","templateName":"access-switch-day0","templateVersion":"1",
+               "devices":[{"deviceId":"3f0ac5d4-6f3d-4b5e-9d2b-5b0f3a3c2f10","name":"zrh-acc-sw01","ipAddress":"10.10.1.11","status":"SUCCESS","detailedStatusMessage":"Provisioning success"},
+                          {"deviceId":"8c1e2b7a-2d44-4f0e-a1e3-0d6c9b3e5a21","name":"zrh-acc-sw02","ipAddress":"10.10.1.12","status":"FAILURE","detailedStatusMessage":"Device unreachable"}]}
+              "#,
+        )
+        .await;
+
+        let device_id: DeviceId = "3f0ac5d4-6f3d-4b5e-9d2b-5b0f3a3c2f10".parse().unwrap();
+        let request = DeployRequest {
+            template_id: "3a3c2f10-3f0a-4c5d-86f3-d4b5e9d2b5b0".parse().unwrap(),
+            force_push_template: false,
+            is_composite: false,
+            target_info: vec![DeployTarget::device(
+                device_id,
+                HashMap::from([("hostname".to_string(), Value::from("zrh-acc-sw01"))]),
+            )],
+        };
+        let deployment_id = Template::deploy(&mock.dnac, request).await.unwrap();
+
+        let status = Template::deployment_status(&mock.dnac, deployment_id)
+            .await
+            .unwrap();
+        assert!(status.is_finished());
+        assert_eq!(
+            status.succeeded().next().unwrap().device_id,
+            Some(device_id)
+        );
+        assert_eq!(
+            status
+                .failed()
+                .next()
+                .unwrap()
+                .detailed_status_message
+                .as_deref(),
+            Some("Device unreachable")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_preview_template() {
+        let mock = MockDnac::start().await;
+        mock.mock_response(
+            "PUT",
+            "/dna/intent/api/v1/template-programmer/template/preview",
+            200,
+            r#"
+              {"cliPreview":"hostname zrh-acc-sw01\nvlan 10\n name users","deviceId":"3f0ac5d4-6f3d-4b5e-9d2b-5b0f3a3c2f10","templateId":"3a3c2f10-3f0a-4c5d-86f3-d4b5e9d2b5b0","validationErrors":[]}
+              "#,
+        )
+        .await;
+
+        let params = HashMap::from([("hostname".to_string(), Value::from("zrh-acc-sw01"))]);
+        let preview = Template::preview(
+            &mock.dnac,
+            "3a3c2f10-3f0a-4c5d-86f3-d4b5e9d2b5b0".parse().unwrap(),
+            &params,
+            "3f0ac5d4-6f3d-4b5e-9d2b-5b0f3a3c2f10".parse().unwrap(),
+        )
+        .await
+        .unwrap();
+
+        assert!(!preview.has_errors());
+        let running = "hostname zrh-acc-sw01\nvlan 10\n";
+        assert_eq!(preview.new_lines(running), vec![" name users"]);
+
+        let requests = mock.server.received_requests().await.unwrap();
+        let body: Value = serde_json::from_slice(&requests.last().unwrap().body).unwrap();
+        assert_eq!(body["params"]["hostname"], "zrh-acc-sw01");
+    }
+
+    #[tokio::test]
+    async fn test_export_and_import() {
+        let mock = MockDnac::start().await;
+        mock.mock_response(
+            "POST",
+            "/dna/intent/api/v1/template-programmer/template/name/exportTemplates",
+            202,
+            fixtures::TASK_INFO,
+        )
+        .await;
+        mock.mock_response(
+            "GET",
+            &format!("/api/v1/task/{}/tree/", fixtures::TASK_ID),
+            200,
+            r#"
+              {"response":[{"id":"0193739c-0d88-78e4-ba0f-d82889fca555","progress":"Successfully exported templates","data":"[{\"name\":\"access-switch-day0\",\"projectName\":\"Onboarding Configuration\",\"language\":\"VELOCITY\",\"templateContent\":\"hostname $hostname\"}]","startTime":1732811427209,"endTime":1732811429512,"serviceType":"NCTP","isError":false,"instanceTenantId":"6307971e4289f95403c86831","version":1732811427309}]}
+              "#,
+        )
+        .await;
+        mock.mock_response(
+            "POST",
+            "/dna/intent/api/v1/template-programmer/project/importprojects",
+            202,
+            fixtures::TASK_INFO,
+        )
+        .await;
+
+        let template_id: TemplateId = "3a3c2f10-3f0a-4c5d-86f3-d4b5e9d2b5b0".parse().unwrap();
+        let templates = Template::export(&mock.dnac, &[template_id]).await.unwrap();
+        assert_eq!(templates.len(), 1);
+        assert_eq!(templates[0]["name"], "access-switch-day0");
+
+        let project =
+            serde_json::json!({"name": "Onboarding Configuration", "templates": templates});
+        Project::import(&mock.dnac, &[project], true).await.unwrap();
+
+        let requests = mock.server.received_requests().await.unwrap();
+        let import = requests
+            .iter()
+            .find(|r| r.url.path().ends_with("importprojects"))
+            .unwrap();
+        assert_eq!(import.url.query(), Some("doVersion=true"));
+    }
+}
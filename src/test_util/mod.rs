@@ -0,0 +1,211 @@
+use serde_json::Value;
+use wiremock::{
+    matchers::{method, path},
+    Mock, MockServer, ResponseTemplate,
+};
+
+use crate::{
+    cache::ResponseCache,
+    dnac::{Middleware, PaginationPolicy, Token, DNAC},
+    metrics::Metrics,
+    rate_limit::RateLimiter,
+};
+
+// fixtures are realistic responses captured from a Catalyst Center with
+// anonymized data, they can be used with MockDnac or on their own
+pub mod fixtures {
+    pub const DEVICES: &str = include_str!("fixtures/devices.json");
+    pub const SITES: &str = include_str!("fixtures/sites.json");
+    pub const TASK_INFO: &str = include_str!("fixtures/task_info.json");
+    pub const TASK_TREE: &str = include_str!("fixtures/task_tree.json");
+    pub const TASK_TREE_ERROR: &str = include_str!("fixtures/task_tree_error.json");
+    pub const API_ERROR: &str = include_str!("fixtures/api_error.json");
+    pub const RELEASE_SUMMARY: &str = include_str!("fixtures/release_summary.json");
+    pub const EXECUTION_INFO: &str = include_str!("fixtures/execution_info.json");
+    pub const EXECUTION_STATUS: &str = include_str!("fixtures/execution_status.json");
+    pub const EXECUTION_STATUS_ERROR: &str = include_str!("fixtures/execution_status_error.json");
+
+    pub const TASK_ID: &str = "0193739c-0d88-78e4-ba0f-d82889fca555";
+    pub const EXECUTION_ID: &str = "5e2f1a9c-7d34-4b8e-a0c1-3f6d8e2b9a47";
+}
+
+// MockDnac runs a local HTTP server which answers like a Catalyst Center,
+// the dnac field is a client connected to it which can be passed to all
+// module functions
+pub struct MockDnac {
+    pub server: MockServer,
+    pub dnac: DNAC,
+}
+
+impl MockDnac {
+    // starts the server with the release summary mounted, so version checks pass
+    pub async fn start() -> Self {
+        let server = MockServer::start().await;
+
+        let dnac = DNAC {
+            client: reqwest::Client::new(),
+            token: Token::default(),
+            token_file: String::new(),
+            dnac: server.uri(),
+            user: "admin".to_string(),
+            password: "password".to_string(),
+            pagination_policy: PaginationPolicy::default(),
+            middleware: Middleware::default(),
+            metrics: Metrics::default(),
+            cache: ResponseCache::default(),
+            rate_limiter: RateLimiter::default(),
+            token_key: None,
+            version: Some("2.3.7.6".to_string()),
+        };
+
+        let mock = Self { server, dnac };
+        mock.mock_response(
+            "GET",
+            "/dna/intent/api/v1/dnac-release",
+            200,
+            fixtures::RELEASE_SUMMARY,
+        )
+        .await;
+
+        mock
+    }
+
+    // mounts a raw json body for the given method and path
+    pub async fn mock_response(&self, http_method: &str, url_path: &str, status: u16, body: &str) {
+        let body: Value = serde_json::from_str(body).expect("Invalid fixture json");
+        Mock::given(method(http_method))
+            .and(path(url_path))
+            .respond_with(ResponseTemplate::new(status).set_body_json(body))
+            .mount(&self.server)
+            .await;
+    }
+
+    pub async fn mock_devices(&self) {
+        self.mock_response(
+            "GET",
+            "/dna/intent/api/v1/network-device",
+            200,
+            fixtures::DEVICES,
+        )
+        .await;
+    }
+
+    pub async fn mock_sites(&self) {
+        self.mock_response("GET", "/dna/intent/api/v2/site", 200, fixtures::SITES)
+            .await;
+    }
+
+    // mounts the given write endpoint to return the fixture task which is
+    // already finished, failed selects the task tree with an error
+    pub async fn mock_task(&self, http_method: &str, url_path: &str, failed: bool) {
+        self.mock_response(http_method, url_path, 202, fixtures::TASK_INFO)
+            .await;
+
+        let tree = if failed {
+            fixtures::TASK_TREE_ERROR
+        } else {
+            fixtures::TASK_TREE
+        };
+        self.mock_response(
+            "GET",
+            &format!("/api/v1/task/{}/tree/", fixtures::TASK_ID),
+            200,
+            tree,
+        )
+        .await;
+    }
+
+    // like mock_task, for the business APIs which return an execution
+    pub async fn mock_execution(&self, http_method: &str, url_path: &str, failed: bool) {
+        self.mock_response(http_method, url_path, 202, fixtures::EXECUTION_INFO)
+            .await;
+
+        let status = if failed {
+            fixtures::EXECUTION_STATUS_ERROR
+        } else {
+            fixtures::EXECUTION_STATUS
+        };
+        self.mock_response(
+            "GET",
+            &format!(
+                "/dna/platform/management/business-api/v1/execution-status/{}",
+                fixtures::EXECUTION_ID
+            ),
+            200,
+            status,
+        )
+        .await;
+    }
+
+    // mounts the fixture api error, which is returned with status 500 like DNAC does
+    pub async fn mock_error(&self, http_method: &str, url_path: &str) {
+        self.mock_response(http_method, url_path, 500, fixtures::API_ERROR)
+            .await;
+    }
+}
+
+#[cfg(all(test, feature = "sites"))]
+mod tests {
+    use super::*;
+    use crate::{
+        devices::{AddDevice, Device, DeviceRole},
+        sites::{SiteError, Sites},
+    };
+
+    #[tokio::test]
+    async fn test_mock_dnac() {
+        let mock = MockDnac::start().await;
+        mock.mock_devices().await;
+        mock.mock_task("POST", "/dna/intent/api/v1/network-device", false)
+            .await;
+        mock.mock_error("GET", "/dna/intent/api/v2/site").await;
+
+        let devices = Device::get_device_list(&mock.dnac, None, None)
+            .await
+            .unwrap();
+        assert_eq!(devices.len(), 3);
+
+        Device::add_device(&mock.dnac, AddDevice::default())
+            .await
+            .unwrap();
+
+        let device_id = devices[0].id;
+        mock.mock_task(
+            "DELETE",
+            &format!("/dna/intent/api/v1/network-device/{device_id}"),
+            false,
+        )
+        .await;
+        Device::delete(&mock.dnac, device_id, true).await.unwrap();
+
+        mock.mock_task("PUT", "/dna/intent/api/v1/network-device/sync", false)
+            .await;
+        let task_info = Device::sync(&mock.dnac, &[device_id], true).await.unwrap();
+        assert_eq!(task_info.task_id, fixtures::TASK_ID);
+        let requests = mock.server.received_requests().await.unwrap();
+        let sync = requests
+            .iter()
+            .find(|r| r.url.path() == "/dna/intent/api/v1/network-device/sync")
+            .unwrap();
+        assert_eq!(sync.url.query(), Some("forceSync=true"));
+
+        mock.mock_task("PUT", "/dna/intent/api/v1/network-device/brief", false)
+            .await;
+        Device::update_role(&mock.dnac, device_id, DeviceRole::Access)
+            .await
+            .unwrap();
+
+        mock.mock_task(
+            "PUT",
+            &format!("/dna/intent/api/v1/network-device/{device_id}/user-defined-field"),
+            false,
+        )
+        .await;
+        Device::set_udf(&mock.dnac, device_id, "owner", "netops")
+            .await
+            .unwrap();
+
+        let sites = Sites::get_site(&mock.dnac, None, None).await;
+        assert!(matches!(sites, Err(SiteError::InvalidSite)));
+    }
+}
@@ -0,0 +1,110 @@
+use anyhow::{anyhow, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chacha20poly1305::{
+    aead::{Aead, AeadCore, KeyInit, OsRng},
+    ChaCha20Poly1305, Key, Nonce,
+};
+use serde::{Deserialize, Serialize};
+
+// TokenKey provides the 32 byte key the token file is encrypted with
+pub trait TokenKey: Send + Sync {
+    fn key(&self) -> Result<[u8; 32]>;
+}
+
+// EnvKey reads the base64 encoded key from the environment variable
+pub struct EnvKey(pub String);
+
+// KeyringKey keeps the key in the keyring of the OS, a new key is generated
+// and stored on first use
+#[cfg(feature = "keyring")]
+pub struct KeyringKey {
+    pub service: String,
+    pub user: String,
+}
+
+// EncryptedToken is the format of an encrypted token file, the version allows
+// changing the algorithm later
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct EncryptedToken {
+    version: u8,
+    nonce: String,
+    ciphertext: String,
+}
+
+impl TokenKey for EnvKey {
+    fn key(&self) -> Result<[u8; 32]> {
+        let key = std::env::var(&self.0).map_err(|_| anyhow!("Missing '{}' env var", self.0))?;
+        decode_key(&key)
+    }
+}
+
+#[cfg(feature = "keyring")]
+impl TokenKey for KeyringKey {
+    fn key(&self) -> Result<[u8; 32]> {
+        let entry = keyring::Entry::new(&self.service, &self.user)?;
+        match entry.get_password() {
+            Ok(key) => decode_key(&key),
+            Err(keyring::Error::NoEntry) => {
+                let key: [u8; 32] = ChaCha20Poly1305::generate_key(&mut OsRng).into();
+                entry.set_password(&STANDARD.encode(key))?;
+                Ok(key)
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+impl EncryptedToken {
+    pub(crate) fn encrypt(key: &[u8; 32], plaintext: &[u8]) -> Result<Self> {
+        let cipher = ChaCha20Poly1305::new(Key::from_slice(key));
+        let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = cipher
+            .encrypt(&nonce, plaintext)
+            .map_err(|_| anyhow!("Token encryption failed"))?;
+
+        Ok(Self {
+            version: 1,
+            nonce: STANDARD.encode(nonce),
+            ciphertext: STANDARD.encode(ciphertext),
+        })
+    }
+
+    pub(crate) fn decrypt(&self, key: &[u8; 32]) -> Result<Vec<u8>> {
+        if self.version != 1 {
+            return Err(anyhow!("Unsupported token file version {}", self.version));
+        }
+
+        let nonce = STANDARD.decode(&self.nonce)?;
+        if nonce.len() != 12 {
+            return Err(anyhow!("Invalid nonce in token file"));
+        }
+        let ciphertext = STANDARD.decode(&self.ciphertext)?;
+
+        let cipher = ChaCha20Poly1305::new(Key::from_slice(key));
+        cipher
+            .decrypt(Nonce::from_slice(&nonce), ciphertext.as_slice())
+            .map_err(|_| anyhow!("Token decryption failed, wrong key or modified file"))
+    }
+}
+
+fn decode_key(key: &str) -> Result<[u8; 32]> {
+    STANDARD
+        .decode(key.trim())?
+        .try_into()
+        .map_err(|_| anyhow!("The token key has to be 32 bytes"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encrypt_decrypt() {
+        let key = [7u8; 32];
+        let encrypted = EncryptedToken::encrypt(&key, b"{\"Token\":\"abc\"}").unwrap();
+        assert!(!encrypted.ciphertext.contains("abc"));
+
+        assert_eq!(encrypted.decrypt(&key).unwrap(), b"{\"Token\":\"abc\"}");
+        assert!(encrypted.decrypt(&[8u8; 32]).is_err());
+    }
+}
@@ -0,0 +1,400 @@
+use std::fmt;
+
+use serde::Deserialize;
+use thiserror::Error;
+use tracing::{event, Level};
+
+#[cfg(feature = "sites")]
+use crate::{devices::MembershipDevice, ids::SiteId, sites::Sites};
+use crate::{
+    dnac::{ApiClient, ResponseType},
+    query::Query,
+};
+
+pub struct Topology;
+
+// TopologyGraph is returned by the physical, layer 2 and layer 3 topology,
+// links reference their nodes by id
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TopologyGraph {
+    pub id: Option<String>,
+    #[serde(default)]
+    pub nodes: Vec<TopologyNode>,
+    #[serde(default)]
+    pub links: Vec<TopologyLink>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TopologyNode {
+    pub id: String,
+    pub label: Option<String>,
+    pub ip: Option<String>,
+    pub device_type: Option<String>,
+    pub family: Option<String>,
+    pub role: Option<String>,
+    pub platform_id: Option<String>,
+    pub software_version: Option<String>,
+    // e.g. device or HOST
+    pub node_type: Option<String>,
+    // nodes which aren't managed by DNAC are greyed out
+    #[serde(default)]
+    pub grey_out: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TopologyLink {
+    pub id: Option<String>,
+    pub source: String,
+    pub target: String,
+    pub start_port_name: Option<String>,
+    pub end_port_name: Option<String>,
+    pub start_port_speed: Option<String>,
+    pub end_port_speed: Option<String>,
+    pub link_status: Option<LinkStatus>,
+}
+
+// the enrichment details return the status in upper case
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LinkStatus {
+    #[serde(alias = "UP")]
+    Up,
+    #[serde(alias = "DOWN")]
+    Down,
+    #[serde(other)]
+    Unknown,
+}
+
+// SiteTopology is the site hierarchy as graph, sites reference their parent
+// by id and hold the devices which are assigned to them directly
+#[cfg(feature = "sites")]
+#[derive(Debug, Deserialize)]
+pub struct SiteTopology {
+    #[serde(default)]
+    pub sites: Vec<TopologySite>,
+}
+
+#[cfg(feature = "sites")]
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TopologySite {
+    pub id: SiteId,
+    pub name: String,
+    pub display_name: Option<String>,
+    // empty for Global
+    pub parent_id: Option<String>,
+    pub group_name_hierarchy: Option<String>,
+    // e.g. area, building or floor
+    pub location_type: Option<String>,
+    pub latitude: Option<String>,
+    pub longitude: Option<String>,
+    #[serde(skip)]
+    pub devices: Vec<MembershipDevice>,
+}
+
+// the routing domains of the layer 3 topology
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RoutingProtocol {
+    Ospf,
+    Eigrp,
+    Isis,
+    Static,
+}
+
+impl fmt::Display for RoutingProtocol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self {
+            Self::Ospf => write!(f, "OSPF"),
+            Self::Eigrp => write!(f, "EIGRP"),
+            Self::Isis => write!(f, "ISIS"),
+            Self::Static => write!(f, "STATIC"),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum TopologyError {
+    #[error("General Topology Error")]
+    GeneralError,
+}
+
+async fn get_topology<C: ApiClient>(
+    dnac: &C,
+    path: &str,
+    query: Option<&[(&str, String)]>,
+) -> Result<TopologyGraph, TopologyError> {
+    let topology_data = dnac.get::<TopologyGraph>(path, query, None).await;
+
+    match topology_data {
+        Ok(topology_data) => match topology_data.response {
+            ResponseType::Item(data) => Ok(data),
+            ResponseType::Array(mut data) => data.pop().ok_or(TopologyError::GeneralError),
+        },
+        Err(e) => {
+            event!(Level::ERROR, "{e}");
+            Err(TopologyError::GeneralError)
+        }
+    }
+}
+
+impl Topology {
+    // node_type limits the nodes, e.g. "device" skips the hosts
+    pub async fn physical<C: ApiClient>(
+        dnac: &C,
+        node_type: Option<&str>,
+    ) -> Result<TopologyGraph, TopologyError> {
+        let path = "/dna/intent/api/v1/topology/physical-topology";
+        let query = Query::new().eq_opt("nodeType", node_type);
+
+        get_topology(dnac, path, Some(query.as_slice())).await
+    }
+
+    // the topology of the vlan, the vlan_id is one of vlan_names, e.g. "vlan10"
+    pub async fn layer2<C: ApiClient>(
+        dnac: &C,
+        vlan_id: &str,
+    ) -> Result<TopologyGraph, TopologyError> {
+        let path = format!("/dna/intent/api/v1/topology/l2/{vlan_id}");
+        get_topology(dnac, path.as_str(), None).await
+    }
+
+    pub async fn layer3<C: ApiClient>(
+        dnac: &C,
+        protocol: RoutingProtocol,
+    ) -> Result<TopologyGraph, TopologyError> {
+        let path = format!("/dna/intent/api/v1/topology/l3/{protocol}");
+        get_topology(dnac, path.as_str(), None).await
+    }
+
+    pub async fn vlan_names<C: ApiClient>(dnac: &C) -> Result<Vec<String>, TopologyError> {
+        let path = "/dna/intent/api/v1/topology/vlan/vlan-names";
+
+        let vlan_data = dnac.get::<String>(path, None, None).await;
+
+        match vlan_data {
+            Ok(vlan_data) => match vlan_data.response {
+                ResponseType::Array(data) => Ok(data),
+                ResponseType::Item(data) => Ok(vec![data]),
+            },
+            Err(e) => {
+                event!(Level::ERROR, "{e}");
+                Err(TopologyError::GeneralError)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "sites")]
+impl Topology {
+    // the device membership is fetched once for the root site, it contains
+    // the devices of all child sites
+    pub async fn site<C: ApiClient>(dnac: &C) -> Result<SiteTopology, TopologyError> {
+        let path = "/dna/intent/api/v1/topology/site-topology";
+
+        let mut topology = match dnac.get::<SiteTopology>(path, None, None).await {
+            Ok(topology_data) => match topology_data.response {
+                ResponseType::Item(data) => data,
+                ResponseType::Array(mut data) => data.pop().ok_or(TopologyError::GeneralError)?,
+            },
+            Err(e) => {
+                event!(Level::ERROR, "{e}");
+                return Err(TopologyError::GeneralError);
+            }
+        };
+
+        let Some(root_id) = topology.roots().next().map(|s| s.id) else {
+            return Ok(topology);
+        };
+        let membership = Sites::get_membership(dnac, root_id).await.map_err(|e| {
+            event!(Level::ERROR, "{e}");
+            TopologyError::GeneralError
+        })?;
+        for site_devices in membership.devices {
+            if let Some(site) = topology
+                .sites
+                .iter_mut()
+                .find(|s| s.id == site_devices.site_id)
+            {
+                site.devices = site_devices.devices;
+            }
+        }
+
+        Ok(topology)
+    }
+}
+
+#[cfg(feature = "sites")]
+impl SiteTopology {
+    pub fn get(&self, site_id: SiteId) -> Option<&TopologySite> {
+        self.sites.iter().find(|s| s.id == site_id)
+    }
+
+    pub fn children(&self, site_id: SiteId) -> impl Iterator<Item = &TopologySite> {
+        self.sites
+            .iter()
+            .filter(move |s| s.parent() == Some(site_id))
+    }
+
+    // sites without a parent in the topology, usually only Global
+    pub fn roots(&self) -> impl Iterator<Item = &TopologySite> {
+        self.sites
+            .iter()
+            .filter(|s| s.parent().is_none_or(|parent| self.get(parent).is_none()))
+    }
+}
+
+#[cfg(feature = "sites")]
+impl TopologySite {
+    pub fn parent(&self) -> Option<SiteId> {
+        self.parent_id.as_deref().and_then(|id| id.parse().ok())
+    }
+}
+
+impl TopologyGraph {
+    pub fn node(&self, node_id: &str) -> Option<&TopologyNode> {
+        self.nodes.iter().find(|n| n.id == node_id)
+    }
+
+    // the nodes which are linked to the node, in both directions
+    pub fn neighbors<'a>(&'a self, node_id: &'a str) -> impl Iterator<Item = &'a TopologyNode> {
+        self.links
+            .iter()
+            .filter_map(move |l| match node_id {
+                id if id == l.source => Some(l.target.as_str()),
+                id if id == l.target => Some(l.source.as_str()),
+                _ => None,
+            })
+            .filter_map(|id| self.node(id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::MockDnac;
+
+    #[tokio::test]
+    async fn test_physical() {
+        let mock = MockDnac::start().await;
+        mock.mock_response(
+            "GET",
+            "/dna/intent/api/v1/topology/physical-topology",
+            200,
+            r#"
+              {"response":{"id":"4b5e9d2b-5b0f-4a3c-8f10-3f0ac5d46f3d","nodes":[
+                {"id":"3f0ac5d4-6f3d-4b5e-9d2b-5b0f3a3c2f10","label":"zrh-acc-sw01","ip":"10.10.1.11","deviceType":"Cisco Catalyst 9300 Switch","family":"Switches and Hubs","role":"ACCESS","platformId":"C9300-48P","softwareVersion":"17.9.4a","nodeType":"device","greyOut":false},
+                {"id":"5b0f3a3c-2f10-4f0a-8c5d-46f3d4b5e9d2","label":"zrh-dist-sw01","ip":"10.10.1.1","family":"Switches and Hubs","role":"DISTRIBUTION","nodeType":"device"},
+                {"id":"9d2b5b0f-3a3c-4f10-8f0a-c5d46f3d4b5e","label":"zrh-core-01","ip":"10.10.0.1","nodeType":"device","greyOut":true}],
+               "links":[
+                {"id":"1","source":"3f0ac5d4-6f3d-4b5e-9d2b-5b0f3a3c2f10","target":"5b0f3a3c-2f10-4f0a-8c5d-46f3d4b5e9d2","startPortName":"TenGigabitEthernet1/1/1","endPortName":"TenGigabitEthernet1/0/5","startPortSpeed":"10000000","endPortSpeed":"10000000","linkStatus":"up"},
+                {"id":"2","source":"5b0f3a3c-2f10-4f0a-8c5d-46f3d4b5e9d2","target":"9d2b5b0f-3a3c-4f10-8f0a-c5d46f3d4b5e","linkStatus":"down"}]},
+               "version":"1.0"}
+              "#,
+        )
+        .await;
+
+        let topology = Topology::physical(&mock.dnac, Some("device"))
+            .await
+            .unwrap();
+        assert_eq!(topology.nodes.len(), 3);
+        assert_eq!(topology.links[1].link_status, Some(LinkStatus::Down));
+
+        let neighbors: Vec<&str> = topology
+            .neighbors("5b0f3a3c-2f10-4f0a-8c5d-46f3d4b5e9d2")
+            .filter_map(|n| n.label.as_deref())
+            .collect();
+        assert_eq!(neighbors, vec!["zrh-acc-sw01", "zrh-core-01"]);
+
+        let requests = mock.server.received_requests().await.unwrap();
+        let request = requests
+            .iter()
+            .find(|r| r.url.path().ends_with("physical-topology"))
+            .unwrap();
+        assert_eq!(request.url.query(), Some("nodeType=device"));
+    }
+
+    #[tokio::test]
+    async fn test_layer2_and_layer3() {
+        let mock = MockDnac::start().await;
+        mock.mock_response(
+            "GET",
+            "/dna/intent/api/v1/topology/vlan/vlan-names",
+            200,
+            r#"{"response":["vlan1","vlan10"],"version":"1.0"}"#,
+        )
+        .await;
+        mock.mock_response(
+            "GET",
+            "/dna/intent/api/v1/topology/l2/vlan10",
+            200,
+            r#"
+              {"response":{"id":"vlan10","nodes":[{"id":"3f0ac5d4-6f3d-4b5e-9d2b-5b0f3a3c2f10","label":"zrh-acc-sw01"}],"links":[]},"version":"1.0"}
+              "#,
+        )
+        .await;
+        mock.mock_response(
+            "GET",
+            "/dna/intent/api/v1/topology/l3/OSPF",
+            200,
+            r#"
+              {"response":{"id":"OSPF","nodes":[{"id":"9d2b5b0f-3a3c-4f10-8f0a-c5d46f3d4b5e","label":"zrh-core-01"},{"id":"5b0f3a3c-2f10-4f0a-8c5d-46f3d4b5e9d2","label":"zrh-core-02"}],
+                           "links":[{"source":"9d2b5b0f-3a3c-4f10-8f0a-c5d46f3d4b5e","target":"5b0f3a3c-2f10-4f0a-8c5d-46f3d4b5e9d2","startPortName":"TenGigabitEthernet1/0/1","endPortName":"TenGigabitEthernet1/0/1","linkStatus":"up"}]},
+               "version":"1.0"}
+              "#,
+        )
+        .await;
+
+        let vlans = Topology::vlan_names(&mock.dnac).await.unwrap();
+        assert_eq!(vlans, vec!["vlan1", "vlan10"]);
+
+        let layer2 = Topology::layer2(&mock.dnac, &vlans[1]).await.unwrap();
+        assert_eq!(layer2.nodes.len(), 1);
+
+        let layer3 = Topology::layer3(&mock.dnac, RoutingProtocol::Ospf)
+            .await
+            .unwrap();
+        assert_eq!(layer3.links[0].link_status, Some(LinkStatus::Up));
+    }
+
+    #[cfg(feature = "sites")]
+    #[tokio::test]
+    async fn test_site() {
+        let mock = MockDnac::start().await;
+        mock.mock_response(
+            "GET",
+            "/dna/intent/api/v1/topology/site-topology",
+            200,
+            r#"
+              {"response":{"sites":[
+                {"id":"00000000-0000-4000-8000-000000000001","name":"Global","displayName":"Global","parentId":"","groupNameHierarchy":"Global"},
+                {"id":"a4f3a3c2-f103-4f0a-8c5d-46f3d4b5e9d2","name":"HQ","displayName":"HQ","parentId":"00000000-0000-4000-8000-000000000001","groupNameHierarchy":"Global/HQ","locationType":"building","latitude":"47.3686","longitude":"8.5392"}]},
+               "version":"1.0"}
+              "#,
+        )
+        .await;
+        mock.mock_response(
+            "GET",
+            "/dna/intent/api/v1/membership/00000000-0000-4000-8000-000000000001",
+            200,
+            r#"
+              {"site":{"response":[],"version":"1.0"},
+               "device":[{"response":[{"instanceUuid":"3f0ac5d4-6f3d-4b5e-9d2b-5b0f3a3c2f10","collectionStatus":"Managed","managementIpAddress":"10.10.1.11","hostname":"zrh-acc-sw01","family":"Switches and Hubs"}],"version":"1.0","siteId":"a4f3a3c2-f103-4f0a-8c5d-46f3d4b5e9d2"}]}
+              "#,
+        )
+        .await;
+
+        let topology = Topology::site(&mock.dnac).await.unwrap();
+        let global = topology.roots().next().unwrap();
+        assert_eq!(global.name, "Global");
+
+        let children: Vec<&TopologySite> = topology.children(global.id).collect();
+        assert_eq!(children.len(), 1);
+        assert_eq!(
+            children[0].devices[0].hostname.as_deref(),
+            Some("zrh-acc-sw01")
+        );
+    }
+}
@@ -0,0 +1,367 @@
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::{event, Level};
+
+use crate::{
+    dnac::{ApiClient, ResponseType},
+    query::Query,
+};
+
+pub struct AccessPoint;
+
+#[derive(Debug, Serialize)]
+struct ApRebootRequest<'a> {
+    #[serde(rename = "apMacAddresses")]
+    ap_mac_addresses: &'a [String],
+}
+
+#[derive(Debug, Serialize)]
+struct ApFactoryResetRequest<'a> {
+    #[serde(rename = "keepStaticIPConfig")]
+    keep_static_ip_config: bool,
+    #[serde(rename = "apMacAddresses")]
+    ap_mac_addresses: &'a [String],
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ApRebootStatus {
+    #[serde(rename = "wlcIP")]
+    pub wlc_ip: Option<String>,
+    #[serde(rename = "apList", default)]
+    pub ap_list: Vec<ApRebootResult>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ApRebootResult {
+    #[serde(rename = "apName")]
+    pub ap_name: Option<String>,
+    #[serde(rename = "rebootStatus")]
+    pub reboot_status: Option<String>,
+    #[serde(rename = "failureReason")]
+    pub failure_reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ApFactoryResetStatus {
+    #[serde(rename = "wlcIP")]
+    pub wlc_ip: Option<String>,
+    #[serde(rename = "wlcName")]
+    pub wlc_name: Option<String>,
+    #[serde(rename = "apResponseInfoList", default)]
+    pub ap_response_info_list: Vec<ApFactoryResetResult>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ApFactoryResetResult {
+    #[serde(rename = "apName")]
+    pub ap_name: Option<String>,
+    #[serde(rename = "apFactoryResetStatus")]
+    pub ap_factory_reset_status: Option<String>,
+    #[serde(rename = "failureReason")]
+    pub failure_reason: Option<String>,
+    #[serde(rename = "radioMacAddress")]
+    pub radio_mac_address: Option<String>,
+    #[serde(rename = "ethernetMacAddress")]
+    pub ethernet_mac_address: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AccessPointConfig {
+    #[serde(rename = "apName")]
+    pub ap_name: Option<String>,
+    #[serde(rename = "macAddress")]
+    pub mac_address: Option<String>,
+    #[serde(rename = "ethMac")]
+    pub eth_mac: Option<String>,
+    #[serde(rename = "apMode")]
+    pub ap_mode: Option<ApMode>,
+    #[serde(rename = "adminStatus")]
+    pub admin_status: Option<String>,
+    pub location: Option<String>,
+    #[serde(rename = "failoverPriority")]
+    pub failover_priority: Option<String>,
+    #[serde(rename = "primaryControllerName")]
+    pub primary_controller_name: Option<String>,
+    #[serde(rename = "primaryIpAddress")]
+    pub primary_ip_address: Option<ControllerAddress>,
+    #[serde(rename = "secondaryControllerName")]
+    pub secondary_controller_name: Option<String>,
+    #[serde(rename = "secondaryIpAddress")]
+    pub secondary_ip_address: Option<ControllerAddress>,
+    #[serde(rename = "radioDTOs", default)]
+    pub radios: Vec<RadioSettings>,
+    #[serde(rename = "meshDTOs", default)]
+    pub mesh: Vec<MeshSettings>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ControllerAddress {
+    pub address: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RadioSettings {
+    #[serde(rename = "slotId")]
+    pub slot_id: Option<u8>,
+    #[serde(rename = "macAddress")]
+    pub mac_address: Option<String>,
+    #[serde(rename = "adminStatus")]
+    pub admin_status: Option<String>,
+    // e.g. 2.4 GHz, 5 GHz or 6 GHz
+    #[serde(rename = "radioBand")]
+    pub radio_band: Option<String>,
+    #[serde(rename = "radioRoleAssignment")]
+    pub radio_role_assignment: Option<String>,
+    #[serde(rename = "channelAssignmentMode")]
+    pub channel_assignment_mode: Option<String>,
+    #[serde(rename = "channelNumber")]
+    pub channel_number: Option<u16>,
+    #[serde(rename = "channelWidth")]
+    pub channel_width: Option<String>,
+    #[serde(rename = "powerAssignmentMode")]
+    pub power_assignment_mode: Option<String>,
+    #[serde(rename = "powertxLevel")]
+    pub power_tx_level: Option<u8>,
+    #[serde(rename = "antennaPatternName")]
+    pub antenna_pattern_name: Option<String>,
+    #[serde(rename = "antennaGain")]
+    pub antenna_gain: Option<i16>,
+    #[serde(rename = "cleanAirSI")]
+    pub clean_air_si: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum ApMode {
+    Local,
+    FlexConnect,
+    Bridge,
+    #[serde(rename = "Flex+Bridge")]
+    FlexBridge,
+    Monitor,
+    Sniffer,
+    Sensor,
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum MeshRole {
+    // root APs (RAP) have a wired uplink and bridge the mesh APs to the network
+    #[serde(rename = "ROOT", alias = "RootAP")]
+    Root,
+    // mesh APs (MAP) connect to their parent over the wireless backhaul
+    #[serde(rename = "MESH", alias = "MeshAP")]
+    Mesh,
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MeshSettings {
+    #[serde(rename = "apRole")]
+    pub ap_role: Option<MeshRole>,
+    #[serde(rename = "bridgeGroupName")]
+    pub bridge_group_name: Option<String>,
+    #[serde(rename = "backhaulClientAccess")]
+    pub backhaul_client_access: Option<bool>,
+    #[serde(rename = "ethernetBridging")]
+    pub ethernet_bridging: Option<bool>,
+    #[serde(rename = "rapDownlinkBackhaul")]
+    pub rap_downlink_backhaul: Option<String>,
+    pub range: Option<u32>,
+    #[serde(rename = "ghz5BackhaulDataRates")]
+    pub ghz5_backhaul_data_rates: Option<String>,
+    #[serde(rename = "ghz24BackhaulDataRates")]
+    pub ghz24_backhaul_data_rates: Option<String>,
+}
+
+#[derive(Debug, Error)]
+pub enum WirelessError {
+    #[error("General Wireless Error")]
+    GeneralError,
+    #[error("Task failed")]
+    TaskFailed,
+}
+
+impl AccessPoint {
+    // ap_mac_address is the ethernet MAC address of the AP
+    pub async fn get_config<C: ApiClient>(
+        dnac: &C,
+        ap_mac_address: &str,
+    ) -> Result<AccessPointConfig, WirelessError> {
+        let path = "/dna/intent/api/v1/wireless/accesspoint-configuration/summary";
+        let query = Query::new().eq("key", ap_mac_address);
+
+        let config_data = dnac
+            .get::<AccessPointConfig>(path, Some(query.as_slice()), None)
+            .await;
+
+        match config_data {
+            Ok(config_data) => match config_data.response {
+                ResponseType::Item(data) => Ok(data),
+                ResponseType::Array(mut data) if data.len() == 1 => Ok(data.remove(0)),
+                ResponseType::Array(_) => Err(WirelessError::GeneralError),
+            },
+            Err(e) => {
+                event!(Level::ERROR, "{e}");
+                Err(WirelessError::GeneralError)
+            }
+        }
+    }
+
+    // ap_mac_addresses are the ethernet MAC addresses of the APs to reboot,
+    // the returned status is grouped per WLC
+    pub async fn reboot<C: ApiClient>(
+        dnac: &C,
+        ap_mac_addresses: &[String],
+    ) -> Result<Vec<ApRebootStatus>, WirelessError> {
+        let path = "/dna/intent/api/v1/device-reboot/apreboot";
+        let request = ApRebootRequest { ap_mac_addresses };
+
+        let task_info = dnac.post_task(path, request).await.map_err(|e| {
+            event!(Level::ERROR, "{e}");
+            WirelessError::GeneralError
+        })?;
+        let task_id = task_info.task_id.clone();
+
+        dnac.poll_task(task_info).await.map_err(|e| {
+            event!(Level::ERROR, "{e}");
+            WirelessError::TaskFailed
+        })?;
+
+        AccessPoint::get_reboot_status(dnac, &task_id).await
+    }
+
+    pub async fn get_reboot_status<C: ApiClient>(
+        dnac: &C,
+        parent_task_id: &str,
+    ) -> Result<Vec<ApRebootStatus>, WirelessError> {
+        let path = "/dna/intent/api/v1/device-reboot/apreboot/status";
+        let query = Query::new().eq("parentTaskId", parent_task_id);
+
+        let status_data = dnac
+            .get::<ApRebootStatus>(path, Some(query.as_slice()), None)
+            .await;
+
+        match status_data {
+            Ok(status_data) => match status_data.response {
+                ResponseType::Array(data) => Ok(data),
+                ResponseType::Item(data) => Ok(vec![data]),
+            },
+            Err(e) => {
+                event!(Level::ERROR, "{e}");
+                Err(WirelessError::GeneralError)
+            }
+        }
+    }
+
+    // keep_static_ip_config keeps the static IP configuration of the AP after the reset
+    pub async fn factory_reset<C: ApiClient>(
+        dnac: &C,
+        ap_mac_addresses: &[String],
+        keep_static_ip_config: bool,
+    ) -> Result<Vec<ApFactoryResetStatus>, WirelessError> {
+        let path = "/dna/intent/api/v1/wirelessAccessPoints/factoryResetRequest/provision";
+        let request = ApFactoryResetRequest {
+            keep_static_ip_config,
+            ap_mac_addresses,
+        };
+
+        let task_info = dnac.post_task(path, request).await.map_err(|e| {
+            event!(Level::ERROR, "{e}");
+            WirelessError::GeneralError
+        })?;
+        let task_id = task_info.task_id.clone();
+
+        dnac.poll_task(task_info).await.map_err(|e| {
+            event!(Level::ERROR, "{e}");
+            WirelessError::TaskFailed
+        })?;
+
+        AccessPoint::get_factory_reset_status(dnac, &task_id).await
+    }
+
+    pub async fn get_factory_reset_status<C: ApiClient>(
+        dnac: &C,
+        task_id: &str,
+    ) -> Result<Vec<ApFactoryResetStatus>, WirelessError> {
+        let path = "/dna/intent/api/v1/wirelessAccessPoints/factoryResetRequestStatus";
+        let query = Query::new().eq("taskId", task_id);
+
+        let status_data = dnac
+            .get::<ApFactoryResetStatus>(path, Some(query.as_slice()), None)
+            .await;
+
+        match status_data {
+            Ok(status_data) => match status_data.response {
+                ResponseType::Array(data) => Ok(data),
+                ResponseType::Item(data) => Ok(vec![data]),
+            },
+            Err(e) => {
+                event!(Level::ERROR, "{e}");
+                Err(WirelessError::GeneralError)
+            }
+        }
+    }
+}
+
+impl AccessPointConfig {
+    // bridge mode APs take part in a mesh, either as root or as mesh AP
+    pub fn is_mesh(&self) -> bool {
+        matches!(self.ap_mode, Some(ApMode::Bridge | ApMode::FlexBridge))
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.admin_status.as_deref() == Some("Enabled")
+    }
+
+    pub fn primary_controller_ip(&self) -> Option<&str> {
+        self.primary_ip_address.as_ref()?.address.as_deref()
+    }
+
+    pub fn secondary_controller_ip(&self) -> Option<&str> {
+        self.secondary_ip_address.as_ref()?.address.as_deref()
+    }
+
+    pub fn mesh_role(&self) -> Option<MeshRole> {
+        self.mesh.iter().find_map(|m| m.ap_role)
+    }
+
+    pub fn bridge_group_name(&self) -> Option<&str> {
+        self.mesh
+            .iter()
+            .find_map(|m| m.bridge_group_name.as_deref())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_mesh_ap() {
+        let config = r#"
+          {"apName":"out-ap-01","ethMac":"00:11:22:33:44:55","apMode":"Flex+Bridge","meshDTOs":[{"apRole":"MESH","bridgeGroupName":"yard","ethernetBridging":true}]}
+          "#;
+
+        let config: AccessPointConfig = serde_json::from_str(config).unwrap();
+        assert!(config.is_mesh());
+        assert_eq!(config.mesh_role(), Some(MeshRole::Mesh));
+        assert_eq!(config.bridge_group_name(), Some("yard"));
+    }
+
+    #[test]
+    fn test_parse_ap_config() {
+        let config = r#"
+          {"apName":"ap-2f-01","ethMac":"00:11:22:33:44:66","macAddress":"aa:bb:cc:dd:ee:00","apMode":"Local","adminStatus":"Enabled","primaryControllerName":"wlc-01","primaryIpAddress":{"address":"10.1.1.10"},"secondaryControllerName":"wlc-02","secondaryIpAddress":{"address":"10.1.2.10"},"radioDTOs":[{"slotId":0,"adminStatus":"Enabled","radioBand":"2.4 GHz","channelNumber":6,"channelAssignmentMode":"Global","powertxLevel":3},{"slotId":1,"adminStatus":"Enabled","radioBand":"5 GHz","channelNumber":36,"channelWidth":"40 MHz"}]}
+          "#;
+
+        let config: AccessPointConfig = serde_json::from_str(config).unwrap();
+        assert!(config.is_enabled());
+        assert!(!config.is_mesh());
+        assert_eq!(config.primary_controller_ip(), Some("10.1.1.10"));
+        assert_eq!(config.secondary_controller_name.as_deref(), Some("wlc-02"));
+        assert_eq!(config.radios.len(), 2);
+        assert_eq!(config.radios[1].channel_number, Some(36));
+    }
+}